@@ -68,6 +68,7 @@ const generateRustModels = (schema) => {
   const dataStructs = [];
   const eventDataVariants = [];
   const fromImplementations = [];
+  const variantImplementations = [];
   const deserializeMatchArms = [];
   const serializeMatchArms = [];
   for (const name of schema.allOf[1].oneOf.map((x) =>
@@ -90,6 +91,19 @@ const generateRustModels = (schema) => {
         }
       }
     `);
+    variantImplementations.push(`
+      impl EventDataVariant for ${name}Data {
+        const EVENT_TYPE: &'static str = "${eventType}";
+        const DATA_VERSION: &'static str = "${dataVersion}";
+
+        fn from_event_data(data: EventData) -> Option<Self> {
+          match data {
+            EventData::${name}(data) => Some(data),
+            _ => None,
+          }
+        }
+      }
+    `);
     deserializeMatchArms.push(`
       ("${eventType}", "${dataVersion}") => Ok(Self::${name}(
         serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
@@ -105,6 +119,7 @@ const generateRustModels = (schema) => {
     // DO NOT MODIFY IT BY HAND. Instead, modify the source JSONSchema file,
     // and run yarn generate to regenerate this file.
 
+    use super::EventDataVariant;
     use chrono::{DateTime, Utc};
     use serde::{Deserialize, Serialize};
 
@@ -117,6 +132,8 @@ const generateRustModels = (schema) => {
 
     ${fromImplementations.join("")}
 
+    ${variantImplementations.join("")}
+
     pub(crate) enum EventDataDeserializationError {
       Json(serde_json::Error),
       UnknownVariant,
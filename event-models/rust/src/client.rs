@@ -1,8 +1,9 @@
-use crate::Event;
+use crate::{Event, EventGridPublisher, FanOutPublisher};
 use async_trait::async_trait;
-use std::sync::{mpsc::SyncSender, Arc};
-use tracing::info_span;
-use tracing_futures::Instrument as _;
+use std::{
+    sync::{mpsc::SyncSender, Arc},
+    time::Duration,
+};
 
 #[async_trait]
 pub trait EventPublisher: std::fmt::Debug {
@@ -17,18 +18,35 @@ pub struct EventClient {
 
 impl EventClient {
     pub fn new(topic_hostname: String, topic_key: String) -> Self {
+        Self::with_event_grid(EventGridPublisher::new(&topic_hostname, topic_key))
+    }
+
+    /// Publishes to EventGrid with a publisher that has been configured beyond the defaults of
+    /// `new`, e.g. with a different retry policy.
+    pub fn with_event_grid(publisher: EventGridPublisher) -> Self {
         EventClient {
-            publisher: Arc::new(EventGridPublisher {
-                url: format!(
-                    "https://{}/api/events?api-version=2018-01-01",
-                    topic_hostname
-                ),
-                key: topic_key,
-            }),
+            publisher: Arc::new(publisher),
             is_configured: true,
         }
     }
 
+    /// Publishes to all of the publishers, e.g. EventGrid and a webhook, at the same time (see
+    /// `FanOutPublisher`). Without any publishers, events are not published and the client is
+    /// not configured, like the default client.
+    pub fn with_publishers(mut publishers: Vec<Arc<dyn EventPublisher + Send + Sync>>) -> Self {
+        match publishers.len() {
+            0 => Self::default(),
+            1 => EventClient {
+                publisher: publishers.remove(0),
+                is_configured: true,
+            },
+            _ => EventClient {
+                publisher: Arc::new(FanOutPublisher::new(publishers)),
+                is_configured: true,
+            },
+        }
+    }
+
     pub fn is_configured(&self) -> bool {
         self.is_configured
     }
@@ -68,43 +86,6 @@ impl EventPublisher for SyncSender<Event> {
     }
 }
 
-#[derive(Debug)]
-struct EventGridPublisher {
-    url: String,
-    key: String,
-}
-
-#[async_trait]
-impl EventPublisher for EventGridPublisher {
-    /// Publish events to Azure EventGrid.
-    ///
-    /// See also: https://docs.microsoft.com/en-us/rest/api/eventgrid/dataplane/publishevents/publishevents
-    async fn publish_events<'a>(&'a self, events: &'a [Event]) -> Result<(), PublishEventsError> {
-        let span = info_span!(
-            "POST /api/events",
-            otel.kind = "client",
-            http.method = "GET",
-            http.url = self.url.as_str(),
-            http.status_code = tracing::field::Empty,
-            http.status_text = tracing::field::Empty
-        );
-
-        let res = surf::post(&self.url)
-            .header("aeg-sas-key", self.key.as_str())
-            .body(surf::Body::from_json(&events)?)
-            .instrument(span.clone())
-            .await?;
-
-        span.record("http.status_code", &u16::from(res.status()));
-
-        if res.status() == 200 {
-            Ok(())
-        } else {
-            Err(PublishEventsError::Server(u16::from(res.status())))
-        }
-    }
-}
-
 #[derive(Debug)]
 struct NoopPublisher {}
 
@@ -118,7 +99,42 @@ impl EventPublisher for NoopPublisher {
 #[derive(Debug)]
 pub enum PublishEventsError {
     Internal(String),
+    /// The request could not be sent or the response could not be received
+    Connection(String),
+    /// There was no response within the timeout
+    Timeout,
+    /// Too many requests were sent, and the server may say how long to wait before the next one
+    Throttled {
+        retry_after: Option<Duration>,
+    },
+    /// The server rejected the credentials (401 or 403)
+    Unauthorized(u16),
+    /// An event or a request was too large for the server
+    PayloadTooLarge,
+    /// Any other unsuccessful status
     Server(u16),
+    /// The request succeeded, but the server could not accept some of the events
+    Rejected {
+        reason: String,
+        transient: bool,
+    },
+    /// An event does not match schema.json (see `ValidatingPublisher`)
+    Invalid(String),
+}
+
+impl PublishEventsError {
+    /// Whether trying again later may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Connection(_) | Self::Timeout | Self::Throttled { .. } => true,
+            Self::Server(status) => *status >= 500,
+            Self::Rejected { transient, .. } => *transient,
+            Self::Internal(_)
+            | Self::Unauthorized(_)
+            | Self::PayloadTooLarge
+            | Self::Invalid(_) => false,
+        }
+    }
 }
 
 impl From<serde_json::Error> for PublishEventsError {
@@ -129,7 +145,7 @@ impl From<serde_json::Error> for PublishEventsError {
 
 impl From<surf::Error> for PublishEventsError {
     fn from(err: surf::Error) -> Self {
-        Self::Internal(format!("{}", err))
+        Self::Connection(format!("{}", err))
     }
 }
 
@@ -139,7 +155,17 @@ impl std::fmt::Display for PublishEventsError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Internal(err) => write!(f, "Internal: {}", err),
+            Self::Connection(err) => write!(f, "Connection: {}", err),
+            Self::Timeout => write!(f, "Timeout"),
+            Self::Throttled {
+                retry_after: Some(retry_after),
+            } => write!(f, "Throttled: retry after {:?}", retry_after),
+            Self::Throttled { retry_after: None } => write!(f, "Throttled"),
+            Self::Unauthorized(status) => write!(f, "Unauthorized: {}", status),
+            Self::PayloadTooLarge => write!(f, "Payload too large"),
             Self::Server(status) => write!(f, "Server: {}", status),
+            Self::Rejected { reason, .. } => write!(f, "Rejected: {}", reason),
+            Self::Invalid(err) => write!(f, "Invalid: {}", err),
         }
     }
 }
@@ -0,0 +1,239 @@
+use crate::{http, Event, EventPublisher, EventSchema, PublishEventsError, RetryPolicy};
+use async_trait::async_trait;
+use std::time::Duration;
+use tracing::info_span;
+
+/// EventGrid rejects requests larger than 1 MB.
+///
+/// See also: https://docs.microsoft.com/en-us/azure/event-grid/quotas-limits
+pub const MAX_REQUEST_SIZE: usize = 1024 * 1024;
+
+/// Publishes events to an Azure EventGrid topic.
+///
+/// Events are sent in as few requests as fit in `MAX_REQUEST_SIZE`, in order. If a request
+/// fails, the events in the requests before it have been published, and the rest have not.
+#[derive(Clone)]
+pub struct EventGridPublisher {
+    url: String,
+    key: String,
+    schema: EventSchema,
+    retry_policy: RetryPolicy,
+    timeout: Duration,
+    max_request_size: usize,
+}
+
+impl EventGridPublisher {
+    pub fn new(topic_hostname: &str, topic_key: String) -> Self {
+        Self {
+            url: format!(
+                "https://{}/api/events?api-version=2018-01-01",
+                topic_hostname
+            ),
+            key: topic_key,
+            schema: EventSchema::EventGrid,
+            retry_policy: RetryPolicy::default(),
+            timeout: Duration::from_secs(30),
+            max_request_size: MAX_REQUEST_SIZE,
+        }
+    }
+
+    /// Sets the schema of the topic's input, which is the EventGrid schema by default.
+    pub fn with_schema(mut self, schema: EventSchema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets how long to wait for each request, including each retry. The default is 30 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn send(&self, body: &[u8]) -> Result<(), PublishEventsError> {
+        let span = info_span!(
+            "POST /api/events",
+            otel.kind = "client",
+            http.method = "POST",
+            http.url = self.url.as_str(),
+            http.status_code = tracing::field::Empty,
+            http.status_text = tracing::field::Empty
+        );
+
+        let request = surf::post(&self.url)
+            .header("aeg-sas-key", self.key.as_str())
+            .content_type(self.schema.batch_content_type())
+            .body(body.to_vec());
+        http::send(request, span, self.timeout).await?;
+
+        Ok(())
+    }
+}
+
+// Not derived, so that the key does not end up in logs.
+impl std::fmt::Debug for EventGridPublisher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventGridPublisher")
+            .field("url", &self.url)
+            .field("schema", &self.schema)
+            .field("retry_policy", &self.retry_policy)
+            .field("timeout", &self.timeout)
+            .field("max_request_size", &self.max_request_size)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl EventPublisher for EventGridPublisher {
+    /// Publish events to Azure EventGrid.
+    ///
+    /// See also: https://docs.microsoft.com/en-us/rest/api/eventgrid/dataplane/publishevents/publishevents
+    async fn publish_events<'a>(&'a self, events: &'a [Event]) -> Result<(), PublishEventsError> {
+        for body in request_bodies(events, &self.schema, self.max_request_size)? {
+            self.retry_policy.retry(|| self.send(&body)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes the events into as few JSON arrays as possible that are at most `max_size` bytes
+/// each, keeping them in order.
+pub(crate) fn request_bodies(
+    events: &[Event],
+    schema: &EventSchema,
+    max_size: usize,
+) -> Result<Vec<Vec<u8>>, PublishEventsError> {
+    let mut bodies = vec![];
+    let mut body = b"[".to_vec();
+    for event in events {
+        let event = schema.to_vec(event)?;
+        // The event, the closing bracket and, unless it is the first one, a comma.
+        if event.len() + 2 > max_size {
+            return Err(PublishEventsError::PayloadTooLarge);
+        }
+        if body.len() > 1 && body.len() + event.len() + 2 > max_size {
+            body.push(b']');
+            bodies.push(body);
+            body = b"[".to_vec();
+        }
+        if body.len() > 1 {
+            body.push(b',');
+        }
+        body.extend(event);
+    }
+    if body.len() > 1 {
+        body.push(b']');
+        bodies.push(body);
+    }
+
+    Ok(bodies)
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::{
+        http::test::{fake_server, fast_retries},
+        FolderCreatedData,
+    };
+
+    pub(crate) fn event() -> Event {
+        Event::new(
+            "subject",
+            FolderCreatedData {
+                folder_id: "folder".into(),
+                workspace_id: "workspace".into(),
+                user_id: "user".into(),
+                title: "title".into(),
+                description: "description".into(),
+                role_required: "PLATFORM_MEMBER".into(),
+                parent_folder_id: None,
+            },
+        )
+    }
+
+    #[test]
+    fn request_bodies_are_json_arrays_of_at_most_max_size() {
+        let events = vec![event(), event(), event()];
+        let event_size = serde_json::to_vec(&events[0]).unwrap().len();
+
+        let bodies = request_bodies(&events, &EventSchema::EventGrid, 2 * event_size + 3).unwrap();
+
+        assert_eq!(bodies.len(), 2);
+        let mut published = vec![];
+        for body in bodies {
+            assert!(body.len() <= 2 * event_size + 3);
+            published.extend(serde_json::from_slice::<Vec<Event>>(&body).unwrap());
+        }
+        assert_eq!(published, events);
+    }
+
+    #[test]
+    fn request_bodies_rejects_an_event_larger_than_max_size() {
+        let result = request_bodies(&[event()], &EventSchema::EventGrid, 10);
+
+        assert!(matches!(result, Err(PublishEventsError::PayloadTooLarge)));
+    }
+
+    #[test]
+    fn request_bodies_of_no_events_is_empty() {
+        assert!(
+            request_bodies(&[], &EventSchema::EventGrid, MAX_REQUEST_SIZE)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    fn publisher(url: String) -> EventGridPublisher {
+        EventGridPublisher {
+            url,
+            ..EventGridPublisher::new("localhost", "key".into()).with_retry_policy(fast_retries())
+        }
+    }
+
+    #[async_std::test]
+    async fn publish_events_retries_transient_errors() {
+        let url = fake_server(
+            "/api/events",
+            &[
+                ("503 Service Unavailable", ""),
+                ("429 Too Many Requests", ""),
+                ("200 OK", ""),
+            ],
+            None,
+        )
+        .await;
+
+        let result = publisher(url).publish_events(&[event()]).await;
+
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[async_std::test]
+    async fn publish_events_gives_up_after_max_retries() {
+        let url = fake_server("/api/events", &[("500 Internal Server Error", ""); 3], None).await;
+
+        let result = publisher(url).publish_events(&[event()]).await;
+
+        assert!(matches!(result, Err(PublishEventsError::Server(500))));
+    }
+
+    #[async_std::test]
+    async fn publish_events_does_not_retry_auth_failures() {
+        let url = fake_server(
+            "/api/events",
+            &[("401 Unauthorized", ""), ("200 OK", "")],
+            None,
+        )
+        .await;
+
+        let result = publisher(url).publish_events(&[event()]).await;
+
+        assert!(matches!(result, Err(PublishEventsError::Unauthorized(401))));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{Event, EventPublisher, PublishEventsError};
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::sync::Arc;
+
+/// Publishes events to several publishers at the same time.
+///
+/// Publishing succeeds only if it succeeds for every publisher. If it fails for some of them,
+/// each failure is logged and the first one is returned, but the events may already have been
+/// published by the others, so retrying publishes them to those again.
+#[derive(Debug, Clone)]
+pub struct FanOutPublisher {
+    publishers: Vec<Arc<dyn EventPublisher + Send + Sync>>,
+}
+
+impl FanOutPublisher {
+    pub fn new(publishers: Vec<Arc<dyn EventPublisher + Send + Sync>>) -> Self {
+        Self { publishers }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for FanOutPublisher {
+    async fn publish_events<'a>(&'a self, events: &'a [Event]) -> Result<(), PublishEventsError> {
+        let results = join_all(
+            self.publishers
+                .iter()
+                .map(|publisher| publisher.publish_events(events)),
+        )
+        .await;
+
+        let mut first_error = None;
+        for (publisher, result) in self.publishers.iter().zip(results) {
+            if let Err(err) = result {
+                tracing::error!("publishing events with {:?} failed: {}", publisher, err);
+                first_error = first_error.or(Some(err));
+            }
+        }
+
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event_grid::test::event;
+    use std::sync::mpsc::sync_channel;
+
+    #[derive(Debug)]
+    struct FailingPublisher;
+
+    #[async_trait]
+    impl EventPublisher for FailingPublisher {
+        async fn publish_events<'a>(
+            &'a self,
+            _events: &'a [Event],
+        ) -> Result<(), PublishEventsError> {
+            Err(PublishEventsError::Timeout)
+        }
+    }
+
+    #[async_std::test]
+    async fn publish_events_publishes_to_every_publisher() {
+        let (first, first_events) = sync_channel(10);
+        let (second, second_events) = sync_channel(10);
+        let publisher = FanOutPublisher::new(vec![Arc::new(first), Arc::new(second)]);
+        let event = event();
+
+        publisher
+            .publish_events(std::slice::from_ref(&event))
+            .await
+            .unwrap();
+
+        assert_eq!(first_events.try_recv().unwrap(), event);
+        assert_eq!(second_events.try_recv().unwrap(), event);
+    }
+
+    #[async_std::test]
+    async fn publish_events_fails_if_any_publisher_fails_but_publishes_to_the_others() {
+        let (sender, events) = sync_channel(10);
+        let publisher = FanOutPublisher::new(vec![Arc::new(FailingPublisher), Arc::new(sender)]);
+
+        let result = publisher.publish_events(&[event()]).await;
+
+        assert!(matches!(result, Err(PublishEventsError::Timeout)));
+        assert!(events.try_recv().is_ok());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::PublishEventsError;
+use rand::Rng as _;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+use tracing::Span;
+use tracing_futures::Instrument as _;
+
+/// How often and how long to wait before trying again when publishing events fails with a
+/// transient error (see `PublishEventsError::is_transient`).
+///
+/// The wait before each retry is a random duration between zero and an exponentially growing
+/// limit (starting at `initial_backoff` and doubling every time, up to `max_backoff`), so that
+/// many clients that failed at the same time do not all retry at the same time. If the server
+/// says how long to wait with a `Retry-After` header, that is used instead, up to `max_backoff`.
+///
+/// No retry is started that would end more than `max_elapsed` after the first attempt started.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// How many times to try again after the first attempt
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// How long to keep trying, including the time the attempts take
+    pub max_elapsed: Duration,
+}
+
+impl RetryPolicy {
+    /// Never retries.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Calls `attempt` until it succeeds, fails with an error that is not transient, has been
+    /// retried `max_retries` times, or waiting to retry would take it past `max_elapsed`.
+    pub(crate) async fn retry<T, F, Fut>(&self, mut attempt: F) -> Result<T, PublishEventsError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, PublishEventsError>>,
+    {
+        let start = Instant::now();
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Err(err) if err.is_transient() && retry < self.max_retries => {
+                    let wait = self.wait(&err, retry);
+                    if start.elapsed() + wait > self.max_elapsed {
+                        tracing::warn!(
+                            "publishing events failed ({}), giving up after {:?}",
+                            err,
+                            start.elapsed()
+                        );
+                        return Err(err);
+                    }
+                    tracing::warn!("publishing events failed ({}), retrying in {:?}", err, wait);
+                    async_std::task::sleep(wait).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// How long to wait before retry number `retry` (starting at 0) after `err`.
+    fn wait(&self, err: &PublishEventsError, retry: u32) -> Duration {
+        match err {
+            PublishEventsError::Throttled {
+                retry_after: Some(retry_after),
+            } => (*retry_after).min(self.max_backoff),
+            _ => self.backoff(retry),
+        }
+    }
+
+    /// The limit of the wait before retry number `retry` (starting at 0).
+    fn backoff_limit(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .checked_mul(2u32.saturating_pow(retry))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        let limit = self.backoff_limit(retry).as_millis() as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(0, limit + 1))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            max_elapsed: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Sends the request within `span`, and returns the response if its status is successful (2xx).
+pub(crate) async fn send(
+    request: surf::RequestBuilder,
+    span: Span,
+    timeout: Duration,
+) -> Result<surf::Response, PublishEventsError> {
+    let res = async_std::future::timeout(timeout, request.instrument(span.clone()))
+        .await
+        .map_err(|_| PublishEventsError::Timeout)??;
+
+    span.record("http.status_code", &u16::from(res.status()));
+
+    match u16::from(res.status()) {
+        200..=299 => Ok(res),
+        401 | 403 => Err(PublishEventsError::Unauthorized(u16::from(res.status()))),
+        413 => Err(PublishEventsError::PayloadTooLarge),
+        429 => Err(PublishEventsError::Throttled {
+            retry_after: res
+                .header("retry-after")
+                .and_then(|value| value.as_str().parse().ok())
+                .map(Duration::from_secs),
+        }),
+        status => Err(PublishEventsError::Server(status)),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use async_std::{net::TcpListener, prelude::*};
+    use std::sync::mpsc::Sender;
+
+    pub(crate) fn fast_retries() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+            max_elapsed: Duration::from_secs(5),
+        }
+    }
+
+    /// A request received by `fake_server`.
+    #[derive(Debug)]
+    pub(crate) struct Request {
+        pub head: String,
+        pub body: Vec<u8>,
+    }
+
+    impl Request {
+        pub(crate) fn header(&self, name: &str) -> Option<&str> {
+            self.head.lines().skip(1).find_map(|line| {
+                let colon = line.find(':')?;
+                if line[..colon].eq_ignore_ascii_case(name) {
+                    Some(line[colon + 1..].trim())
+                } else {
+                    None
+                }
+            })
+        }
+    }
+
+    /// Responds to each request with the next status and body, and returns the URL to send them
+    /// to. Every request is sent to `requests`.
+    pub(crate) async fn fake_server(
+        path: &str,
+        responses: &'static [(&'static str, &'static str)],
+        requests: Option<Sender<Request>>,
+    ) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), path);
+        async_std::task::spawn(async move {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut stream).await;
+                if let Some(requests) = &requests {
+                    requests.send(request).unwrap();
+                }
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        url
+    }
+
+    /// Good enough to read the small requests of the tests, which have a `Content-Length`.
+    async fn read_request(stream: &mut async_std::net::TcpStream) -> Request {
+        let mut data = vec![];
+        let mut chunk = [0; 8192];
+        loop {
+            let read = stream.read(&mut chunk).await.unwrap();
+            data.extend_from_slice(&chunk[..read]);
+            if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+                let head = String::from_utf8(data[..end].to_vec()).unwrap();
+                let mut request = Request {
+                    head,
+                    body: data[end + 4..].to_vec(),
+                };
+                let length = request
+                    .header("content-length")
+                    .map_or(0, |length| length.parse().unwrap());
+                while request.body.len() < length {
+                    let read = stream.read(&mut chunk).await.unwrap();
+                    request.body.extend_from_slice(&chunk[..read]);
+                }
+                return request;
+            }
+            if read == 0 {
+                panic!("connection closed before the end of the request");
+            }
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+            ..RetryPolicy::default()
+        };
+
+        assert_eq!(policy.backoff_limit(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff_limit(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff_limit(4), Duration::from_millis(1000));
+        assert_eq!(policy.backoff_limit(40), Duration::from_millis(1000));
+        assert!(policy.backoff(1) <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn retry_after_is_limited_to_the_maximum_backoff() {
+        let policy = RetryPolicy {
+            max_backoff: Duration::from_secs(10),
+            ..RetryPolicy::default()
+        };
+        let throttled = |seconds| PublishEventsError::Throttled {
+            retry_after: Some(Duration::from_secs(seconds)),
+        };
+
+        assert_eq!(policy.wait(&throttled(2), 0), Duration::from_secs(2));
+        assert_eq!(policy.wait(&throttled(3600), 0), Duration::from_secs(10));
+    }
+
+    #[async_std::test]
+    async fn retry_gives_up_when_waiting_would_take_too_long() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            max_elapsed: Duration::from_millis(50),
+            ..RetryPolicy::default()
+        };
+        let mut attempts = 0;
+
+        let result: Result<(), _> = policy
+            .retry(|| {
+                attempts += 1;
+                async {
+                    Err(PublishEventsError::Throttled {
+                        retry_after: Some(Duration::from_millis(20)),
+                    })
+                }
+            })
+            .await;
+
+        assert!(matches!(result, Err(PublishEventsError::Throttled { .. })));
+        // waiting 20ms a third time would end after more than 50ms
+        assert!(attempts <= 3, "{} attempts", attempts);
+    }
+}
@@ -0,0 +1,439 @@
+use crate::{http, Event, EventPublisher, EventSchema, PublishEventsError, RetryPolicy};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Mutex, time::Duration};
+use tracing::info_span;
+
+/// The default `max.request.size` of the proxy's producer. Larger requests are rejected.
+pub const MAX_KAFKA_REQUEST_SIZE: usize = 1024 * 1024;
+
+/// Publishes events to a Kafka topic through a [Kafka REST Proxy] (API v2).
+///
+/// This is an HTTP client of the proxy rather than a native Kafka producer, so that it does not
+/// need librdkafka and works wherever the other publishers do.
+///
+/// Each event is a record whose value is the event as JSON (see `with_schema`) and whose key is
+/// the event's subject, so that events about the same subject go to the same partition and are
+/// consumed in order. Records are produced in as few requests as fit in
+/// `MAX_KAFKA_REQUEST_SIZE`, in order. If the proxy could not produce some of the records of a
+/// request for a reason that may go away, those records are sent again along with every later
+/// record with the same key, so that the records of each key are still produced in order.
+///
+/// [Kafka REST Proxy]: https://docs.confluent.io/platform/current/kafka-rest/api.html
+#[derive(Debug, Clone)]
+pub struct KafkaRestPublisher {
+    url: String,
+    schema: EventSchema,
+    retry_policy: RetryPolicy,
+    timeout: Duration,
+    max_request_size: usize,
+}
+
+const CONTENT_TYPE: &str = "application/vnd.kafka.json.v2+json";
+const ACCEPT: &str = "application/vnd.kafka.v2+json";
+
+/// The proxy's error code for records that may be produced if they are sent again.
+const RETRIABLE_ERROR: i32 = 2;
+
+/// A produce request is `{"records":[...]}` around the records, separated by commas.
+const REQUEST_START: &[u8] = br#"{"records":["#;
+const REQUEST_END: &[u8] = b"]}";
+
+#[derive(Serialize)]
+struct Record<'a> {
+    key: &'a str,
+    value: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct ProduceResponse {
+    offsets: Vec<Offset>,
+}
+
+#[derive(Deserialize)]
+struct Offset {
+    error_code: Option<i32>,
+    error: Option<String>,
+}
+
+impl KafkaRestPublisher {
+    /// `proxy_url` is the URL of the REST Proxy, e.g. `http://kafka-rest:8082`.
+    pub fn new(proxy_url: &str, topic: &str) -> Self {
+        Self {
+            url: format!("{}/topics/{}", proxy_url.trim_end_matches('/'), topic),
+            schema: EventSchema::EventGrid,
+            retry_policy: RetryPolicy::default(),
+            timeout: Duration::from_secs(30),
+            max_request_size: MAX_KAFKA_REQUEST_SIZE,
+        }
+    }
+
+    /// Sets the schema of the record values, which is the EventGrid schema by default.
+    pub fn with_schema(mut self, schema: EventSchema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets how long to wait for each request, including each retry. The default is 30 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the records in one request, and returns the offset of each record.
+    async fn send(&self, records: &[&[u8]]) -> Result<Vec<Offset>, PublishEventsError> {
+        let span = info_span!(
+            "POST /topics",
+            otel.kind = "client",
+            http.method = "POST",
+            http.url = self.url.as_str(),
+            http.status_code = tracing::field::Empty,
+            http.status_text = tracing::field::Empty
+        );
+
+        let request = surf::post(&self.url)
+            .header("accept", ACCEPT)
+            .content_type(CONTENT_TYPE)
+            .body(produce_request(records));
+        let mut res = http::send(request, span, self.timeout).await?;
+
+        let response: ProduceResponse = res.body_json().await?;
+        if response.offsets.len() != records.len() {
+            return Err(PublishEventsError::Internal(format!(
+                "the proxy returned {} offsets for {} records",
+                response.offsets.len(),
+                records.len()
+            )));
+        }
+        Ok(response.offsets)
+    }
+
+    /// Produces the records, given with their keys, and sends the ones that failed with a
+    /// retriable error again until they have all been produced or the retry policy gives up.
+    async fn produce(&self, records: Vec<(&str, &[u8])>) -> Result<(), PublishEventsError> {
+        let pending = &Mutex::new(records);
+        self.retry_policy
+            .retry(move || async move {
+                let records = pending.lock().unwrap().clone();
+                // The proxy responds with 200 OK even if some of the records could not be
+                // produced.
+                let values = records
+                    .iter()
+                    .map(|(_, record)| *record)
+                    .collect::<Vec<_>>();
+                let offsets = self.send(&values).await?;
+
+                // A record after a failed one with the same key may have been produced, so it
+                // is sent again too, after the failed one.
+                let mut failed = vec![];
+                let mut failed_keys = HashSet::new();
+                let mut reason = None;
+                for ((key, record), offset) in records.into_iter().zip(offsets) {
+                    if let Some(error_code) = offset.error_code {
+                        if error_code != RETRIABLE_ERROR {
+                            return Err(PublishEventsError::Rejected {
+                                reason: offset.error.unwrap_or_default(),
+                                transient: false,
+                            });
+                        }
+                        failed_keys.insert(key);
+                        reason = reason.or(offset.error);
+                    }
+                    if failed_keys.contains(key) {
+                        failed.push((key, record));
+                    }
+                }
+                if failed.is_empty() {
+                    return Ok(());
+                }
+
+                *pending.lock().unwrap() = failed;
+                Err(PublishEventsError::Rejected {
+                    reason: reason.unwrap_or_default(),
+                    transient: true,
+                })
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl EventPublisher for KafkaRestPublisher {
+    async fn publish_events<'a>(&'a self, events: &'a [Event]) -> Result<(), PublishEventsError> {
+        let records = events
+            .iter()
+            .map(|event| {
+                serde_json::to_vec(&Record {
+                    key: &event.subject,
+                    value: self.schema.to_value(event)?,
+                })
+            })
+            .collect::<serde_json::Result<Vec<_>>>()?;
+
+        let mut keys = events.iter().map(|event| event.subject.as_str());
+        for batch in batches(&records, self.max_request_size)? {
+            let batch = batch.into_iter().zip(keys.by_ref());
+            self.produce(batch.map(|(record, key)| (key, record)).collect())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Groups the serialized records into as few produce requests as possible that are at most
+/// `max_size` bytes each, keeping them in order.
+fn batches(records: &[Vec<u8>], max_size: usize) -> Result<Vec<Vec<&[u8]>>, PublishEventsError> {
+    let empty_size = REQUEST_START.len() + REQUEST_END.len();
+    let mut batches = vec![];
+    let mut batch: Vec<&[u8]> = vec![];
+    let mut size = empty_size;
+    for record in records {
+        if empty_size + record.len() > max_size {
+            return Err(PublishEventsError::PayloadTooLarge);
+        }
+        // The record and, unless it is the first one, a comma.
+        if !batch.is_empty() && size + record.len() + 1 > max_size {
+            batches.push(std::mem::take(&mut batch));
+            size = empty_size;
+        }
+        if !batch.is_empty() {
+            size += 1;
+        }
+        size += record.len();
+        batch.push(record);
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+
+    Ok(batches)
+}
+
+fn produce_request(records: &[&[u8]]) -> Vec<u8> {
+    let mut body = REQUEST_START.to_vec();
+    for (i, record) in records.iter().enumerate() {
+        if i > 0 {
+            body.push(b',');
+        }
+        body.extend_from_slice(record);
+    }
+    body.extend_from_slice(REQUEST_END);
+    body
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        event_grid::test::event,
+        http::test::{fake_server, fast_retries},
+    };
+    use serde_json::json;
+    use std::sync::mpsc::channel;
+
+    const PRODUCED: &str =
+        r#"{"offsets":[{"partition":0,"offset":1,"error_code":null,"error":null}]}"#;
+
+    fn event_about(subject: &str) -> Event {
+        Event {
+            subject: subject.into(),
+            ..event()
+        }
+    }
+
+    fn body(request: &crate::http::test::Request) -> serde_json::Value {
+        serde_json::from_slice(&request.body).unwrap()
+    }
+
+    #[async_std::test]
+    async fn publish_events_produces_records_keyed_by_subject() {
+        let (sender, requests) = channel();
+        let url = fake_server("", &[("200 OK", PRODUCED)], Some(sender)).await;
+        let event = event();
+
+        let result = KafkaRestPublisher::new(&url, "events")
+            .publish_events(std::slice::from_ref(&event))
+            .await;
+
+        assert!(result.is_ok(), "{:?}", result);
+        let request = requests.recv().unwrap();
+        assert!(request.head.starts_with("POST /topics/events "));
+        assert_eq!(request.header("content-type"), Some(CONTENT_TYPE));
+        assert_eq!(
+            body(&request),
+            json!({ "records": [{ "key": "subject", "value": event }] })
+        );
+    }
+
+    #[async_std::test]
+    async fn publish_events_retries_only_records_that_failed_transiently() {
+        let (sender, requests) = channel();
+        let url = fake_server(
+            "",
+            &[
+                (
+                    "200 OK",
+                    r#"{"offsets":[{"partition":0,"offset":1,"error_code":null,"error":null},{"partition":null,"offset":null,"error_code":2,"error":"Leader not available"}]}"#,
+                ),
+                ("200 OK", PRODUCED),
+            ],
+            Some(sender),
+        )
+        .await;
+
+        let result = KafkaRestPublisher::new(&url, "events")
+            .with_retry_policy(fast_retries())
+            .publish_events(&[event_about("first"), event_about("second")])
+            .await;
+
+        assert!(result.is_ok(), "{:?}", result);
+        let keys = |request: crate::http::test::Request| {
+            body(&request)["records"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|record| record["key"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(requests.recv().unwrap()), vec!["first", "second"]);
+        assert_eq!(keys(requests.recv().unwrap()), vec!["second"]);
+    }
+
+    #[async_std::test]
+    async fn publish_events_retries_later_records_with_the_same_key_as_a_failed_one() {
+        let (sender, requests) = channel();
+        let url = fake_server(
+            "",
+            &[
+                (
+                    "200 OK",
+                    r#"{"offsets":[{"partition":null,"offset":null,"error_code":2,"error":"Leader not available"},{"partition":0,"offset":1,"error_code":null,"error":null},{"partition":1,"offset":1,"error_code":null,"error":null}]}"#,
+                ),
+                (
+                    "200 OK",
+                    r#"{"offsets":[{"partition":0,"offset":2,"error_code":null,"error":null},{"partition":0,"offset":3,"error_code":null,"error":null}]}"#,
+                ),
+            ],
+            Some(sender),
+        )
+        .await;
+        let events = [
+            Event {
+                id: "1".into(),
+                ..event_about("first")
+            },
+            Event {
+                id: "2".into(),
+                ..event_about("first")
+            },
+            event_about("second"),
+        ];
+
+        let result = KafkaRestPublisher::new(&url, "events")
+            .with_retry_policy(fast_retries())
+            .publish_events(&events)
+            .await;
+
+        assert!(result.is_ok(), "{:?}", result);
+        requests.recv().unwrap();
+        assert_eq!(
+            body(&requests.recv().unwrap()),
+            json!({
+                "records": [
+                    { "key": "first", "value": events[0] },
+                    { "key": "first", "value": events[1] },
+                ]
+            })
+        );
+    }
+
+    #[async_std::test]
+    async fn publish_events_fails_if_a_record_is_rejected() {
+        let url = fake_server(
+            "",
+            &[(
+                "200 OK",
+                r#"{"offsets":[{"partition":null,"offset":null,"error_code":1,"error":"Record too large"}]}"#,
+            )],
+            None,
+        )
+        .await;
+
+        let result = KafkaRestPublisher::new(&url, "events")
+            .with_retry_policy(fast_retries())
+            .publish_events(&[event()])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(PublishEventsError::Rejected {
+                transient: false,
+                ..
+            })
+        ));
+    }
+
+    #[async_std::test]
+    async fn publish_events_splits_records_into_requests_that_fit() {
+        let (sender, requests) = channel();
+        let url = fake_server(
+            "",
+            &[("200 OK", PRODUCED), ("200 OK", PRODUCED)],
+            Some(sender),
+        )
+        .await;
+        let events = [event_about("first"), event_about("second")];
+        // Room for the larger of the two records, but not for both.
+        let record_size = serde_json::to_vec(&Record {
+            key: "second",
+            value: serde_json::to_value(&events[1]).unwrap(),
+        })
+        .unwrap()
+        .len();
+        let mut publisher = KafkaRestPublisher::new(&url, "events");
+        publisher.max_request_size = REQUEST_START.len() + REQUEST_END.len() + record_size;
+
+        let result = publisher.publish_events(&events).await;
+
+        assert!(result.is_ok(), "{:?}", result);
+        for event in &events {
+            assert_eq!(
+                body(&requests.recv().unwrap()),
+                json!({ "records": [{ "key": event.subject, "value": event }] })
+            );
+        }
+    }
+
+    #[test]
+    fn batches_keeps_records_in_order_within_the_maximum_size() {
+        let records = vec![b"1".to_vec(), b"22".to_vec(), b"333".to_vec()];
+        let empty_size = REQUEST_START.len() + REQUEST_END.len();
+
+        let batches = batches(&records, empty_size + 4).unwrap();
+
+        assert_eq!(
+            batches,
+            vec![vec![&b"1"[..], &b"22"[..]], vec![&b"333"[..]]]
+        );
+        assert_eq!(
+            produce_request(&batches[0]),
+            br#"{"records":[1,22]}"#.to_vec()
+        );
+    }
+
+    #[test]
+    fn batches_fails_for_a_record_that_is_too_large() {
+        let records = vec![vec![b'1'; 100]];
+
+        assert!(matches!(
+            batches(&records, 100),
+            Err(PublishEventsError::PayloadTooLarge)
+        ));
+    }
+}
@@ -1,5 +1,19 @@
 mod client;
+mod event_grid;
+mod fan_out;
+mod http;
+mod kafka;
 mod models;
+mod receiver;
+mod validation;
+pub mod webhook;
 
 pub use client::*;
+pub use event_grid::*;
+pub use fan_out::*;
+pub use http::RetryPolicy;
+pub use kafka::*;
 pub use models::*;
+pub use receiver::*;
+pub use validation::*;
+pub use webhook::WebhookPublisher;
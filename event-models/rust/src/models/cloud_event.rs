@@ -0,0 +1,269 @@
+use super::{Event, RawEvent};
+use chrono::{DateTime, Utc};
+use serde::de;
+use serde::ser::{self, Error as _, SerializeStruct};
+use serde::{Deserialize, Serialize};
+
+const SPEC_VERSION: &str = "1.0";
+const DATA_CONTENT_TYPE: &str = "application/json";
+const DATA_SCHEMA_PREFIX: &str = "urn:fnhs-event-models:";
+
+/// An event in the [CloudEvents 1.0] JSON format (structured content mode), with the `source`
+/// that identifies where it happened, which `Event` does not have.
+///
+/// The `eventType` is the `type`, and the `dataVersion` is part of the `dataschema`, which is
+/// `urn:fnhs-event-models:<eventType>:<dataVersion>`, so that converting an `Event` to a
+/// `CloudEvent` and back gives the same `Event`.
+///
+/// [CloudEvents 1.0]: https://github.com/cloudevents/spec/blob/v1.0/spec.md
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloudEvent {
+    pub source: String,
+    pub event: Event,
+}
+
+impl From<CloudEvent> for Event {
+    fn from(cloud_event: CloudEvent) -> Self {
+        cloud_event.event
+    }
+}
+
+/// How events are encoded when they are published.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventSchema {
+    /// The EventGrid event schema, which is also how `Event` is serialized
+    EventGrid,
+    /// CloudEvents 1.0, with this `source` (see `CloudEvent`)
+    CloudEvents { source: String },
+}
+
+impl EventSchema {
+    pub fn to_value(&self, event: &Event) -> serde_json::Result<serde_json::Value> {
+        match self {
+            EventSchema::EventGrid => serde_json::to_value(event),
+            EventSchema::CloudEvents { source } => {
+                serde_json::to_value(CloudEventRef { source, event })
+            }
+        }
+    }
+
+    pub fn to_vec(&self, event: &Event) -> serde_json::Result<Vec<u8>> {
+        match self {
+            EventSchema::EventGrid => serde_json::to_vec(event),
+            EventSchema::CloudEvents { source } => {
+                serde_json::to_vec(&CloudEventRef { source, event })
+            }
+        }
+    }
+
+    /// The content type of a JSON array of events in this schema.
+    pub(crate) fn batch_content_type(&self) -> &'static str {
+        match self {
+            EventSchema::EventGrid => "application/json",
+            EventSchema::CloudEvents { .. } => "application/cloudevents-batch+json; charset=utf-8",
+        }
+    }
+}
+
+impl Default for EventSchema {
+    fn default() -> Self {
+        EventSchema::EventGrid
+    }
+}
+
+/// Serializes a `CloudEvent` without owning the event.
+struct CloudEventRef<'a> {
+    source: &'a str,
+    event: &'a Event,
+}
+
+impl Serialize for CloudEventRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let (event_type, data_version, data) =
+            self.event.data.serialize().map_err(S::Error::custom)?;
+        let mut state = serializer.serialize_struct("CloudEvent", 9)?;
+        state.serialize_field("specversion", SPEC_VERSION)?;
+        state.serialize_field("type", event_type)?;
+        state.serialize_field("source", self.source)?;
+        state.serialize_field("id", &self.event.id)?;
+        state.serialize_field("subject", &self.event.subject)?;
+        state.serialize_field("time", &self.event.event_time)?;
+        state.serialize_field("datacontenttype", DATA_CONTENT_TYPE)?;
+        state.serialize_field(
+            "dataschema",
+            &format!("{}{}:{}", DATA_SCHEMA_PREFIX, event_type, data_version),
+        )?;
+        state.serialize_field("data", &data)?;
+        state.end()
+    }
+}
+
+impl Serialize for CloudEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        CloudEventRef {
+            source: &self.source,
+            event: &self.event,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// A `CloudEvent` whose data has not been deserialized yet (see `RawEvent`).
+#[derive(Deserialize)]
+pub(crate) struct RawCloudEvent {
+    specversion: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    source: String,
+    id: String,
+    subject: String,
+    time: DateTime<Utc>,
+    #[serde(default)]
+    datacontenttype: Option<String>,
+    dataschema: String,
+    data: serde_json::Value,
+}
+
+impl RawCloudEvent {
+    /// The source and the event in the EventGrid schema.
+    pub(crate) fn into_raw_event<E: de::Error>(self) -> Result<(String, RawEvent), E> {
+        if self.specversion != SPEC_VERSION {
+            return Err(E::invalid_value(
+                de::Unexpected::Str(&self.specversion),
+                &SPEC_VERSION,
+            ));
+        }
+        if let Some(content_type) = &self.datacontenttype {
+            if content_type != DATA_CONTENT_TYPE {
+                return Err(E::invalid_value(
+                    de::Unexpected::Str(content_type),
+                    &DATA_CONTENT_TYPE,
+                ));
+            }
+        }
+        let data_version = self
+            .dataschema
+            .strip_prefix(DATA_SCHEMA_PREFIX)
+            .and_then(|schema| schema.strip_prefix(self.event_type.as_str()))
+            .and_then(|schema| schema.strip_prefix(':'))
+            .ok_or_else(|| {
+                E::invalid_value(
+                    de::Unexpected::Str(&self.dataschema),
+                    &"urn:fnhs-event-models:<type>:<dataVersion>",
+                )
+            })?
+            .to_string();
+
+        Ok((
+            self.source,
+            RawEvent {
+                id: self.id,
+                subject: self.subject,
+                event_time: self.time,
+                event_type: self.event_type,
+                data: self.data,
+                data_version,
+            },
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for CloudEvent {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let (source, raw) = RawCloudEvent::deserialize(deserializer)?.into_raw_event()?;
+        let (event_type, data_version) = (raw.event_type.clone(), raw.data_version.clone());
+        let event = raw
+            .into_event()
+            .map_err(|err| err.into_de_error(&event_type, &data_version))?;
+
+        Ok(CloudEvent { source, event })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContentViewedData;
+
+    const CLOUD_EVENT: &str = r#"{"specversion":"1.0","type":"ContentViewed","source":"/workspace-service","id":"id","subject":"subj","time":"2020-09-09T10:22:42.235679Z","datacontenttype":"application/json","dataschema":"urn:fnhs-event-models:ContentViewed:1","data":{"contentId":"content","contentType":"Folder","userId":"user","workspaceId":"workspace"}}"#;
+
+    fn cloud_event() -> CloudEvent {
+        CloudEvent {
+            source: "/workspace-service".into(),
+            event: Event {
+                id: "id".into(),
+                subject: "subj".into(),
+                event_time: DateTime::parse_from_rfc3339("2020-09-09T10:22:42.235679Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+                data: ContentViewedData {
+                    user_id: "user".into(),
+                    content_id: "content".into(),
+                    content_type: "Folder".into(),
+                    workspace_id: "workspace".into(),
+                    error: None,
+                }
+                .into(),
+            },
+        }
+    }
+
+    #[test]
+    fn serialize() {
+        assert_eq!(serde_json::to_string(&cloud_event()).unwrap(), CLOUD_EVENT);
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_eq!(
+            serde_json::from_str::<CloudEvent>(CLOUD_EVENT).unwrap(),
+            cloud_event()
+        );
+    }
+
+    #[test]
+    fn converts_from_and_to_the_event_grid_schema() {
+        let event = cloud_event().event;
+        let source = "/workspace-service".to_string();
+
+        let json = EventSchema::CloudEvents {
+            source: source.clone(),
+        }
+        .to_vec(&event)
+        .unwrap();
+        let cloud_event: CloudEvent = serde_json::from_slice(&json).unwrap();
+
+        assert_eq!(cloud_event.source, source);
+        assert_eq!(Event::from(cloud_event), event);
+    }
+
+    #[test]
+    fn deserialize_rejects_a_data_schema_for_another_type() {
+        let json = CLOUD_EVENT.replace(
+            "urn:fnhs-event-models:ContentViewed:1",
+            "urn:fnhs-event-models:FileCreated:1",
+        );
+
+        assert!(serde_json::from_str::<CloudEvent>(&json).is_err());
+    }
+
+    #[test]
+    fn deserialize_rejects_an_unknown_data_version() {
+        let json = CLOUD_EVENT.replace(
+            "urn:fnhs-event-models:ContentViewed:1",
+            "urn:fnhs-event-models:ContentViewed:2",
+        );
+
+        let err = serde_json::from_str::<CloudEvent>(&json).unwrap_err();
+        assert!(err.to_string().contains("dataVersion: 2"), "{}", err);
+    }
+}
@@ -2,6 +2,7 @@
 // DO NOT MODIFY IT BY HAND. Instead, modify the source JSONSchema file,
 // and run yarn generate to regenerate this file.
 
+use super::EventDataVariant;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -141,6 +142,138 @@ pub struct FileDeletedData {
     pub workspace_id: String,
 }
 
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileMovedData {
+    ///
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+
+    /// The user that moved the file
+    #[serde(rename = "userId")]
+    pub user_id: String,
+
+    /// The folder that the file was in
+    #[serde(rename = "oldFolderId")]
+    pub old_folder_id: String,
+
+    /// The folder that the file is in now
+    #[serde(rename = "newFolderId")]
+    pub new_folder_id: String,
+
+    /// The workspace that the file was in
+    #[serde(rename = "oldWorkspaceId")]
+    pub old_workspace_id: String,
+
+    /// The workspace that the file is in now
+    #[serde(rename = "newWorkspaceId")]
+    pub new_workspace_id: String,
+}
+
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileRestoredData {
+    ///
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+
+    /// The user that restored the file
+    #[serde(rename = "userId")]
+    pub user_id: String,
+
+    ///
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+
+    ///
+    #[serde(rename = "versionNumber")]
+    pub version_number: i64,
+
+    /// The workspace that the file is in
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+}
+
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileRejectedData {
+    /// What the content of the upload looks like, e.g. "Windows executable"
+    #[serde(rename = "detectedType")]
+    pub detected_type: String,
+
+    /// The file that the upload was a new version of, if any
+    #[serde(rename = "fileId", skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+
+    ///
+    #[serde(rename = "fileName")]
+    pub file_name: String,
+
+    /// The MIME type that the file was declared as
+    #[serde(rename = "fileType")]
+    pub file_type: String,
+
+    /// The folder that the file was going to be added to
+    #[serde(rename = "folderId")]
+    pub folder_id: String,
+
+    ///
+    #[serde(rename = "uploadId")]
+    pub upload_id: String,
+
+    /// The user that uploaded the file
+    #[serde(rename = "userId")]
+    pub user_id: String,
+
+    /// The workspace that the file was going to be added to
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+}
+
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileScanPassedData {
+    ///
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+
+    ///
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+
+    ///
+    #[serde(rename = "versionNumber")]
+    pub version_number: i64,
+
+    ///
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+}
+
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileScanFailedData {
+    ///
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+
+    /// The name of the malware that the scanner found, e.g. "Eicar-Signature"
+    #[serde(rename = "signature")]
+    pub signature: String,
+
+    ///
+    #[serde(rename = "versionId")]
+    pub version_id: String,
+
+    ///
+    #[serde(rename = "versionNumber")]
+    pub version_number: i64,
+
+    ///
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+}
+
 ///
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FileDownloadedData {
@@ -191,6 +324,10 @@ pub struct FolderCreatedData {
     /// The role that is needed to view the folder contents
     #[serde(rename = "roleRequired")]
     pub role_required: String,
+
+    /// The folder that the folder is in, if it is not at the top level of the workspace
+    #[serde(rename = "parentFolderId", skip_serializing_if = "Option::is_none")]
+    pub parent_folder_id: Option<String>,
 }
 
 ///
@@ -219,6 +356,10 @@ pub struct FolderUpdatedData {
     /// The role that is needed to view the folder contents
     #[serde(rename = "roleRequired")]
     pub role_required: String,
+
+    /// The folder that the folder is in, if it is not at the top level of the workspace
+    #[serde(rename = "parentFolderId", skip_serializing_if = "Option::is_none")]
+    pub parent_folder_id: Option<String>,
 }
 
 ///
@@ -235,6 +376,30 @@ pub struct FolderDeletedData {
     /// The workspace that the folder is in
     #[serde(rename = "workspaceId")]
     pub workspace_id: String,
+
+    /// The number of files that were deleted with the folder, including those in the folders in it
+    #[serde(rename = "fileCount")]
+    pub file_count: i64,
+}
+
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FolderRestoredData {
+    ///
+    #[serde(rename = "folderId")]
+    pub folder_id: String,
+
+    /// The user that restored the folder
+    #[serde(rename = "userId")]
+    pub user_id: String,
+
+    /// The workspace that the folder is in
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+
+    /// The number of files that were restored with the folder, including those in the folders in it
+    #[serde(rename = "fileCount")]
+    pub file_count: i64,
 }
 
 ///
@@ -253,6 +418,38 @@ pub struct WorkspaceCreatedData {
     pub workspace_id: String,
 }
 
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceUpdatedData {
+    ///
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+
+    ///
+    #[serde(rename = "title")]
+    pub title: String,
+
+    ///
+    #[serde(rename = "description")]
+    pub description: String,
+
+    /// The id of the user that updated the workspace
+    #[serde(rename = "userId")]
+    pub user_id: String,
+}
+
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceDeletedData {
+    ///
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: String,
+
+    /// The id of the user that deleted the workspace
+    #[serde(rename = "userId")]
+    pub user_id: String,
+}
+
 ///
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WorkspaceMembershipChangedData {
@@ -279,11 +476,19 @@ pub enum EventData {
     FileCreated(FileCreatedData),
     FileUpdated(FileUpdatedData),
     FileDeleted(FileDeletedData),
+    FileMoved(FileMovedData),
+    FileRestored(FileRestoredData),
+    FileRejected(FileRejectedData),
+    FileScanPassed(FileScanPassedData),
+    FileScanFailed(FileScanFailedData),
     FileDownloaded(FileDownloadedData),
     FolderCreated(FolderCreatedData),
     FolderUpdated(FolderUpdatedData),
     FolderDeleted(FolderDeletedData),
+    FolderRestored(FolderRestoredData),
     WorkspaceCreated(WorkspaceCreatedData),
+    WorkspaceUpdated(WorkspaceUpdatedData),
+    WorkspaceDeleted(WorkspaceDeletedData),
     WorkspaceMembershipChanged(WorkspaceMembershipChangedData),
 }
 
@@ -311,6 +516,36 @@ impl From<FileDeletedData> for EventData {
     }
 }
 
+impl From<FileMovedData> for EventData {
+    fn from(data: FileMovedData) -> Self {
+        Self::FileMoved(data)
+    }
+}
+
+impl From<FileRestoredData> for EventData {
+    fn from(data: FileRestoredData) -> Self {
+        Self::FileRestored(data)
+    }
+}
+
+impl From<FileRejectedData> for EventData {
+    fn from(data: FileRejectedData) -> Self {
+        Self::FileRejected(data)
+    }
+}
+
+impl From<FileScanPassedData> for EventData {
+    fn from(data: FileScanPassedData) -> Self {
+        Self::FileScanPassed(data)
+    }
+}
+
+impl From<FileScanFailedData> for EventData {
+    fn from(data: FileScanFailedData) -> Self {
+        Self::FileScanFailed(data)
+    }
+}
+
 impl From<FileDownloadedData> for EventData {
     fn from(data: FileDownloadedData) -> Self {
         Self::FileDownloaded(data)
@@ -335,18 +570,252 @@ impl From<FolderDeletedData> for EventData {
     }
 }
 
+impl From<FolderRestoredData> for EventData {
+    fn from(data: FolderRestoredData) -> Self {
+        Self::FolderRestored(data)
+    }
+}
+
 impl From<WorkspaceCreatedData> for EventData {
     fn from(data: WorkspaceCreatedData) -> Self {
         Self::WorkspaceCreated(data)
     }
 }
 
+impl From<WorkspaceUpdatedData> for EventData {
+    fn from(data: WorkspaceUpdatedData) -> Self {
+        Self::WorkspaceUpdated(data)
+    }
+}
+
+impl From<WorkspaceDeletedData> for EventData {
+    fn from(data: WorkspaceDeletedData) -> Self {
+        Self::WorkspaceDeleted(data)
+    }
+}
+
 impl From<WorkspaceMembershipChangedData> for EventData {
     fn from(data: WorkspaceMembershipChangedData) -> Self {
         Self::WorkspaceMembershipChanged(data)
     }
 }
 
+impl EventDataVariant for ContentViewedData {
+    const EVENT_TYPE: &'static str = "ContentViewed";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::ContentViewed(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileCreatedData {
+    const EVENT_TYPE: &'static str = "FileCreated";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileCreated(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileUpdatedData {
+    const EVENT_TYPE: &'static str = "FileUpdated";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileUpdated(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileDeletedData {
+    const EVENT_TYPE: &'static str = "FileDeleted";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileDeleted(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileMovedData {
+    const EVENT_TYPE: &'static str = "FileMoved";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileMoved(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileRestoredData {
+    const EVENT_TYPE: &'static str = "FileRestored";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileRestored(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileRejectedData {
+    const EVENT_TYPE: &'static str = "FileRejected";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileRejected(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileScanPassedData {
+    const EVENT_TYPE: &'static str = "FileScanPassed";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileScanPassed(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileScanFailedData {
+    const EVENT_TYPE: &'static str = "FileScanFailed";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileScanFailed(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FileDownloadedData {
+    const EVENT_TYPE: &'static str = "FileDownloaded";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FileDownloaded(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FolderCreatedData {
+    const EVENT_TYPE: &'static str = "FolderCreated";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FolderCreated(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FolderUpdatedData {
+    const EVENT_TYPE: &'static str = "FolderUpdated";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FolderUpdated(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FolderDeletedData {
+    const EVENT_TYPE: &'static str = "FolderDeleted";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FolderDeleted(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for FolderRestoredData {
+    const EVENT_TYPE: &'static str = "FolderRestored";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::FolderRestored(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for WorkspaceCreatedData {
+    const EVENT_TYPE: &'static str = "WorkspaceCreated";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::WorkspaceCreated(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for WorkspaceUpdatedData {
+    const EVENT_TYPE: &'static str = "WorkspaceUpdated";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::WorkspaceUpdated(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for WorkspaceDeletedData {
+    const EVENT_TYPE: &'static str = "WorkspaceDeleted";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::WorkspaceDeleted(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl EventDataVariant for WorkspaceMembershipChangedData {
+    const EVENT_TYPE: &'static str = "WorkspaceMembershipChanged";
+    const DATA_VERSION: &'static str = "1";
+
+    fn from_event_data(data: EventData) -> Option<Self> {
+        match data {
+            EventData::WorkspaceMembershipChanged(data) => Some(data),
+            _ => None,
+        }
+    }
+}
+
 pub(crate) enum EventDataDeserializationError {
     Json(serde_json::Error),
     UnknownVariant,
@@ -375,6 +844,26 @@ impl EventData {
                 serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
             )),
 
+            ("FileMoved", "1") => Ok(Self::FileMoved(
+                serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
+            )),
+
+            ("FileRestored", "1") => Ok(Self::FileRestored(
+                serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
+            )),
+
+            ("FileRejected", "1") => Ok(Self::FileRejected(
+                serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
+            )),
+
+            ("FileScanPassed", "1") => Ok(Self::FileScanPassed(
+                serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
+            )),
+
+            ("FileScanFailed", "1") => Ok(Self::FileScanFailed(
+                serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
+            )),
+
             ("FileDownloaded", "1") => Ok(Self::FileDownloaded(
                 serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
             )),
@@ -391,10 +880,22 @@ impl EventData {
                 serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
             )),
 
+            ("FolderRestored", "1") => Ok(Self::FolderRestored(
+                serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
+            )),
+
             ("WorkspaceCreated", "1") => Ok(Self::WorkspaceCreated(
                 serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
             )),
 
+            ("WorkspaceUpdated", "1") => Ok(Self::WorkspaceUpdated(
+                serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
+            )),
+
+            ("WorkspaceDeleted", "1") => Ok(Self::WorkspaceDeleted(
+                serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
+            )),
+
             ("WorkspaceMembershipChanged", "1") => Ok(Self::WorkspaceMembershipChanged(
                 serde_json::from_value(data).map_err(EventDataDeserializationError::Json)?,
             )),
@@ -415,6 +916,16 @@ impl EventData {
 
             Self::FileDeleted(data) => ("FileDeleted", "1", serde_json::to_value(data)?),
 
+            Self::FileMoved(data) => ("FileMoved", "1", serde_json::to_value(data)?),
+
+            Self::FileRestored(data) => ("FileRestored", "1", serde_json::to_value(data)?),
+
+            Self::FileRejected(data) => ("FileRejected", "1", serde_json::to_value(data)?),
+
+            Self::FileScanPassed(data) => ("FileScanPassed", "1", serde_json::to_value(data)?),
+
+            Self::FileScanFailed(data) => ("FileScanFailed", "1", serde_json::to_value(data)?),
+
             Self::FileDownloaded(data) => ("FileDownloaded", "1", serde_json::to_value(data)?),
 
             Self::FolderCreated(data) => ("FolderCreated", "1", serde_json::to_value(data)?),
@@ -423,8 +934,14 @@ impl EventData {
 
             Self::FolderDeleted(data) => ("FolderDeleted", "1", serde_json::to_value(data)?),
 
+            Self::FolderRestored(data) => ("FolderRestored", "1", serde_json::to_value(data)?),
+
             Self::WorkspaceCreated(data) => ("WorkspaceCreated", "1", serde_json::to_value(data)?),
 
+            Self::WorkspaceUpdated(data) => ("WorkspaceUpdated", "1", serde_json::to_value(data)?),
+
+            Self::WorkspaceDeleted(data) => ("WorkspaceDeleted", "1", serde_json::to_value(data)?),
+
             Self::WorkspaceMembershipChanged(data) => (
                 "WorkspaceMembershipChanged",
                 "1",
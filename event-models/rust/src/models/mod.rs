@@ -1,6 +1,8 @@
+mod cloud_event;
 mod generated;
 
 use chrono::{DateTime, Utc};
+pub use cloud_event::*;
 pub use generated::*;
 use serde::de;
 use serde::ser::{self, Error as _, SerializeStruct};
@@ -26,50 +28,65 @@ impl Event {
     }
 }
 
-impl<'de> Deserialize<'de> for Event {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: de::Deserializer<'de>,
-    {
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct RawEvent {
-            id: String,
-            subject: String,
-            event_time: DateTime<Utc>,
-            event_type: String,
-            data: serde_json::Value,
-            data_version: String,
-        }
+/// The data of one kind of event, which is one of the variants of `EventData`.
+pub trait EventDataVariant: Into<EventData> + Sized {
+    const EVENT_TYPE: &'static str;
+    const DATA_VERSION: &'static str;
 
-        const DATA_VARIANTS: &[&str] = &["see valid combinations in schema.json"];
+    /// The data, if it is of this kind.
+    fn from_event_data(data: EventData) -> Option<Self>;
+}
 
-        let RawEvent {
-            id,
-            subject,
-            event_time,
-            event_type,
-            data,
-            data_version,
-        } = RawEvent::deserialize(deserializer)?;
-        let data =
-            EventData::deserialize(&event_type, &data_version, data).map_err(|err| match err {
-                EventDataDeserializationError::Json(err) => de::Error::custom(err),
-                EventDataDeserializationError::UnknownVariant => de::Error::unknown_variant(
-                    &format!("eventType: {}, dataVersion: {}", event_type, data_version),
-                    DATA_VARIANTS,
-                ),
-            })?;
+/// An event whose data has not been deserialized yet, because its type is not known until the
+/// `eventType` and `dataVersion` have been read.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawEvent {
+    pub id: String,
+    pub subject: String,
+    pub event_time: DateTime<Utc>,
+    pub event_type: String,
+    pub data: serde_json::Value,
+    pub data_version: String,
+}
 
+impl RawEvent {
+    pub(crate) fn into_event(self) -> Result<Event, EventDataDeserializationError> {
         Ok(Event {
-            id,
-            subject,
-            event_time,
-            data,
+            data: EventData::deserialize(&self.event_type, &self.data_version, self.data)?,
+            id: self.id,
+            subject: self.subject,
+            event_time: self.event_time,
         })
     }
 }
 
+impl EventDataDeserializationError {
+    fn into_de_error<E: de::Error>(self, event_type: &str, data_version: &str) -> E {
+        const DATA_VARIANTS: &[&str] = &["see valid combinations in schema.json"];
+
+        match self {
+            EventDataDeserializationError::Json(err) => E::custom(err),
+            EventDataDeserializationError::UnknownVariant => E::unknown_variant(
+                &format!("eventType: {}, dataVersion: {}", event_type, data_version),
+                DATA_VARIANTS,
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let raw = RawEvent::deserialize(deserializer)?;
+        let (event_type, data_version) = (raw.event_type.clone(), raw.data_version.clone());
+        raw.into_event()
+            .map_err(|err| err.into_de_error(&event_type, &data_version))
+    }
+}
+
 impl Serialize for Event {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
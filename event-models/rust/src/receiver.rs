@@ -0,0 +1,599 @@
+use crate::{
+    models::{EventDataDeserializationError, RawCloudEvent, RawEvent},
+    webhook, Event, EventDataVariant,
+};
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::json;
+use std::{collections::HashMap, future::Future};
+
+const VALIDATION_EVENT_TYPE: &str = "Microsoft.EventGrid.SubscriptionValidationEvent";
+
+/// An event received by an `EventReceiver`, with the data of the kind its handler was registered
+/// for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedEvent<D> {
+    pub id: String,
+    pub subject: String,
+    pub event_time: DateTime<Utc>,
+    pub data: D,
+}
+
+/// An event whose `eventType` and `dataVersion` are not in schema.json, e.g. because it was
+/// published by a newer version of this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownEvent {
+    pub id: String,
+    pub subject: String,
+    pub event_type: String,
+    pub data_version: String,
+    pub data: serde_json::Value,
+}
+
+/// The error of a handler, which fails the batch so that it is delivered again.
+pub type HandlerError = Box<dyn std::error::Error + Send + Sync>;
+
+type Handler<State> =
+    Box<dyn Fn(Event, State) -> Option<BoxFuture<'static, Result<(), HandlerError>>> + Send + Sync>;
+type UnknownHandler<State> =
+    Box<dyn Fn(UnknownEvent, State) -> BoxFuture<'static, Result<(), HandlerError>> + Send + Sync>;
+
+/// The response of an `EventReceiver` to a request, to be sent back by the web server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiverResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+/// Receives batches of events from an EventGrid subscription (or from a `WebhookPublisher`) and
+/// passes each of them to the handler registered for its kind of data.
+///
+/// It does not depend on a web framework: a route of any server passes the method, headers and
+/// body of each request to `handle`, and sends back the response it returns. With the `tide`
+/// feature, it is also a tide endpoint.
+///
+/// ```ignore
+/// let receiver = EventReceiver::new()
+///     .on(|event: ReceivedEvent<FileCreatedData>, state: State| async move { ... })
+///     .on(|event: ReceivedEvent<FileDeletedData>, state: State| async move { ... });
+/// app.at("/events").all(receiver);
+/// ```
+///
+/// Events can be in the EventGrid or the CloudEvents schema. It answers the subscription
+/// validation handshakes of both, so the route can be used as the webhook of a subscription
+/// straight away, as long as it is routed for OPTIONS as well as POST requests. Events of kinds
+/// without a handler are ignored, and events whose `eventType` and `dataVersion` are unknown are
+/// passed to the `on_unknown` handler, which logs them by default. Either way they are
+/// acknowledged, as receiving them again would not help.
+///
+/// If a batch cannot be parsed, nothing is handled and it is rejected with 400 Bad Request. The
+/// events in a batch are handled one after the other, and if a handler fails the rest are not
+/// handled and `handle` fails, so that the batch is rejected with 500 Internal Server Error and
+/// delivered again. Handlers should therefore be idempotent.
+pub struct EventReceiver<State> {
+    handlers: HashMap<(String, String), Handler<State>>,
+    unknown_handler: UnknownHandler<State>,
+    webhook_secret: Option<Vec<u8>>,
+}
+
+impl<State: Clone + Send + Sync + 'static> EventReceiver<State> {
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::new(),
+            unknown_handler: Box::new(|event, _| {
+                Box::pin(async move {
+                    tracing::warn!(
+                        "ignoring event {} of unknown eventType {} and dataVersion {}",
+                        event.id,
+                        event.event_type,
+                        event.data_version
+                    );
+                    Ok(())
+                })
+            }),
+            webhook_secret: None,
+        }
+    }
+
+    /// Handles events with data of type `D`, replacing any handler registered for it before.
+    pub fn on<D, F, Fut>(mut self, handler: F) -> Self
+    where
+        D: EventDataVariant + Send + 'static,
+        F: Fn(ReceivedEvent<D>, State) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        self.handlers.insert(
+            (D::EVENT_TYPE.to_string(), D::DATA_VERSION.to_string()),
+            Box::new(move |event: Event, state| {
+                let data = D::from_event_data(event.data)?;
+                let event = ReceivedEvent {
+                    id: event.id,
+                    subject: event.subject,
+                    event_time: event.event_time,
+                    data,
+                };
+                Some(Box::pin(handler(event, state)))
+            }),
+        );
+        self
+    }
+
+    /// Handles events whose `eventType` and `dataVersion` are unknown, instead of logging them.
+    pub fn on_unknown<F, Fut>(mut self, handler: F) -> Self
+    where
+        F: Fn(UnknownEvent, State) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), HandlerError>> + Send + 'static,
+    {
+        self.unknown_handler = Box::new(move |event, state| Box::pin(handler(event, state)));
+        self
+    }
+
+    /// Only accepts requests that are signed with this secret by a `WebhookPublisher`.
+    ///
+    /// EventGrid does not sign its requests, so do not use this for an EventGrid subscription.
+    pub fn with_webhook_secret(mut self, secret: &[u8]) -> Self {
+        self.webhook_secret = Some(secret.to_vec());
+        self
+    }
+
+    /// Handles a request with the given method, headers and body, and returns the response to
+    /// send back. Fails if a handler fails, in which case the server should respond with 500
+    /// Internal Server Error.
+    pub async fn handle(
+        &self,
+        method: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+        state: State,
+    ) -> Result<ReceiverResponse, HandlerError> {
+        match method {
+            "POST" => {}
+            "OPTIONS" => return Ok(abuse_protection_response(headers)),
+            _ => return Ok(response(405)),
+        }
+
+        if let Some(secret) = &self.webhook_secret {
+            let signed = match header(headers, webhook::SIGNATURE_HEADER) {
+                Some(signature) => webhook::verify_signature(secret, body, signature),
+                None => false,
+            };
+            if !signed {
+                return Ok(response(401));
+            }
+        }
+
+        self.receive(body, state).await
+    }
+
+    async fn receive(&self, body: &[u8], state: State) -> Result<ReceiverResponse, HandlerError> {
+        let batch: Vec<serde_json::Value> = match serde_json::from_slice(body) {
+            Ok(batch) => batch,
+            Err(err) => return Ok(bad_request(&format!("invalid batch: {}", err))),
+        };
+
+        if let Some(validation_code) = validation_code(&batch) {
+            return Ok(ReceiverResponse {
+                status: 200,
+                headers: vec![("content-type".into(), "application/json".into())],
+                body: json!({ "validationResponse": validation_code }).to_string(),
+            });
+        }
+
+        let mut events = vec![];
+        for value in batch {
+            let raw = if value.get("specversion").is_some() {
+                RawCloudEvent::deserialize(value)
+                    .and_then(RawCloudEvent::into_raw_event)
+                    .map(|(_, raw)| raw)
+            } else {
+                RawEvent::deserialize(value)
+            };
+            let raw = match raw {
+                Ok(raw) => raw,
+                Err(err) => return Ok(bad_request(&format!("invalid event: {}", err))),
+            };
+            let unknown = UnknownEvent {
+                id: raw.id.clone(),
+                subject: raw.subject.clone(),
+                event_type: raw.event_type.clone(),
+                data_version: raw.data_version.clone(),
+                data: raw.data.clone(),
+            };
+            match raw.into_event() {
+                Ok(event) => events.push(Ok((unknown.event_type, unknown.data_version, event))),
+                Err(EventDataDeserializationError::UnknownVariant) => events.push(Err(unknown)),
+                Err(EventDataDeserializationError::Json(err)) => {
+                    return Ok(bad_request(&format!(
+                        "invalid data in event {}: {}",
+                        unknown.id, err
+                    )))
+                }
+            }
+        }
+
+        for event in events {
+            match event {
+                Ok((event_type, data_version, event)) => {
+                    let handling = self
+                        .handlers
+                        .get(&(event_type, data_version))
+                        .and_then(|handler| handler(event, state.clone()));
+                    if let Some(handling) = handling {
+                        handling.await?;
+                    }
+                }
+                Err(unknown) => (self.unknown_handler)(unknown, state.clone()).await?,
+            }
+        }
+
+        Ok(response(200))
+    }
+}
+
+impl<State: Clone + Send + Sync + 'static> Default for EventReceiver<State> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "tide")]
+#[async_trait::async_trait]
+impl<State: Clone + Send + Sync + 'static> tide::Endpoint<State> for EventReceiver<State> {
+    async fn call(&self, mut req: tide::Request<State>) -> tide::Result {
+        let headers = req
+            .iter()
+            .map(|(name, values)| (name.to_string(), values.last().to_string()))
+            .collect::<Vec<_>>();
+        let body = req.body_bytes().await?;
+
+        let res = self
+            .handle(req.method().as_ref(), &headers, &body, req.state().clone())
+            .await
+            .map_err(|err| {
+                tide::Error::from_str(tide::StatusCode::InternalServerError, err.to_string())
+            })?;
+
+        let mut response = tide::Response::new(res.status);
+        if !res.body.is_empty() {
+            response.set_body(res.body);
+        }
+        for (name, value) in res.headers {
+            response.insert_header(name.as_str(), value);
+        }
+        Ok(response)
+    }
+}
+
+/// The code to send back to EventGrid to validate the subscription, if the batch is the
+/// subscription validation event.
+///
+/// See also: https://docs.microsoft.com/en-us/azure/event-grid/webhook-event-delivery
+fn validation_code(batch: &[serde_json::Value]) -> Option<&str> {
+    match batch {
+        [event] if event["eventType"] == VALIDATION_EVENT_TYPE => {
+            event["data"]["validationCode"].as_str()
+        }
+        _ => None,
+    }
+}
+
+/// The response to the validation handshake of the CloudEvents HTTP webhook spec, which
+/// EventGrid uses for subscriptions with the CloudEvents schema.
+///
+/// See also: https://github.com/cloudevents/spec/blob/v1.0/http-webhook.md#4-abuse-protection
+fn abuse_protection_response(headers: &[(String, String)]) -> ReceiverResponse {
+    match header(headers, "webhook-request-origin") {
+        Some(origin) => ReceiverResponse {
+            headers: vec![
+                ("webhook-allowed-origin".into(), origin.into()),
+                ("webhook-allowed-rate".into(), "*".into()),
+            ],
+            ..response(200)
+        },
+        None => response(400),
+    }
+}
+
+fn bad_request(message: &str) -> ReceiverResponse {
+    tracing::warn!("rejecting events: {}", message);
+    ReceiverResponse {
+        headers: vec![("content-type".into(), "text/plain".into())],
+        body: message.into(),
+        ..response(400)
+    }
+}
+
+fn response(status: u16) -> ReceiverResponse {
+    ReceiverResponse {
+        status,
+        headers: vec![],
+        body: String::new(),
+    }
+}
+
+/// The value of the header with the given name, which is case-insensitive.
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{event_grid::test::event, EventSchema, FileDeletedData, FolderCreatedData};
+    use std::sync::{
+        mpsc::{sync_channel, SyncSender},
+        Arc, Mutex,
+    };
+
+    #[derive(Clone)]
+    struct State {
+        received: Arc<Mutex<Vec<String>>>,
+    }
+
+    fn receiver() -> EventReceiver<State> {
+        EventReceiver::new().on(
+            |event: ReceivedEvent<FolderCreatedData>, state: State| async move {
+                state.received.lock().unwrap().push(event.data.title);
+                Ok(())
+            },
+        )
+    }
+
+    fn state() -> State {
+        State {
+            received: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    async fn post(
+        receiver: EventReceiver<State>,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) -> (ReceiverResponse, Vec<String>) {
+        request(receiver, "POST", body, headers).await
+    }
+
+    async fn request(
+        receiver: EventReceiver<State>,
+        method: &str,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) -> (ReceiverResponse, Vec<String>) {
+        let state = state();
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+
+        let res = receiver
+            .handle(method, &headers, body.as_bytes(), state.clone())
+            .await
+            .unwrap();
+        let received = state.received.lock().unwrap().clone();
+        (res, received)
+    }
+
+    fn batch(events: &[Event]) -> String {
+        serde_json::to_string(events).unwrap()
+    }
+
+    #[async_std::test]
+    async fn answers_the_subscription_validation_handshake() {
+        let body = r#"[{
+            "id": "2d1781af-3a4c-4d7c-bd0c-e34b19da4e66",
+            "topic": "/subscriptions/xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx",
+            "subject": "",
+            "data": {
+                "validationCode": "512d38b6-c7b8-40c8-89fe-f46f9e9622b6",
+                "validationUrl": "https://rp-eastus2.eventgrid.azure.net:553/eventsubscriptions/myeventsub/validate?id=0000000000-0000-0000-0000-00000000000000&t=2018-04-26T20:30:54.4538837Z&apiVersion=2018-05-01-preview&token=1A1A1A1A"
+            },
+            "eventType": "Microsoft.EventGrid.SubscriptionValidationEvent",
+            "eventTime": "2018-01-25T22:12:19.4556811Z",
+            "metadataVersion": "1",
+            "dataVersion": "1"
+        }]"#;
+
+        let (res, _) = post(
+            receiver(),
+            body,
+            &[("aeg-event-type", "SubscriptionValidation")],
+        )
+        .await;
+
+        assert_eq!(res.status, 200);
+        let body: serde_json::Value = serde_json::from_str(&res.body).unwrap();
+        assert_eq!(
+            body,
+            json!({ "validationResponse": "512d38b6-c7b8-40c8-89fe-f46f9e9622b6" })
+        );
+    }
+
+    #[async_std::test]
+    async fn answers_the_cloud_events_validation_handshake() {
+        let (res, _) = request(
+            receiver(),
+            "OPTIONS",
+            "",
+            &[("WebHook-Request-Origin", "eventemitter.example.com")],
+        )
+        .await;
+
+        assert_eq!(res.status, 200);
+        assert_eq!(
+            header(&res.headers, "WebHook-Allowed-Origin").unwrap(),
+            "eventemitter.example.com"
+        );
+    }
+
+    #[async_std::test]
+    async fn dispatches_cloud_events() {
+        let schema = EventSchema::CloudEvents {
+            source: "/test".into(),
+        };
+        let body = format!(
+            "[{}]",
+            String::from_utf8(schema.to_vec(&event()).unwrap()).unwrap()
+        );
+
+        let (res, received) = post(receiver(), &body, &[]).await;
+
+        assert_eq!(res.status, 200);
+        assert_eq!(received, vec!["title"]);
+    }
+
+    #[async_std::test]
+    async fn dispatches_events_to_the_handler_for_their_data() {
+        let deleted = Event::new(
+            "subject",
+            FileDeletedData {
+                file_id: "file".into(),
+                workspace_id: "workspace".into(),
+                user_id: "user".into(),
+                version_id: "version".into(),
+                version_number: 1,
+            },
+        );
+
+        let (res, received) = post(receiver(), &batch(&[event(), deleted, event()]), &[]).await;
+
+        assert_eq!(res.status, 200);
+        assert_eq!(received, vec!["title", "title"]);
+    }
+
+    #[async_std::test]
+    async fn reports_unknown_events_and_handles_the_others() {
+        let (sender, unknown): (SyncSender<UnknownEvent>, _) = sync_channel(10);
+        let receiver = receiver().on_unknown(move |event, _| {
+            sender.send(event).unwrap();
+            async { Ok(()) }
+        });
+        let mut events = serde_json::to_value(&[event()]).unwrap();
+        events
+            .as_array_mut()
+            .unwrap()
+            .push(json!({"id": "id", "subject": "subject", "eventTime": "2020-09-09T10:22:42.235679Z", "eventType": "FolderCreated", "dataVersion": "99", "data": {}}));
+
+        let (res, received) = post(receiver, &events.to_string(), &[]).await;
+
+        assert_eq!(res.status, 200);
+        assert_eq!(received, vec!["title"]);
+        let unknown = unknown.try_recv().unwrap();
+        assert_eq!(
+            (unknown.event_type.as_str(), unknown.data_version.as_str()),
+            ("FolderCreated", "99")
+        );
+    }
+
+    #[async_std::test]
+    async fn rejects_a_batch_with_invalid_data_without_handling_any_of_it() {
+        let mut events = serde_json::to_value(&[event()]).unwrap();
+        events
+            .as_array_mut()
+            .unwrap()
+            .push(json!({"id": "id", "subject": "subject", "eventTime": "2020-09-09T10:22:42.235679Z", "eventType": "FolderCreated", "dataVersion": "1", "data": {}}));
+
+        let (res, received) = post(receiver(), &events.to_string(), &[]).await;
+
+        assert_eq!(res.status, 400);
+        assert!(received.is_empty());
+    }
+
+    #[async_std::test]
+    async fn fails_if_a_handler_fails() {
+        let receiver = EventReceiver::new()
+            .on(|_: ReceivedEvent<FolderCreatedData>, _: State| async { Err("oops".into()) });
+
+        let result = receiver
+            .handle("POST", &[], batch(&[event()]).as_bytes(), state())
+            .await;
+
+        assert_eq!(result.unwrap_err().to_string(), "oops");
+    }
+
+    #[async_std::test]
+    async fn with_a_webhook_secret_only_accepts_signed_requests() {
+        let body = batch(&[event()]);
+        let signature = webhook::signature(b"secret", body.as_bytes());
+
+        let (res, received) = post(
+            receiver().with_webhook_secret(b"secret"),
+            &body,
+            &[(webhook::SIGNATURE_HEADER, &signature)],
+        )
+        .await;
+        assert_eq!(res.status, 200);
+        assert_eq!(received, vec!["title"]);
+
+        let (res, received) = post(
+            receiver().with_webhook_secret(b"other"),
+            &body,
+            &[(webhook::SIGNATURE_HEADER, &signature)],
+        )
+        .await;
+        assert_eq!(res.status, 401);
+        assert!(received.is_empty());
+    }
+
+    #[cfg(feature = "tide")]
+    async fn tide_request(
+        method: tide::http::Method,
+        body: &str,
+        headers: &[(&str, &str)],
+    ) -> (tide::http::Response, Vec<String>) {
+        let receiver = receiver()
+            .on(|_: ReceivedEvent<FileDeletedData>, _: State| async { Err("oops".into()) });
+        let state = state();
+        let mut app = tide::with_state(state.clone());
+        app.at("/events").all(receiver);
+        let url = tide::http::Url::parse("http://localhost/events").unwrap();
+        let mut req = tide::http::Request::new(method, url);
+        for (name, value) in headers {
+            req.insert_header(*name, *value);
+        }
+        req.set_body(body);
+
+        let res: tide::http::Response = app.respond(req).await.unwrap();
+        let received = state.received.lock().unwrap().clone();
+        (res, received)
+    }
+
+    #[cfg(feature = "tide")]
+    #[async_std::test]
+    async fn is_a_tide_endpoint() {
+        use tide::{http::Method, StatusCode};
+
+        let (res, received) = tide_request(Method::Post, &batch(&[event()]), &[]).await;
+        assert_eq!(res.status(), StatusCode::Ok);
+        assert_eq!(received, vec!["title"]);
+
+        let (res, _) = tide_request(
+            Method::Options,
+            "",
+            &[("WebHook-Request-Origin", "eventemitter.example.com")],
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::Ok);
+        assert_eq!(
+            res.header("WebHook-Allowed-Origin").unwrap().as_str(),
+            "eventemitter.example.com"
+        );
+
+        let deleted = Event::new(
+            "subject",
+            FileDeletedData {
+                file_id: "file".into(),
+                workspace_id: "workspace".into(),
+                user_id: "user".into(),
+                version_id: "version".into(),
+                version_number: 1,
+            },
+        );
+        let (res, _) = tide_request(Method::Post, &batch(&[deleted]), &[]).await;
+        assert_eq!(res.status(), StatusCode::InternalServerError);
+    }
+}
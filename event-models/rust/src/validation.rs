@@ -0,0 +1,346 @@
+use crate::{Event, EventPublisher, PublishEventsError};
+use async_trait::async_trait;
+use chrono::DateTime;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// The schema of events that is shared with the TypeScript package.
+const SCHEMA: &str = include_str!("../../schema.json");
+
+/// Checks events against schema.json.
+///
+/// This understands only the parts of JSON Schema that schema.json uses: `$ref` to its own
+/// definitions, `allOf`, `oneOf`, `type`, `properties`, `required`, `enum`, `minLength` and the
+/// `date-time` format. Any other keyword is reported as an error, rather than ignored, so that
+/// events are not let through by a schema this does not understand.
+#[derive(Debug, Clone)]
+pub struct EventValidator {
+    schema: Value,
+}
+
+impl EventValidator {
+    pub fn new() -> Self {
+        Self {
+            schema: serde_json::from_str(SCHEMA).expect("schema.json is valid JSON"),
+        }
+    }
+
+    /// Checks the event as it is serialized. The error lists every problem, one per line.
+    pub fn validate(&self, event: &Event) -> Result<(), String> {
+        let instance = serde_json::to_value(event).map_err(|err| err.to_string())?;
+        self.validate_value(&instance)
+    }
+
+    /// Checks an event that has been serialized already.
+    pub fn validate_value(&self, instance: &Value) -> Result<(), String> {
+        let mut errors = vec![];
+        self.check(&self.schema, instance, "", &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    fn check(&self, schema: &Value, instance: &Value, path: &str, errors: &mut Vec<String>) {
+        let schema = match schema.as_object() {
+            Some(schema) => schema,
+            None => return errors.push(format!("{}: the schema is not an object", path)),
+        };
+        for (keyword, value) in schema {
+            match keyword.as_str() {
+                "$schema" | "title" | "description" | "definitions" => {}
+                "$ref" => match value.as_str().and_then(|reference| self.resolve(reference)) {
+                    Some(schema) => self.check(schema, instance, path, errors),
+                    None => errors.push(format!("{}: cannot resolve $ref {}", path, value)),
+                },
+                "allOf" => {
+                    for schema in value.as_array().into_iter().flatten() {
+                        self.check(schema, instance, path, errors);
+                    }
+                }
+                "oneOf" => {
+                    let mut matched = 0;
+                    // The errors of the schema that the instance is closest to matching, which is
+                    // most likely the one it was meant to match.
+                    let mut closest_errors: Option<Vec<String>> = None;
+                    for schema in value.as_array().into_iter().flatten() {
+                        let mut branch_errors = vec![];
+                        self.check(schema, instance, path, &mut branch_errors);
+                        if branch_errors.is_empty() {
+                            matched += 1;
+                        } else if !matches!(&closest_errors, Some(closest) if closest.len() <= branch_errors.len())
+                        {
+                            closest_errors = Some(branch_errors);
+                        }
+                    }
+                    if matched != 1 {
+                        errors.push(format!(
+                            "{}: matches {} of the schemas in oneOf instead of exactly 1",
+                            path, matched
+                        ));
+                    }
+                    if matched == 0 {
+                        errors.extend(closest_errors.into_iter().flatten());
+                    }
+                }
+                "type" => {
+                    let valid = match value.as_str() {
+                        Some("object") => instance.is_object(),
+                        Some("array") => instance.is_array(),
+                        Some("string") => instance.is_string(),
+                        Some("integer") => instance.is_i64() || instance.is_u64(),
+                        Some("number") => instance.is_number(),
+                        Some("boolean") => instance.is_boolean(),
+                        Some("null") => instance.is_null(),
+                        _ => false,
+                    };
+                    if !valid {
+                        errors.push(format!("{}: is not of type {}", path, value));
+                    }
+                }
+                "properties" => {
+                    if let (Some(properties), Some(instance)) =
+                        (value.as_object(), instance.as_object())
+                    {
+                        for (name, schema) in properties {
+                            if let Some(property) = instance.get(name) {
+                                self.check(schema, property, &format!("{}/{}", path, name), errors);
+                            }
+                        }
+                    }
+                }
+                "required" => {
+                    if let Some(instance) = instance.as_object() {
+                        for name in value.as_array().into_iter().flatten() {
+                            if name
+                                .as_str()
+                                .filter(|name| instance.contains_key(*name))
+                                .is_none()
+                            {
+                                errors.push(format!("{}: {} is required", path, name));
+                            }
+                        }
+                    }
+                }
+                "enum" => {
+                    if value
+                        .as_array()
+                        .filter(|values| values.contains(instance))
+                        .is_none()
+                    {
+                        errors.push(format!("{}: is not one of {}", path, value));
+                    }
+                }
+                "minLength" => {
+                    if let (Some(min_length), Some(instance)) = (value.as_u64(), instance.as_str())
+                    {
+                        if (instance.chars().count() as u64) < min_length {
+                            errors.push(format!("{}: is shorter than {}", path, min_length));
+                        }
+                    }
+                }
+                "format" => match value.as_str() {
+                    Some("date-time") => {
+                        if let Some(instance) = instance.as_str() {
+                            if DateTime::parse_from_rfc3339(instance).is_err() {
+                                errors.push(format!("{}: is not a date-time", path));
+                            }
+                        }
+                    }
+                    _ => errors.push(format!("{}: unsupported format {}", path, value)),
+                },
+                keyword => errors.push(format!("{}: unsupported keyword {}", path, keyword)),
+            }
+        }
+    }
+
+    fn resolve(&self, reference: &str) -> Option<&Value> {
+        reference
+            .strip_prefix('#')
+            .and_then(|pointer| self.schema.pointer(pointer))
+    }
+
+    /// An event of every kind in schema.json, with every property set, as it is serialized.
+    fn samples(&self) -> Vec<Value> {
+        let variants = self.schema["allOf"][1]["oneOf"].as_array();
+        variants
+            .into_iter()
+            .flatten()
+            .filter_map(|variant| variant["$ref"].as_str())
+            .filter_map(|reference| self.resolve(reference))
+            .map(|variant| {
+                let mut sample = self.sample(&json!({ "$ref": "#/definitions/BaseEvent" }));
+                if let (Some(sample), Value::Object(variant)) =
+                    (sample.as_object_mut(), self.sample(variant))
+                {
+                    sample.extend(variant);
+                }
+                sample
+            })
+            .collect()
+    }
+
+    /// A value of the schema that is as simple as possible, but sets every property.
+    fn sample(&self, schema: &Value) -> Value {
+        if let Some(schema) = schema["$ref"]
+            .as_str()
+            .and_then(|reference| self.resolve(reference))
+        {
+            return self.sample(schema);
+        }
+        if let Some(value) = schema["enum"].get(0) {
+            return value.clone();
+        }
+        match schema["type"].as_str() {
+            Some("object") => Value::Object(
+                schema["properties"]
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, schema)| (name.clone(), self.sample(schema)))
+                    .collect(),
+            ),
+            Some("string") if schema["format"] == "date-time" => json!("2020-09-09T10:22:42Z"),
+            Some("string") => json!("x"),
+            Some("integer") => json!(1),
+            _ => Value::Null,
+        }
+    }
+}
+
+impl Default for EventValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Asserts that an event of every kind in schema.json deserializes into `EventData`, serializes
+/// to the same JSON, and is valid. This checks that the models have been generated from the
+/// current schema.json, and that `EventValidator` understands it.
+///
+/// Call it from a test.
+pub fn assert_all_event_data_variants_round_trip_and_validate() {
+    let validator = EventValidator::new();
+    let samples = validator.samples();
+    assert!(!samples.is_empty(), "schema.json does not have any events");
+
+    for sample in samples {
+        let event: Event = serde_json::from_value(sample.clone())
+            .unwrap_or_else(|err| panic!("cannot deserialize {}: {}", sample, err));
+        let round_tripped = serde_json::to_value(&event).unwrap();
+        assert_eq!(round_tripped, sample, "the event changed in a round trip");
+        if let Err(errors) = validator.validate(&event) {
+            panic!("{} is not valid:\n{}", round_tripped, errors);
+        }
+    }
+}
+
+/// Checks every event against schema.json before passing them on to another publisher, and
+/// fails with `PublishEventsError::Invalid` without publishing any of them if one is not valid.
+#[derive(Debug, Clone)]
+pub struct ValidatingPublisher {
+    publisher: Arc<dyn EventPublisher + Send + Sync>,
+    validator: EventValidator,
+}
+
+impl ValidatingPublisher {
+    pub fn new(publisher: Arc<dyn EventPublisher + Send + Sync>) -> Self {
+        Self {
+            publisher,
+            validator: EventValidator::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for ValidatingPublisher {
+    async fn publish_events<'a>(&'a self, events: &'a [Event]) -> Result<(), PublishEventsError> {
+        for event in events {
+            self.validator.validate(event).map_err(|errors| {
+                PublishEventsError::Invalid(format!("event {}: {}", event.id, errors))
+            })?;
+        }
+
+        self.publisher.publish_events(events).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{event_grid::test::event, WorkspaceCreatedData};
+    use std::sync::mpsc::sync_channel;
+
+    #[test]
+    fn every_event_data_variant_round_trips_and_validates() {
+        assert_all_event_data_variants_round_trip_and_validate();
+    }
+
+    #[test]
+    fn validate_rejects_an_empty_id() {
+        let event = Event::new(
+            "workspace",
+            WorkspaceCreatedData {
+                workspace_id: "workspace".into(),
+                user_id: "".into(),
+                title: "title".into(),
+            },
+        );
+
+        let errors = EventValidator::new().validate(&event).unwrap_err();
+
+        assert!(
+            errors.contains("/data/userId: is shorter than 1"),
+            "{}",
+            errors
+        );
+    }
+
+    #[test]
+    fn validate_value_reports_every_problem() {
+        let validator = EventValidator::new();
+        let mut instance = serde_json::to_value(event()).unwrap();
+        instance["eventTime"] = json!("yesterday");
+        instance["data"]
+            .as_object_mut()
+            .unwrap()
+            .remove("workspaceId");
+
+        let errors = validator.validate_value(&instance).unwrap_err();
+
+        assert!(
+            errors.contains("/eventTime: is not a date-time"),
+            "{}",
+            errors
+        );
+        assert!(errors.contains("workspaceId"), "{}", errors);
+    }
+
+    #[async_std::test]
+    async fn publish_events_publishes_nothing_if_an_event_is_invalid() {
+        let (sender, events) = sync_channel(10);
+        let publisher = ValidatingPublisher::new(Arc::new(sender));
+        let mut invalid = event();
+        invalid.id = "".into();
+
+        let result = publisher.publish_events(&[event(), invalid]).await;
+
+        assert!(matches!(result, Err(PublishEventsError::Invalid(_))));
+        assert!(events.try_recv().is_err());
+    }
+
+    #[async_std::test]
+    async fn publish_events_publishes_valid_events() {
+        let (sender, events) = sync_channel(10);
+        let publisher = ValidatingPublisher::new(Arc::new(sender));
+        let event = event();
+
+        publisher
+            .publish_events(std::slice::from_ref(&event))
+            .await
+            .unwrap();
+
+        assert_eq!(events.try_recv().unwrap(), event);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    event_grid::request_bodies, http, Event, EventPublisher, EventSchema, PublishEventsError,
+    RetryPolicy, MAX_REQUEST_SIZE,
+};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use std::time::Duration;
+use tracing::info_span;
+
+/// The header that carries the signature of the request body.
+pub const SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+
+/// Publishes events by POSTing them as a JSON array to a URL, in the same format as EventGrid
+/// (see `with_schema`).
+///
+/// Each request is signed with a secret shared with the receiver: the `X-Hub-Signature-256`
+/// header is `sha256=` followed by the hex-encoded HMAC-SHA256 of the body (see `signature`).
+/// Receivers should compute the signature of the body they received and compare it with the
+/// header in constant time before trusting the events.
+///
+/// Like `EventGridPublisher`, events are sent in requests of at most `MAX_REQUEST_SIZE`, in
+/// order, and transient errors are retried.
+#[derive(Clone)]
+pub struct WebhookPublisher {
+    url: String,
+    secret: Vec<u8>,
+    schema: EventSchema,
+    retry_policy: RetryPolicy,
+    timeout: Duration,
+}
+
+impl WebhookPublisher {
+    pub fn new(url: String, secret: &[u8]) -> Self {
+        Self {
+            url,
+            secret: secret.to_vec(),
+            schema: EventSchema::EventGrid,
+            retry_policy: RetryPolicy::default(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Sets the schema of the events, which is the EventGrid schema by default. With CloudEvents,
+    /// requests are batches in the CloudEvents HTTP binding.
+    pub fn with_schema(mut self, schema: EventSchema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets how long to wait for each request, including each retry. The default is 30 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    async fn send(&self, body: &[u8]) -> Result<(), PublishEventsError> {
+        let span = info_span!(
+            "POST webhook",
+            otel.kind = "client",
+            http.method = "POST",
+            http.url = self.url.as_str(),
+            http.status_code = tracing::field::Empty,
+            http.status_text = tracing::field::Empty
+        );
+
+        let request = surf::post(&self.url)
+            .header(SIGNATURE_HEADER, signature(&self.secret, body))
+            .content_type(self.schema.batch_content_type())
+            .body(body.to_vec());
+        http::send(request, span, self.timeout).await?;
+
+        Ok(())
+    }
+}
+
+// Not derived, so that the secret does not end up in logs.
+impl std::fmt::Debug for WebhookPublisher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookPublisher")
+            .field("url", &self.url)
+            .field("schema", &self.schema)
+            .field("retry_policy", &self.retry_policy)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl EventPublisher for WebhookPublisher {
+    async fn publish_events<'a>(&'a self, events: &'a [Event]) -> Result<(), PublishEventsError> {
+        for body in request_bodies(events, &self.schema, MAX_REQUEST_SIZE)? {
+            self.retry_policy.retry(|| self.send(&body)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The value of the `X-Hub-Signature-256` header for a request with this body.
+pub fn signature(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_varkey(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// Whether `header` is the signature of `body` with `secret`, compared in constant time.
+pub fn verify_signature(secret: &[u8], body: &[u8], header: &str) -> bool {
+    let signature = match header.strip_prefix("sha256=").and_then(decode_hex) {
+        Some(signature) => signature,
+        None => return false,
+    };
+    let mut mac = Hmac::<Sha256>::new_varkey(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    mac.verify(&signature).is_ok()
+}
+
+/// Decodes pairs of hex digits, failing on anything else, including a trailing single digit.
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        event_grid::test::event,
+        http::test::{fake_server, fast_retries},
+        CloudEvent,
+    };
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn signature_is_the_hex_hmac_sha256_of_the_body() {
+        // From RFC 4231, test case 2
+        assert_eq!(
+            signature(b"Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn verify_signature_accepts_only_the_signature_of_the_body() {
+        let header = signature(b"secret", b"body");
+
+        assert!(verify_signature(b"secret", b"body", &header));
+        assert!(!verify_signature(b"secret", b"other body", &header));
+        assert!(!verify_signature(b"other secret", b"body", &header));
+        assert!(!verify_signature(b"secret", b"body", &header[7..]));
+        assert!(!verify_signature(b"secret", b"body", "sha256=zz"));
+        assert!(!verify_signature(
+            b"secret",
+            b"body",
+            &header[..header.len() - 1]
+        ));
+    }
+
+    #[async_std::test]
+    async fn publish_events_posts_signed_json() {
+        let (sender, requests) = channel();
+        let url = fake_server("/hook", &[("204 No Content", "")], Some(sender)).await;
+        let events = vec![event(), event()];
+
+        let result = WebhookPublisher::new(url, b"secret")
+            .publish_events(&events)
+            .await;
+
+        assert!(result.is_ok(), "{:?}", result);
+        let request = requests.recv().unwrap();
+        assert!(request.head.starts_with("POST /hook "));
+        assert_eq!(
+            request.header(SIGNATURE_HEADER),
+            Some(signature(b"secret", &request.body).as_str())
+        );
+        assert_eq!(
+            serde_json::from_slice::<Vec<Event>>(&request.body).unwrap(),
+            events
+        );
+    }
+
+    #[async_std::test]
+    async fn publish_events_posts_cloud_events_batches() {
+        let (sender, requests) = channel();
+        let url = fake_server("/hook", &[("200 OK", "")], Some(sender)).await;
+        let event = event();
+
+        let result = WebhookPublisher::new(url, b"secret")
+            .with_schema(EventSchema::CloudEvents {
+                source: "/test".into(),
+            })
+            .publish_events(std::slice::from_ref(&event))
+            .await;
+
+        assert!(result.is_ok(), "{:?}", result);
+        let request = requests.recv().unwrap();
+        assert!(request
+            .header("content-type")
+            .unwrap()
+            .starts_with("application/cloudevents-batch+json"));
+        let received: Vec<CloudEvent> = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(
+            received,
+            vec![CloudEvent {
+                source: "/test".into(),
+                event
+            }]
+        );
+    }
+
+    #[async_std::test]
+    async fn publish_events_retries_transient_errors() {
+        let url = fake_server("/hook", &[("502 Bad Gateway", ""), ("200 OK", "")], None).await;
+
+        let result = WebhookPublisher::new(url, b"secret")
+            .with_retry_policy(fast_retries())
+            .publish_events(&[event()])
+            .await;
+
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn debug_does_not_show_the_secret() {
+        let publisher = WebhookPublisher::new("http://localhost".into(), b"hunter2");
+
+        assert!(!format!("{:?}", publisher).contains("hunter2"));
+    }
+}
@@ -37,3 +37,11 @@ it("deserializes", () => {
     },
   });
 });
+
+it("rejects empty ids", () => {
+  expect(() =>
+    parse(
+      '{"id":"","subject":"subj","eventTime":"2020-09-09T10:22:42.235679+00:00","eventType":"ContentViewed","dataVersion":"1","data":{"userId":"user","contentId":"content","contentType":"Folder","workspaceId":"workspace"}}'
+    )
+  ).toThrow("Not valid");
+});
@@ -11,11 +11,19 @@ export type Event = BaseEvent &
     | FileCreated
     | FileUpdated
     | FileDeleted
+    | FileMoved
+    | FileRestored
+    | FileRejected
+    | FileScanPassed
+    | FileScanFailed
     | FileDownloaded
     | FolderCreated
     | FolderUpdated
     | FolderDeleted
+    | FolderRestored
     | WorkspaceCreated
+    | WorkspaceUpdated
+    | WorkspaceDeleted
     | WorkspaceMembershipChanged
   );
 
@@ -123,6 +131,116 @@ export interface FileDeleted {
   };
   [k: string]: unknown;
 }
+export interface FileMoved {
+  eventType: "FileMoved";
+  dataVersion: "1";
+  data: {
+    fileId: string;
+    /**
+     * The user that moved the file
+     */
+    userId: string;
+    /**
+     * The folder that the file was in
+     */
+    oldFolderId: string;
+    /**
+     * The folder that the file is in now
+     */
+    newFolderId: string;
+    /**
+     * The workspace that the file was in
+     */
+    oldWorkspaceId: string;
+    /**
+     * The workspace that the file is in now
+     */
+    newWorkspaceId: string;
+    [k: string]: unknown;
+  };
+  [k: string]: unknown;
+}
+export interface FileRestored {
+  eventType: "FileRestored";
+  dataVersion: "1";
+  data: {
+    fileId: string;
+    /**
+     * The user that restored the file
+     */
+    userId: string;
+    versionId: string;
+    versionNumber: number;
+    /**
+     * The workspace that the file is in
+     */
+    workspaceId: string;
+    [k: string]: unknown;
+  };
+  [k: string]: unknown;
+}
+export interface FileRejected {
+  eventType: "FileRejected";
+  dataVersion: "1";
+  data: {
+    /**
+     * What the content of the upload looks like, e.g. "Windows executable"
+     */
+    detectedType: string;
+    /**
+     * The file that the upload was a new version of, if any
+     */
+    fileId?: string;
+    fileName: string;
+    /**
+     * The MIME type that the file was declared as
+     */
+    fileType: string;
+    /**
+     * The folder that the file was going to be added to
+     */
+    folderId: string;
+    uploadId: string;
+    /**
+     * The user that uploaded the file
+     */
+    userId: string;
+    /**
+     * The workspace that the file was going to be added to
+     */
+    workspaceId: string;
+    [k: string]: unknown;
+  };
+  [k: string]: unknown;
+}
+export interface FileScanPassed {
+  eventType: "FileScanPassed";
+  dataVersion: "1";
+  data: {
+    fileId: string;
+    versionId: string;
+    versionNumber: number;
+    workspaceId: string;
+    [k: string]: unknown;
+  };
+  [k: string]: unknown;
+}
+export interface FileScanFailed {
+  eventType: "FileScanFailed";
+  dataVersion: "1";
+  data: {
+    fileId: string;
+    /**
+     * The name of the malware that the scanner found, e.g. "Eicar-Signature"
+     */
+    signature: string;
+    versionId: string;
+    versionNumber: number;
+    workspaceId: string;
+    [k: string]: unknown;
+  };
+  [k: string]: unknown;
+}
 export interface FileDownloaded {
   eventType: "FileDownloaded";
   dataVersion: "1";
@@ -161,6 +279,10 @@ export interface FolderCreated {
      * The role that is needed to view the folder contents
      */
     roleRequired: string;
+    /**
+     * The folder that the folder is in, if it is not at the top level of the workspace
+     */
+    parentFolderId?: string;
     [k: string]: unknown;
   };
   [k: string]: unknown;
@@ -184,6 +306,10 @@ export interface FolderUpdated {
      * The role that is needed to view the folder contents
      */
     roleRequired: string;
+    /**
+     * The folder that the folder is in, if it is not at the top level of the workspace
+     */
+    parentFolderId?: string;
     [k: string]: unknown;
   };
   [k: string]: unknown;
@@ -201,6 +327,31 @@ export interface FolderDeleted {
      * The workspace that the folder is in
      */
     workspaceId: string;
+    /**
+     * The number of files that were deleted with the folder, including those in the folders in it
+     */
+    fileCount: number;
+    [k: string]: unknown;
+  };
+  [k: string]: unknown;
+}
+export interface FolderRestored {
+  eventType: "FolderRestored";
+  dataVersion: "1";
+  data: {
+    folderId: string;
+    /**
+     * The user that restored the folder
+     */
+    userId: string;
+    /**
+     * The workspace that the folder is in
+     */
+    workspaceId: string;
+    /**
+     * The number of files that were restored with the folder, including those in the folders in it
+     */
+    fileCount: number;
     [k: string]: unknown;
   };
   [k: string]: unknown;
@@ -219,6 +370,34 @@ export interface WorkspaceCreated {
   };
   [k: string]: unknown;
 }
+export interface WorkspaceUpdated {
+  eventType: "WorkspaceUpdated";
+  dataVersion: "1";
+  data: {
+    workspaceId: string;
+    title: string;
+    description: string;
+    /**
+     * The id of the user that updated the workspace
+     */
+    userId: string;
+    [k: string]: unknown;
+  };
+  [k: string]: unknown;
+}
+export interface WorkspaceDeleted {
+  eventType: "WorkspaceDeleted";
+  dataVersion: "1";
+  data: {
+    workspaceId: string;
+    /**
+     * The id of the user that deleted the workspace
+     */
+    userId: string;
+    [k: string]: unknown;
+  };
+  [k: string]: unknown;
+}
 export interface WorkspaceMembershipChanged {
   eventType: "WorkspaceMembershipChanged";
   dataVersion: "1";
@@ -38,34 +38,42 @@ describe(FolderHomepage, () => {
     [
       FoldersByWorkspaceDocument,
       {
-        foldersByWorkspace: [
-          {
-            id: "f1",
-            title: "folder 1",
-            description: "first folder",
-            workspace: "1",
-          },
-        ],
+        foldersByWorkspace: {
+          edges: [
+            {
+              node: {
+                id: "f1",
+                title: "folder 1",
+                description: "first folder",
+                workspace: "1",
+              },
+            },
+          ],
+        },
       },
     ],
     [
       FilesByFolderDocument,
       {
-        filesByFolder: [
-          {
-            id: "c12c2d6f-2669-4f82-8351-620ded995abb",
-            title: "London Region NHS England Safeguarding Annual Review",
-            description:
-              "London Region NHS England Safeguarding Annual Review.ppt",
-            folder: "f7f24c43-d3f0-4720-8995-b087316f6b44",
-            fileType: "ppt",
-            fileName:
-              "London Region NHS England Safeguarding Annual Review.ppt",
-            createdAt: "2020-10-06T17:53:45.089829+00:00",
-            modifiedAt: "2020-10-06T17:53:45.089829+00:00",
-            blobStoragePath: "/files/c12c2d6f-2669-4f82-8351-620ded995abb",
-          },
-        ],
+        filesByFolder: {
+          edges: [
+            {
+              node: {
+                id: "c12c2d6f-2669-4f82-8351-620ded995abb",
+                title: "London Region NHS England Safeguarding Annual Review",
+                description:
+                  "London Region NHS England Safeguarding Annual Review.ppt",
+                folder: "f7f24c43-d3f0-4720-8995-b087316f6b44",
+                fileType: "ppt",
+                fileName:
+                  "London Region NHS England Safeguarding Annual Review.ppt",
+                createdAt: "2020-10-06T17:53:45.089829+00:00",
+                modifiedAt: "2020-10-06T17:53:45.089829+00:00",
+                blobStoragePath: "/files/c12c2d6f-2669-4f82-8351-620ded995abb",
+              },
+            },
+          ],
+        },
       },
     ],
   ]);
@@ -38,14 +38,18 @@ describe(UploadFile, () => {
     [
       FoldersByWorkspaceDocument,
       {
-        foldersByWorkspace: [
-          {
-            id: "f1",
-            title: "folder 1",
-            description: "first folder",
-            workspace: "w1",
-          },
-        ],
+        foldersByWorkspace: {
+          edges: [
+            {
+              node: {
+                id: "f1",
+                title: "folder 1",
+                description: "first folder",
+                workspace: "w1",
+              },
+            },
+          ],
+        },
       },
     ],
   ]);
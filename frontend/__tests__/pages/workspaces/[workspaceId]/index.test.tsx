@@ -26,14 +26,18 @@ describe(WorkspaceHomepage, () => {
     [
       FoldersByWorkspaceDocument,
       {
-        foldersByWorkspace: [
-          {
-            id: "f1",
-            title: "folder 1",
-            description: "first folder",
-            workspace: "1",
-          },
-        ],
+        foldersByWorkspace: {
+          edges: [
+            {
+              node: {
+                id: "f1",
+                title: "folder 1",
+                description: "first folder",
+                workspace: "1",
+              },
+            },
+          ],
+        },
       },
     ],
   ]);
@@ -13,11 +13,31 @@ test("takes a snapshot of the component", () => {
     [
       GetWorkspacesDocument,
       {
-        workspaces: [
-          { title: "hospital", id: "1", description: "hospital" },
-          { title: "pharmacy", id: "2", description: "pharmacy" },
-          { title: "ambulance", id: "3", description: "ambulance" },
-        ],
+        workspaces: {
+          edges: [
+            {
+              node: {
+                title: "hospital",
+                id: "1",
+                description: "hospital",
+              },
+            },
+            {
+              node: {
+                title: "pharmacy",
+                id: "2",
+                description: "pharmacy",
+              },
+            },
+            {
+              node: {
+                title: "ambulance",
+                id: "3",
+                description: "ambulance",
+              },
+            },
+          ],
+        },
       },
     ],
   ]);
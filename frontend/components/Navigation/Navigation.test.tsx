@@ -14,20 +14,26 @@ describe.only("<Navigation/>", () => {
     [
       FoldersByWorkspaceDocument,
       {
-        foldersByWorkspace: [
-          {
-            id: "1234",
-            title: "Folder 1",
-            description: "Folder 1",
-            workspace: "1111",
-          },
-          {
-            id: "5678",
-            title: "Folder 2",
-            description: "Folder 2",
-            workspace: "1111",
-          },
-        ],
+        foldersByWorkspace: {
+          edges: [
+            {
+              node: {
+                id: "1234",
+                title: "Folder 1",
+                description: "Folder 1",
+                workspace: "1111",
+              },
+            },
+            {
+              node: {
+                id: "5678",
+                title: "Folder 2",
+                description: "Folder 2",
+                workspace: "1111",
+              },
+            },
+          ],
+        },
       },
     ],
   ]);
@@ -3,6 +3,7 @@ import React, { FC } from "react";
 import Link from "next/link";
 import styled from "styled-components";
 
+import { nodes } from "../../lib/connection";
 import { useFoldersByWorkspaceQuery } from "../../lib/generated/graphql";
 import { DeleteIcon, EditIcon, MoveIcon, UploadIcon } from "../Icon";
 import { Menu, MenuItem } from "../Menu";
@@ -123,7 +124,7 @@ const Navigation: FC<Props> = ({
     open: require("../../public/folderOpen.svg"),
   };
 
-  const alphabetisedFolders = [...data.foldersByWorkspace].sort((a, b) =>
+  const alphabetisedFolders = nodes(data.foldersByWorkspace).sort((a, b) =>
     a.title.localeCompare(b.title, "en", { sensitivity: "base" })
   );
 
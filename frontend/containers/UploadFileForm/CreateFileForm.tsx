@@ -79,7 +79,11 @@ const CreateFileForm: FC<Props> = ({ workspaceId, folderId }) => {
     disableButton(true);
     try {
       const { error, data } = await fileUploadUrls({
-        count: formData.files.length,
+        files: Array.from(formData.files).map((file) => ({
+          fileName: file.name,
+          fileSize: file.size,
+          fileType: file.type,
+        })),
       });
       if (error) {
         throw new Error(`Failed to get upload URL: ${error.toString()}`);
@@ -60,7 +60,15 @@ const UpdateFileForm: FC<Props> = ({
 
   const onSubmit = async (formData: FormData) => {
     try {
-      const { error, data } = await fileUploadUrls({ count: 1 });
+      const { error, data } = await fileUploadUrls({
+        files: [
+          {
+            fileName: formData.files[0].name,
+            fileSize: formData.files[0].size,
+            fileType: formData.files[0].type,
+          },
+        ],
+      });
       if (error) {
         throw new Error(`Failed to get upload URL: ${error.toString()}`);
       }
@@ -0,0 +1,18 @@
+import { nodes } from "./connection";
+
+describe(nodes, () => {
+  test("returns the nodes of the edges in order", () => {
+    const connection = {
+      edges: [
+        { cursor: "a", node: { id: "1" } },
+        { cursor: "b", node: { id: "2" } },
+      ],
+    };
+
+    expect(nodes(connection)).toEqual([{ id: "1" }, { id: "2" }]);
+  });
+
+  test("returns an empty list for an empty connection", () => {
+    expect(nodes({ edges: [] })).toEqual([]);
+  });
+});
@@ -0,0 +1,6 @@
+/**
+ * The workspace service returns lists as paginated connections. The pages show
+ * a single page of them, so they only need the nodes.
+ */
+export const nodes = <T>(connection: { edges: { node: T }[] }): T[] =>
+  connection.edges.map((edge) => edge.node);
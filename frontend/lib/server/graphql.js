@@ -31,14 +31,14 @@ const getOrCreateUser = async ({ authId, name, emailAddress }) => {
   }
 };
 
-const getFileDownloadUrl = async ({ authId, fileId }) => {
+/**
+ * @param {{ headers: Record<string, string>, fileId: string }} params the
+ *   headers come from `authHeaders`
+ */
+const getFileDownloadUrl = async ({ headers, fileId }) => {
   const client = createClient({
     url: requireEnv("WORKSPACE_SERVICE_GRAPHQL_ENDPOINT"),
-    fetchOptions: {
-      headers: {
-        "x-user-auth-id": authId,
-      },
-    },
+    fetchOptions: { headers },
   });
   const query = gql`
     mutation CreateFileDownloadUrl($id: ID!) {
@@ -0,0 +1,129 @@
+// @ts-check
+
+const axios = require("axios");
+
+const { reportError } = require("./reportError");
+
+const B2C_TENANT_URL =
+  "https://futurenhsplatform.b2clogin.com/futurenhsplatform.onmicrosoft.com";
+
+// Refresh ID tokens this long before they expire, so that they are still
+// valid when the workspace service checks them.
+const REFRESH_MARGIN_SECONDS = 5 * 60;
+
+/**
+ * The B2C ID token of the logged in user, which we keep in their session and
+ * forward to the workspace service.
+ *
+ * @typedef {object} IdToken
+ * @property {string} token
+ * @property {number} expiresAt seconds since the epoch
+ * @property {string} policy the user flow that issued the token, which is
+ *   needed to refresh it
+ * @property {string} [refreshToken]
+ */
+
+/**
+ * @typedef {object} RequestWithSession
+ * @property {{ authId: string }} [user]
+ * @property {{ idToken?: IdToken }} [session]
+ */
+
+/**
+ * @param {string} token
+ * @param {string} [refreshToken]
+ * @returns {IdToken}
+ */
+const parseIdToken = (token, refreshToken) => {
+  const claims = JSON.parse(
+    Buffer.from(token.split(".")[1], "base64").toString()
+  );
+  return {
+    token,
+    expiresAt: claims.exp,
+    policy: claims.tfp || claims.acr,
+    refreshToken,
+  };
+};
+
+/**
+ * @param {IdToken} idToken
+ * @param {{ clientId: string, clientSecret: string }} client
+ * @returns {Promise<IdToken>}
+ */
+const refreshIdToken = async (idToken, { clientId, clientSecret }) => {
+  if (!idToken.refreshToken) {
+    throw new Error("The ID token cannot be refreshed without a refresh token");
+  }
+
+  const response = await axios.post(
+    `${B2C_TENANT_URL}/${idToken.policy}/oauth2/v2.0/token`,
+    new URLSearchParams({
+      grant_type: "refresh_token",
+      client_id: clientId,
+      client_secret: clientSecret,
+      refresh_token: idToken.refreshToken,
+      scope: "openid offline_access",
+    }).toString(),
+    { headers: { "content-type": "application/x-www-form-urlencoded" } }
+  );
+  return parseIdToken(response.data.id_token, response.data.refresh_token);
+};
+
+/**
+ * Express middleware that refreshes the ID token of the logged in user shortly
+ * before it expires. Users without an ID token, or whose token cannot be
+ * refreshed, are logged out, so that they log in again.
+ *
+ * @param {{ clientId: string, clientSecret: string }} client
+ */
+const keepIdTokenFresh = (client) =>
+  /**
+   * @param {any} req
+   * @param {any} _res
+   * @param {() => void} next
+   */
+  async (req, _res, next) => {
+    if (req.user) {
+      try {
+        const idToken = req.session.idToken;
+        if (!idToken) {
+          throw new Error("The session does not have an ID token");
+        }
+        if (idToken.expiresAt - REFRESH_MARGIN_SECONDS < Date.now() / 1000) {
+          req.session.idToken = await refreshIdToken(idToken, client);
+        }
+      } catch (err) {
+        reportError(err);
+        delete req.session.idToken;
+        req.logout();
+      }
+    }
+    next();
+  };
+
+/**
+ * The headers that identify the logged in user to the workspace service.
+ *
+ * This is their ID token, unless they logged in with the noop strategy during
+ * development. Then it is their auth id, which the workspace service only
+ * trusts when it runs with TRUST_AUTH_ID_HEADER.
+ *
+ * @param {RequestWithSession} [req]
+ * @returns {Record<string, string>}
+ */
+const authHeaders = (req) => {
+  const idToken = req?.session?.idToken;
+  if (idToken) {
+    return { authorization: `Bearer ${idToken.token}` };
+  }
+  const authId = req?.user?.authId;
+  return authId ? { "x-user-auth-id": authId } : {};
+};
+
+module.exports = {
+  B2C_TENANT_URL,
+  authHeaders,
+  keepIdTokenFresh,
+  parseIdToken,
+};
@@ -0,0 +1,137 @@
+import axios from "axios";
+
+import { withMockedConsole } from "../test-helpers/console";
+import { authHeaders, keepIdTokenFresh, parseIdToken } from "./idToken";
+
+jest.mock("axios");
+
+const mockedAxios = axios as jest.Mocked<typeof axios>;
+
+const fakeToken = (claims: object) =>
+  `header.${Buffer.from(JSON.stringify(claims)).toString("base64")}.signature`;
+
+const now = () => Math.floor(Date.now() / 1000);
+
+const client = { clientId: "client", clientSecret: "secret" };
+
+describe(parseIdToken, () => {
+  test("reads the expiry and user flow from the token", () => {
+    const token = fakeToken({ exp: 1600000000, tfp: "b2c_1_signin" });
+
+    expect(parseIdToken(token, "refresh")).toEqual({
+      token,
+      expiresAt: 1600000000,
+      policy: "b2c_1_signin",
+      refreshToken: "refresh",
+    });
+  });
+});
+
+describe(authHeaders, () => {
+  test("sends the ID token as a bearer token", () => {
+    const token = fakeToken({ exp: now() + 3600, tfp: "b2c_1_signin" });
+
+    const headers = authHeaders({
+      user: { authId: "auth-id" },
+      session: { idToken: parseIdToken(token) },
+    });
+
+    expect(headers).toEqual({ authorization: `Bearer ${token}` });
+  });
+
+  test("sends the auth id without an ID token", () => {
+    const headers = authHeaders({ user: { authId: "auth-id" }, session: {} });
+
+    expect(headers).toEqual({ "x-user-auth-id": "auth-id" });
+  });
+
+  test("sends nothing if not logged in", () => {
+    expect(authHeaders({ session: {} })).toEqual({});
+  });
+});
+
+describe(keepIdTokenFresh, () => {
+  withMockedConsole();
+
+  const run = async (req: any) => {
+    const next = jest.fn();
+    await keepIdTokenFresh(client)(req, {}, next);
+    expect(next).toHaveBeenCalled();
+    return req;
+  };
+
+  const loggedInRequest = (idToken?: object) => ({
+    user: { authId: "auth-id" },
+    session: { idToken },
+    logout: jest.fn(),
+  });
+
+  beforeEach(() => {
+    mockedAxios.post.mockReset();
+  });
+
+  test("keeps a token that does not expire soon", async () => {
+    const idToken = parseIdToken(
+      fakeToken({ exp: now() + 3600, tfp: "b2c_1_signin" }),
+      "refresh"
+    );
+
+    const req = await run(loggedInRequest(idToken));
+
+    expect(req.session.idToken).toBe(idToken);
+    expect(mockedAxios.post).not.toHaveBeenCalled();
+  });
+
+  test("refreshes a token that expires soon", async () => {
+    const newToken = fakeToken({ exp: now() + 3600, tfp: "b2c_1_signin" });
+    mockedAxios.post.mockResolvedValue({
+      data: { id_token: newToken, refresh_token: "new-refresh" },
+    });
+
+    const req = await run(
+      loggedInRequest(
+        parseIdToken(
+          fakeToken({ exp: now() + 60, tfp: "b2c_1_signin" }),
+          "refresh"
+        )
+      )
+    );
+
+    expect(mockedAxios.post).toHaveBeenCalledWith(
+      "https://futurenhsplatform.b2clogin.com/futurenhsplatform.onmicrosoft.com/b2c_1_signin/oauth2/v2.0/token",
+      expect.stringContaining("refresh_token=refresh"),
+      expect.anything()
+    );
+    expect(req.session.idToken.token).toBe(newToken);
+    expect(req.session.idToken.refreshToken).toBe("new-refresh");
+    expect(req.logout).not.toHaveBeenCalled();
+  });
+
+  test("logs out if the token cannot be refreshed", async () => {
+    mockedAxios.post.mockRejectedValue(new Error("invalid_grant"));
+
+    const req = await run(
+      loggedInRequest(
+        parseIdToken(
+          fakeToken({ exp: now() - 60, tfp: "b2c_1_signin" }),
+          "refresh"
+        )
+      )
+    );
+
+    expect(req.session.idToken).toBeUndefined();
+    expect(req.logout).toHaveBeenCalled();
+  });
+
+  test("logs out a session without a token", async () => {
+    const req = await run(loggedInRequest());
+
+    expect(req.logout).toHaveBeenCalled();
+  });
+
+  test("ignores requests that are not logged in", async () => {
+    const req = await run({ session: {}, logout: jest.fn() });
+
+    expect(req.logout).not.toHaveBeenCalled();
+  });
+});
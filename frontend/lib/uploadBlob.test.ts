@@ -0,0 +1,59 @@
+import { BlockBlobClient } from "@azure/storage-blob";
+
+import { uploadBlob } from "./uploadBlob";
+
+jest.mock("@azure/storage-blob");
+
+const mockedBlockBlobClient = BlockBlobClient as jest.MockedClass<
+  typeof BlockBlobClient
+>;
+
+const file = new File(["hello"], "hello.txt", { type: "text/plain" });
+
+describe(uploadBlob, () => {
+  const fetchMock = jest.fn();
+
+  beforeEach(() => {
+    fetchMock.mockReset();
+    mockedBlockBlobClient.mockClear();
+    global.fetch = fetchMock;
+  });
+
+  test("puts the whole file to a presigned S3 URL", async () => {
+    fetchMock.mockResolvedValue({ ok: true, status: 200 });
+    const url =
+      "http://localhost:9000/upload/id?X-Amz-Algorithm=AWS4-HMAC-SHA256&X-Amz-Signature=abc";
+
+    await uploadBlob(url, file);
+
+    expect(fetchMock).toHaveBeenCalledWith(url, {
+      method: "PUT",
+      headers: { "content-type": "text/plain" },
+      body: file,
+    });
+    expect(mockedBlockBlobClient).not.toHaveBeenCalled();
+  });
+
+  test("fails if the S3 upload fails", async () => {
+    fetchMock.mockResolvedValue({ ok: false, status: 403 });
+
+    await expect(
+      uploadBlob("http://localhost:9000/upload/id?X-Amz-Signature=abc", file)
+    ).rejects.toThrow("Failed to upload file: 403");
+  });
+
+  test("uploads to Azure with the block blob client", async () => {
+    const uploadBrowserData = jest.fn().mockResolvedValue({});
+    mockedBlockBlobClient.mockImplementation(
+      () => (({ uploadBrowserData } as unknown) as BlockBlobClient)
+    );
+    const url =
+      "http://localhost:10000/devstoreaccount1/upload/id?sv=2019&sig=abc";
+
+    await uploadBlob(url, file);
+
+    expect(mockedBlockBlobClient).toHaveBeenCalledWith(url);
+    expect(uploadBrowserData).toHaveBeenCalledWith(file, expect.anything());
+    expect(fetchMock).not.toHaveBeenCalled();
+  });
+});
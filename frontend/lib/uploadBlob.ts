@@ -1,8 +1,30 @@
 import { BlockBlobClient } from "@azure/storage-blob";
 
+// Upload URLs from an S3-compatible store are presigned for a single PUT of
+// the whole file, so they cannot be used with the Azure block upload API.
+const isPresignedS3Url = (url: string) =>
+  new URL(url).searchParams.has("X-Amz-Signature");
+
+const putBlob = async (url: string, file: File) => {
+  const response = await fetch(url, {
+    method: "PUT",
+    headers: { "content-type": file.type },
+    body: file,
+  });
+
+  if (!response.ok) {
+    throw new Error(`Failed to upload file: ${response.status}`);
+  }
+};
+
 export const uploadBlob = async (url: string, file: File) => {
+  if (isPresignedS3Url(url)) {
+    return putBlob(url, file);
+  }
+
   const blobClient = new BlockBlobClient(url);
   const uploadResponse = await blobClient.uploadBrowserData(file, {
+    blobHTTPHeaders: { blobContentType: file.type },
     maxSingleShotSize: 4 * 1024 * 1024,
   });
 
@@ -21,6 +21,7 @@ import {
   MutationDeleteFileArgs,
   MutationCreateFolderArgs,
 } from "./generated/graphql";
+import { authHeaders } from "./server/idToken";
 import { requireEnv } from "./server/requireEnv";
 
 const isServerSide = typeof window === "undefined";
@@ -84,7 +85,10 @@ const mutationUpdateResolvers: MutationUpdatesConfig = {
           variables: { workspace: args.newFolder.workspace },
         },
         update<FoldersByWorkspaceQuery>((data) => {
-          data.foldersByWorkspace.push({ __typename, id, title });
+          data.foldersByWorkspace.edges.push({
+            __typename: "FolderEdge",
+            node: { __typename, id, title },
+          });
           return data;
         })
       );
@@ -123,15 +127,18 @@ const mutationUpdateResolvers: MutationUpdatesConfig = {
           variables: { folder: args.newFile.folder },
         },
         update<FilesByFolderQuery>((data) => {
-          data.filesByFolder.push({
-            __typename,
-            id,
-            folder,
-            title,
-            description,
-            fileName,
-            fileType,
-            modifiedAt,
+          data.filesByFolder.edges.push({
+            __typename: "FileEdge",
+            node: {
+              __typename,
+              id,
+              folder,
+              title,
+              description,
+              fileName,
+              fileType,
+              modifiedAt,
+            },
           });
           return data;
         })
@@ -159,7 +166,19 @@ export default function withUrqlClient(
       const exchanges = [
         dedupExchange,
         cacheExchange({
-          keys: {},
+          // Connections, their edges and page info have no id of their own, so
+          // they are cached as part of the field that returned them.
+          keys: {
+            FileConnection: () => null,
+            FileEdge: () => null,
+            FolderConnection: () => null,
+            FolderEdge: () => null,
+            PageInfo: () => null,
+            UserConnection: () => null,
+            UserEdge: () => null,
+            WorkspaceConnection: () => null,
+            WorkspaceEdge: () => null,
+          },
           updates: {
             Mutation: mutationUpdateResolvers as UpdatesConfig["Mutation"],
           },
@@ -173,10 +192,8 @@ export default function withUrqlClient(
       }
       const fetchOptions = isServerSide
         ? {
-            headers: {
-              // @ts-ignore
-              "x-user-auth-id": ctx?.req?.user?.authId,
-            },
+            // @ts-ignore
+            headers: authHeaders(ctx?.req),
           }
         : undefined;
       return {
@@ -3,6 +3,7 @@ import { ApolloServer } from "apollo-server-micro";
 import { NextApiRequest, NextApiResponse } from "next";
 
 import { User } from "../../lib/auth";
+import { authHeaders } from "../../lib/server/idToken";
 import { requireEnv } from "../../lib/server/requireEnv";
 
 const workspaceAPIServerUrl = requireEnv("WORKSPACE_SERVICE_GRAPHQL_ENDPOINT");
@@ -19,10 +20,10 @@ const gateway = new ApolloGateway({
       url,
       willSendRequest: ({ context, request }): void => {
         // context will be empty for schema requests
-        const authId = context.user?.authId;
-        if (authId) {
-          request.http?.headers.set("x-user-auth-id", authId);
-        }
+        const headers: Record<string, string> = context.headers || {};
+        Object.entries(headers).forEach(([name, value]) => {
+          request.http?.headers.set(name, value);
+        });
       },
     }),
 });
@@ -35,7 +36,7 @@ const server = new ApolloServer({
       "request.credentials": "include",
     },
   },
-  context: ({ req }) => ({ user: req.user }),
+  context: ({ req }) => ({ user: req.user, headers: authHeaders(req) }),
 });
 
 export const config = {
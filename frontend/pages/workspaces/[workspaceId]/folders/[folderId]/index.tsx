@@ -25,6 +25,7 @@ import { NavHeader } from "../../../../../components/NavHeader";
 import { Navigation } from "../../../../../components/Navigation";
 import { PageLayout } from "../../../../../components/PageLayout";
 import { MobileList, Table } from "../../../../../components/Table";
+import { nodes } from "../../../../../lib/connection";
 import {
   File,
   useFilesByFolderQuery,
@@ -85,6 +86,7 @@ const FolderHomepage: NextPage = () => {
   const [files] = useFilesByFolderQuery({
     variables: { folder: folderId },
   });
+  const fileList = files.data ? nodes(files.data.filesByFolder) : [];
 
   const accessPermitted =
     folder.error?.graphQLErrors[0]?.extensions?.details === "ACCESS_DENIED"
@@ -180,7 +182,7 @@ const FolderHomepage: NextPage = () => {
                 {folder.error && <p> Oh no... {folder.error?.message} </p>}
                 {files.error && <p> Oh no... {files.error?.message} </p>}
                 {files.fetching || (!files.data && <p>Loading...</p>)}
-                {files.data && files.data.filesByFolder.length > 0 && (
+                {fileList.length > 0 && (
                   <>
                     <MobileList
                       tableHeading="Files"
@@ -190,7 +192,7 @@ const FolderHomepage: NextPage = () => {
                         { content: MobileModifiedAtCell },
                         { content: mobileActionsCell },
                       ]}
-                      data={files.data.filesByFolder as File[]}
+                      data={fileList as File[]}
                     />
                     <Table
                       tableHeading="Files"
@@ -200,7 +202,7 @@ const FolderHomepage: NextPage = () => {
                         { heading: "Last modified", content: ModifiedAtCell },
                         { heading: "Actions", content: downloadCell },
                       ]}
-                      data={files.data.filesByFolder as File[]}
+                      data={fileList as File[]}
                     />
                   </>
                 )}
@@ -14,6 +14,7 @@ import { NavHeader } from "../../../components/NavHeader";
 import { Navigation } from "../../../components/Navigation";
 import { PageLayout } from "../../../components/PageLayout";
 import { ResponsiveTable } from "../../../components/Table";
+import { nodes } from "../../../lib/connection";
 import {
   User,
   useGetWorkspaceWithMembersQuery,
@@ -97,6 +98,8 @@ const WorkspaceMembersPage: NextPage = () => {
     });
 
   const workspaceTitle = (!fetching && data?.workspace.title) || "Loading...";
+  const admins = data ? nodes(data.workspace.admins) : [];
+  const members = data ? nodes(data.workspace.members) : [];
 
   return (
     <>
@@ -113,7 +116,7 @@ const WorkspaceMembersPage: NextPage = () => {
               {data && (
                 <>
                   <CountSentence>
-                    Showing all administrators ({data.workspace.admins.length})
+                    Showing all administrators ({admins.length})
                   </CountSentence>
                   <ResponsiveTable
                     tableHeading="Administrators"
@@ -140,11 +143,11 @@ const WorkspaceMembersPage: NextPage = () => {
                         content: makeNonAdminButtonCell,
                       },
                     ]}
-                    data={data.workspace.admins as User[]}
+                    data={admins as User[]}
                   />
 
                   <CountSentence>
-                    Showing all members ({data.workspace.members.length})
+                    Showing all members ({members.length})
                   </CountSentence>
                   <ResponsiveTable
                     tableHeading="Members"
@@ -171,7 +174,7 @@ const WorkspaceMembersPage: NextPage = () => {
                         content: makeAdminButtonCell,
                       },
                     ]}
-                    data={data.workspace.members as User[]}
+                    data={members as User[]}
                   />
                 </>
               )}
@@ -8,6 +8,7 @@ import { MainHeading } from "../../components/MainHeading";
 import { NavHeader } from "../../components/NavHeader";
 import { PageLayout } from "../../components/PageLayout";
 import WorkspaceDirectoryItem from "../../components/Workspaces/WorkspaceDirectoryItem";
+import { nodes } from "../../lib/connection";
 import { useGetWorkspacesQuery } from "../../lib/generated/graphql";
 import withUrqlClient from "../../lib/withUrqlClient";
 
@@ -31,15 +32,16 @@ const WorkspaceDirectory: NextPage = () => {
         <MainHeading>My workspaces</MainHeading>
         {fetching && <p>Loading...</p>}
         {error && <p> Oh no... {error?.message} </p>}
-        {data?.workspaces.map((workspace) => {
-          return (
-            <WorkspaceDirectoryItem
-              title={workspace.title}
-              id={workspace.id}
-              key={workspace.id}
-            />
-          );
-        })}
+        {data &&
+          nodes(data.workspaces).map((workspace) => {
+            return (
+              <WorkspaceDirectoryItem
+                title={workspace.title}
+                id={workspace.id}
+                key={workspace.id}
+              />
+            );
+          })}
       </PageContent>
       <Footer />
     </PageLayout>
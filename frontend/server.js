@@ -12,6 +12,12 @@ const Noop = require("./lib/server/noop-passport-strategy");
 const url = require("url");
 const { promises: fs } = require("fs");
 const { getOrCreateUser, getFileDownloadUrl } = require("./lib/server/graphql");
+const {
+  B2C_TENANT_URL,
+  authHeaders,
+  keepIdTokenFresh,
+  parseIdToken,
+} = require("./lib/server/idToken");
 const { reportError } = require("./lib/server/reportError");
 const { requireEnv } = require("./lib/server/requireEnv");
 
@@ -109,19 +115,32 @@ async function main() {
       ? new Noop()
       : new OIDCStrategy(
           {
-            identityMetadata:
-              "https://futurenhsplatform.b2clogin.com/futurenhsplatform.onmicrosoft.com/v2.0/.well-known/openid-configuration",
+            identityMetadata: `${B2C_TENANT_URL}/v2.0/.well-known/openid-configuration`,
             clientID: requireEnv("AAD_B2C_CLIENT_ID"),
             clientSecret: requireEnv("AAD_B2C_CLIENT_SECRET"),
             responseType: "code",
             responseMode: "query",
             redirectUrl: `${requireEnv("ORIGIN")}/auth/callback`,
-            passReqToCallback: false,
+            passReqToCallback: true,
             allowHttpForRedirectUrl: dev,
             isB2C: true,
+            // to get a refresh token, so that the ID token can be refreshed
+            scope: ["offline_access"],
           },
-          async (profile, done) => {
+          async (
+            req,
+            _iss,
+            _sub,
+            profile,
+            _jwtClaims,
+            _accessToken,
+            refreshToken,
+            params,
+            done
+          ) => {
             try {
+              // The workspace service identifies users by this token.
+              req.session.idToken = parseIdToken(params.id_token, refreshToken);
               const response = await getOrCreateUser({
                 authId: profile.sub,
                 name: profile.displayName,
@@ -167,6 +186,14 @@ async function main() {
   );
   server.use(passport.initialize());
   server.use(passport.session());
+  if (!dev) {
+    server.use(
+      keepIdTokenFresh({
+        clientId: requireEnv("AAD_B2C_CLIENT_ID"),
+        clientSecret: requireEnv("AAD_B2C_CLIENT_SECRET"),
+      })
+    );
+  }
   server.get(
     "/auth/login",
     configureUserFlow("b2c_1_signin"),
@@ -194,7 +221,7 @@ async function main() {
     async (req, res) => {
       try {
         const response = await getFileDownloadUrl({
-          authId: req.user.authId,
+          headers: authHeaders(req),
           fileId: req.params.fileId,
         });
         const temporaryBlobStoragePath = response.fileDownloadUrl;
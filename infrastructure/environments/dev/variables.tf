@@ -28,3 +28,11 @@ variable "enable_analytics" {
   description = "Should analytics be enabled? true or false"
   type        = bool
 }
+
+variable "b2c_tenant_id" {
+  description = "Directory (tenant) ID of the Azure AD B2C tenant. See docs/aad-b2c.md."
+}
+
+variable "b2c_client_id" {
+  description = "Application (client) ID of the \"Development\" app registration in Azure AD B2C. See docs/aad-b2c.md."
+}
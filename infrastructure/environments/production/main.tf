@@ -92,6 +92,8 @@ module databases {
   eventgrid_topic_endpoint = module.platform.eventgrid_topic_endpoint
   eventgrid_topic_key      = module.platform.eventgrid_topic_key
   instrumentation_key      = module.platform.instrumentation_key
+  b2c_tenant_id            = var.b2c_tenant_id
+  b2c_client_id            = var.b2c_client_id
 }
 
 module storage {
@@ -12,3 +12,11 @@ variable "ip_whitelist_postgresql" {
   description = "List of allowed IPs for use with PostgreSQL for debugging production"
   type        = map(string)
 }
+
+variable "b2c_tenant_id" {
+  description = "Directory (tenant) ID of the Azure AD B2C tenant. See docs/aad-b2c.md."
+}
+
+variable "b2c_client_id" {
+  description = "Application (client) ID of the \"Production\" app registration in Azure AD B2C. See docs/aad-b2c.md."
+}
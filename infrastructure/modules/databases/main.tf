@@ -137,3 +137,16 @@ resource "kubernetes_config_map" "workspace_service_telemetry" {
     instrumentation_key = var.instrumentation_key
   }
 }
+
+resource "kubernetes_config_map" "workspace_service_auth" {
+  metadata {
+    name      = "workspace-service-auth"
+    namespace = kubernetes_namespace.workspace_service.metadata[0].name
+  }
+  data = {
+    # The frontend forwards the B2C ID tokens of its users, so they are issued to the frontend.
+    issuer   = "https://futurenhsplatform.b2clogin.com/${var.b2c_tenant_id}/v2.0/"
+    audience = var.b2c_client_id
+    jwks_url = "https://futurenhsplatform.b2clogin.com/futurenhsplatform.onmicrosoft.com/b2c_1_signin/discovery/v2.0/keys"
+  }
+}
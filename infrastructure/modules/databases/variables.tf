@@ -17,3 +17,11 @@ variable "eventgrid_topic_key" {
 variable "instrumentation_key" {
   description = "Application Insights instrumentation key"
 }
+
+variable "b2c_tenant_id" {
+  description = "Directory (tenant) ID of the Azure AD B2C tenant"
+}
+
+variable "b2c_client_id" {
+  description = "Application (client) ID of the frontend's app registration in Azure AD B2C"
+}
@@ -0,0 +1,57 @@
+-- Postgres 9.5 has no generated columns, so triggers keep the search vectors up to date.
+
+ALTER TABLE workspaces ADD COLUMN search_vector tsvector;
+
+CREATE FUNCTION workspaces_search_vector() RETURNS trigger AS $$
+BEGIN
+  NEW.search_vector :=
+    setweight(to_tsvector('english', NEW.title), 'A') ||
+    setweight(to_tsvector('english', NEW.description), 'B');
+  RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER workspaces_search_vector
+  BEFORE INSERT OR UPDATE OF title, description ON workspaces
+  FOR EACH ROW EXECUTE PROCEDURE workspaces_search_vector();
+
+ALTER TABLE folders ADD COLUMN search_vector tsvector;
+
+CREATE FUNCTION folders_search_vector() RETURNS trigger AS $$
+BEGIN
+  NEW.search_vector :=
+    setweight(to_tsvector('english', NEW.title), 'A') ||
+    setweight(to_tsvector('english', NEW.description), 'B');
+  RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER folders_search_vector
+  BEFORE INSERT OR UPDATE OF title, description ON folders
+  FOR EACH ROW EXECUTE PROCEDURE folders_search_vector();
+
+ALTER TABLE file_versions ADD COLUMN search_vector tsvector;
+
+-- file names are split on punctuation so that "annual-report.pdf" matches "report"
+CREATE FUNCTION file_versions_search_vector() RETURNS trigger AS $$
+BEGIN
+  NEW.search_vector :=
+    setweight(to_tsvector('english', NEW.file_title), 'A') ||
+    setweight(to_tsvector('english', NEW.file_description), 'B') ||
+    setweight(to_tsvector('simple', regexp_replace(NEW.file_name, '[[:punct:]]', ' ', 'g')), 'C');
+  RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER file_versions_search_vector
+  BEFORE INSERT OR UPDATE OF file_title, file_description, file_name ON file_versions
+  FOR EACH ROW EXECUTE PROCEDURE file_versions_search_vector();
+
+-- fill in the search vectors of existing rows
+UPDATE workspaces SET title = title;
+UPDATE folders SET title = title;
+UPDATE file_versions SET file_title = file_title;
+
+CREATE INDEX workspaces_search_idx ON workspaces USING GIN (search_vector);
+CREATE INDEX folders_search_idx ON folders USING GIN (search_vector);
+CREATE INDEX file_versions_search_idx ON file_versions USING GIN (search_vector);
@@ -0,0 +1,5 @@
+ALTER TABLE folders
+  ADD COLUMN parent uuid REFERENCES folders,
+  ADD CONSTRAINT folders_parent_not_self CHECK (parent <> id);
+
+CREATE INDEX folders_parent_idx ON folders (parent);
@@ -0,0 +1,13 @@
+-- A folder that is deleted keeps its rows, and those of the folders and files in it, until they
+-- are purged. `deleted_with` is the folder whose deletion deleted the row, so that restoring
+-- that folder brings back exactly what was deleted with it.
+ALTER TABLE folders
+  ADD COLUMN deleted_at timestamptz,
+  ADD COLUMN deleted_by uuid REFERENCES users,
+  ADD COLUMN deleted_with uuid REFERENCES folders;
+
+ALTER TABLE files
+  ADD COLUMN deleted_with uuid REFERENCES folders;
+
+CREATE INDEX folders_deleted_with_idx ON folders (deleted_with);
+CREATE INDEX files_deleted_with_idx ON files (deleted_with);
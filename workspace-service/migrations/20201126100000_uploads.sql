@@ -0,0 +1,12 @@
+-- What a client said it was going to upload when it asked for an upload URL, so that we can check
+-- the blob when the upload is committed. `id` is the name of the blob in the upload container.
+CREATE TABLE uploads (
+  id uuid PRIMARY KEY,
+  file_name text NOT NULL,
+  file_type text NOT NULL,
+  file_size bigint NOT NULL,
+  created_by uuid NOT NULL REFERENCES users,
+  created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX uploads_created_at_idx ON uploads (created_at);
@@ -0,0 +1,11 @@
+-- Every file version is scanned for malware before it can be downloaded. Versions that were
+-- created before scanning was introduced are treated as clean.
+ALTER TABLE file_versions
+  ADD COLUMN scan_status text NOT NULL DEFAULT 'CLEAN'
+    CHECK (scan_status IN ('PENDING', 'CLEAN', 'INFECTED'));
+
+ALTER TABLE file_versions
+  ALTER COLUMN scan_status SET DEFAULT 'PENDING';
+
+CREATE INDEX file_versions_scan_pending_idx ON file_versions (created_at)
+  WHERE scan_status = 'PENDING';
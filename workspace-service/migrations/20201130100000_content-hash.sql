@@ -0,0 +1,8 @@
+-- The SHA-256 hash (as hex) and size in bytes of the blob of each file version, recorded when
+-- the blob is copied into the files container. They are null for versions created before they
+-- were recorded.
+ALTER TABLE file_versions
+  ADD COLUMN sha256 text,
+  ADD COLUMN file_size bigint;
+
+CREATE INDEX file_versions_sha256_idx ON file_versions (sha256);
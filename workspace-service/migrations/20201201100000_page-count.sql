@@ -0,0 +1,3 @@
+-- An estimate of the number of pages of each PDF file version, or null if it is not a PDF, its
+-- pages could not be counted, or it was created before page counts were recorded.
+ALTER TABLE file_versions ADD COLUMN page_count integer;
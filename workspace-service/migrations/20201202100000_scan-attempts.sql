@@ -0,0 +1,13 @@
+-- A blob that cannot be scanned is tried again after a delay, so that it does not hold up newer
+-- blobs, and after too many attempts its versions are marked as FAILED.
+ALTER TABLE file_versions ADD COLUMN scan_attempts integer NOT NULL DEFAULT 0;
+ALTER TABLE file_versions ADD COLUMN next_scan_at timestamptz NOT NULL DEFAULT NOW();
+UPDATE file_versions SET next_scan_at = created_at;
+
+ALTER TABLE file_versions DROP CONSTRAINT file_versions_scan_status_check;
+ALTER TABLE file_versions ADD CONSTRAINT file_versions_scan_status_check
+  CHECK (scan_status IN ('PENDING', 'CLEAN', 'INFECTED', 'FAILED'));
+
+DROP INDEX file_versions_scan_pending_idx;
+CREATE INDEX file_versions_scan_pending_idx ON file_versions (next_scan_at)
+  WHERE scan_status = 'PENDING';
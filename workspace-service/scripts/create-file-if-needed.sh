@@ -32,7 +32,7 @@ body=$(
 		--null-input \
 		--arg folder "$folder" \
 		'{
-		"query": "query FilesByFolder($folder: ID!) { filesByFolder(folder: $folder) { title, id } }",
+		"query": "query FilesByFolder($folder: ID!) { filesByFolder(folder: $folder, first: 500) { edges { node { title, id } } } }",
 		"variables": {
 			"folder": $folder,
 		}
@@ -54,7 +54,7 @@ found=$(
 		jq \
 			-r \
 			--arg title "$FILE_TITLE" \
-			'.data.filesByFolder | map(select(.title == $title))[0].id'
+			'.data.filesByFolder.edges | map(.node | select(.title == $title))[0].id'
 )
 if [ "$found" != "null" ]; then
 	echo "$found"
@@ -30,7 +30,7 @@ body=$(
 		--null-input \
 		--arg workspace "$workspace" \
 		'{
-		"query": "query FoldersByWorkspace($workspace: ID!) { foldersByWorkspace(workspace: $workspace) { title, id } }",
+		"query": "query FoldersByWorkspace($workspace: ID!) { foldersByWorkspace(workspace: $workspace, first: 500) { edges { node { title, id } } } }",
 		"variables": {
 			"workspace": $workspace,
 		}
@@ -52,7 +52,7 @@ found=$(
 		jq \
 			-r \
 			--arg title "$FOLDER_TITLE" \
-			'.data.foldersByWorkspace | map(select(.title == $title))[0].id'
+			'.data.foldersByWorkspace.edges | map(.node | select(.title == $title))[0].id'
 )
 if [ "$found" != "null" ]; then
 	echo "$found"
@@ -25,14 +25,14 @@ existing_workspaces=$(
 		-XPOST \
 		$WORKSPACE_SERVICE_GRAPHQL_ENDPOINT \
 		-H 'Content-Type: application/json' \
-		-d '{"query": "{workspaces { title, id }}"}'
+		-d '{"query": "{workspaces(first: 500) { edges { node { title, id } } }}"}'
 )
 found=$(
 	echo "$existing_workspaces" |
 		jq \
 			-r \
 			--arg title "$WORKSPACE_TITLE" \
-			'.data.workspaces | map(select(.title == $title))[0].id'
+			'.data.workspaces.edges | map(.node | select(.title == $title))[0].id'
 )
 if [ "$found" != "null" ]; then
 	echo $found
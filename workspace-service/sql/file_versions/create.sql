@@ -10,7 +10,26 @@ INSERT INTO file_versions (
     created_at,
     created_by,
     version_number,
-    version_label
+    version_label,
+    scan_status,
+    sha256,
+    file_size,
+    page_count
 )
-VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), $9, $10, $11)
-RETURNING *
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), $9, $10, $11, $12, $13, $14, $15)
+RETURNING id,
+    folder,
+    file,
+    file_title,
+    file_description,
+    file_name,
+    file_type,
+    blob_storage_path,
+    created_at,
+    created_by,
+    version_number,
+    version_label,
+    scan_status,
+    sha256,
+    file_size,
+    page_count
@@ -0,0 +1,7 @@
+SELECT blob_storage_path
+FROM file_versions
+WHERE scan_status = 'PENDING'
+GROUP BY blob_storage_path
+HAVING MAX(next_scan_at) <= NOW()
+ORDER BY MAX(next_scan_at)
+LIMIT $1
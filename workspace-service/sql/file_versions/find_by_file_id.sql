@@ -1,8 +1,19 @@
-SELECT file_versions.id,
-    file_versions.file_title AS title,
-    file_versions.created_at,
-    users.email_address
-
-FROM file_versions JOIN users ON file_versions.created_by = users.id
-WHERE file_versions.file = $1
-ORDER BY file_versions.version_number desc
+SELECT id,
+    folder,
+    file,
+    file_title,
+    file_description,
+    file_name,
+    file_type,
+    blob_storage_path,
+    created_at,
+    created_by,
+    version_number,
+    version_label,
+    scan_status,
+    sha256,
+    file_size,
+    page_count
+FROM file_versions
+WHERE file = $1
+ORDER BY version_number DESC
@@ -0,0 +1,18 @@
+SELECT id,
+    folder,
+    file,
+    file_title,
+    file_description,
+    file_name,
+    file_type,
+    blob_storage_path,
+    created_at,
+    created_by,
+    version_number,
+    version_label,
+    scan_status,
+    sha256,
+    file_size,
+    page_count
+FROM file_versions
+WHERE id = $1
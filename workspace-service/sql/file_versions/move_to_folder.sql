@@ -0,0 +1,7 @@
+-- Only the latest version is moved, so that the earlier versions still record where they were.
+UPDATE file_versions
+SET folder = $2
+FROM files
+WHERE files.id = $1
+AND file_versions.id = files.latest_version
+RETURNING file_versions.id
@@ -0,0 +1,7 @@
+UPDATE file_versions
+SET scan_attempts = scan_attempts + 1,
+    next_scan_at = NOW() + INTERVAL '1 minute' * POWER(2, scan_attempts),
+    scan_status = CASE WHEN scan_attempts + 1 >= $2 THEN 'FAILED' ELSE 'PENDING' END
+WHERE blob_storage_path = $1
+AND scan_status = 'PENDING'
+RETURNING scan_status
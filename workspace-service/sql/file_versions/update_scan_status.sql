@@ -0,0 +1,10 @@
+UPDATE file_versions
+SET scan_status = $2
+FROM folders
+WHERE file_versions.blob_storage_path = $1
+AND file_versions.scan_status = 'PENDING'
+AND file_versions.folder = folders.id
+RETURNING file_versions.id,
+    file_versions.file,
+    file_versions.version_number,
+    folders.workspace
@@ -0,0 +1,4 @@
+SELECT COUNT(*)
+FROM files JOIN file_versions ON files.latest_version = file_versions.id
+WHERE file_versions.folder = $1
+AND files.deleted_at IS NULL
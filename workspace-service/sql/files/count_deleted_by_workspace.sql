@@ -0,0 +1,7 @@
+SELECT COUNT(*)
+FROM files
+JOIN file_versions ON files.latest_version = file_versions.id
+JOIN folders ON file_versions.folder = folders.id
+WHERE folders.workspace = $1
+AND files.deleted_at IS NOT NULL
+AND files.deleted_with IS NULL
@@ -15,4 +15,8 @@ RETURNING
     file_versions.created_at AS modified_at,
     files.deleted_at,
     files.latest_version AS version,
-    file_versions.version_number
+    file_versions.version_number,
+    file_versions.scan_status,
+    file_versions.sha256,
+    file_versions.file_size,
+    file_versions.page_count
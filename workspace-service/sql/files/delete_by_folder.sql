@@ -0,0 +1,16 @@
+WITH RECURSIVE folder_tree (id) AS (
+    SELECT id
+    FROM folders
+    WHERE id = $1 AND deleted_at IS NULL
+    UNION ALL
+    SELECT folders.id
+    FROM folders JOIN folder_tree ON folders.parent = folder_tree.id
+    WHERE folders.deleted_at IS NULL
+)
+UPDATE files
+SET deleted_at = NOW(), deleted_by = $2, deleted_with = $1
+FROM file_versions
+WHERE files.latest_version = file_versions.id
+AND file_versions.folder IN (SELECT id FROM folder_tree)
+AND files.deleted_at IS NULL
+RETURNING files.id
@@ -9,9 +9,15 @@ SELECT files.id,
     file_versions.created_at AS modified_at,
     files.deleted_at,
     files.latest_version AS version,
-    file_versions.version_number
+    file_versions.version_number,
+    file_versions.scan_status,
+    file_versions.sha256,
+    file_versions.file_size,
+    file_versions.page_count
 
 FROM files JOIN file_versions ON files.latest_version = file_versions.id
 WHERE file_versions.folder = $1
 AND files.deleted_at IS NULL
-ORDER BY file_versions.file_title
+AND ($2::text IS NULL OR (file_versions.file_title, files.id) > ($2, $3))
+ORDER BY file_versions.file_title, files.id
+LIMIT $4
@@ -9,7 +9,11 @@ SELECT files.id,
     file_versions.created_at AS modified_at,
     files.deleted_at,
     files.latest_version AS version,
-    file_versions.version_number
+    file_versions.version_number,
+    file_versions.scan_status,
+    file_versions.sha256,
+    file_versions.file_size,
+    file_versions.page_count
 
 FROM files JOIN file_versions ON files.latest_version = file_versions.id
 WHERE files.id = $1
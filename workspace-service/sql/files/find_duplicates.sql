@@ -0,0 +1,26 @@
+SELECT files.id,
+    file_versions.file_title AS title,
+    file_versions.file_description AS description,
+    file_versions.folder,
+    file_versions.file_name,
+    file_versions.blob_storage_path,
+    file_versions.file_type,
+    files.created_at,
+    file_versions.created_at AS modified_at,
+    files.deleted_at,
+    files.latest_version AS version,
+    file_versions.version_number,
+    file_versions.scan_status,
+    file_versions.sha256,
+    file_versions.file_size,
+    file_versions.page_count
+
+FROM files
+JOIN file_versions ON files.latest_version = file_versions.id
+JOIN folders ON file_versions.folder = folders.id
+WHERE folders.workspace = $1
+AND file_versions.sha256 = $2
+AND files.id <> $3
+AND files.deleted_at IS NULL
+AND ($4 OR folders.role_required <> 'WORKSPACE_MEMBER')
+ORDER BY file_versions.file_title, files.id
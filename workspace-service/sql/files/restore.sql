@@ -0,0 +1,23 @@
+UPDATE files
+SET deleted_at = NULL, deleted_by = NULL, deleted_with = NULL
+FROM file_versions
+WHERE files.id = $1
+AND files.latest_version = file_versions.id
+AND files.deleted_at IS NOT NULL
+RETURNING
+    files.id,
+    file_versions.file_title AS title,
+    file_versions.file_description AS description,
+    file_versions.folder,
+    file_versions.file_name,
+    file_versions.blob_storage_path,
+    file_versions.file_type,
+    files.created_at,
+    file_versions.created_at AS modified_at,
+    files.deleted_at,
+    files.latest_version AS version,
+    file_versions.version_number,
+    file_versions.scan_status,
+    file_versions.sha256,
+    file_versions.file_size,
+    file_versions.page_count
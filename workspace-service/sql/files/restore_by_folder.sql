@@ -0,0 +1,4 @@
+UPDATE files
+SET deleted_at = NULL, deleted_by = NULL, deleted_with = NULL
+WHERE deleted_with = $1
+RETURNING id
@@ -0,0 +1,20 @@
+WITH versions AS (
+    SELECT file_versions.id, file_versions.blob_storage_path, file_versions.file_size
+    FROM file_versions
+    JOIN folders ON file_versions.folder = folders.id
+    WHERE folders.workspace = $1
+),
+latest_versions AS (
+    SELECT versions.file_size
+    FROM files
+    JOIN versions ON files.latest_version = versions.id
+    WHERE files.deleted_at IS NULL
+),
+blobs AS (
+    SELECT DISTINCT ON (blob_storage_path) file_size
+    FROM versions
+    ORDER BY blob_storage_path, file_size DESC NULLS LAST
+)
+SELECT (SELECT COUNT(*) FROM latest_versions) AS file_count,
+    (SELECT COALESCE(SUM(file_size), 0) FROM latest_versions)::bigint AS total_file_size,
+    (SELECT COALESCE(SUM(file_size), 0) FROM blobs)::bigint AS stored_size
@@ -0,0 +1,13 @@
+WITH RECURSIVE ancestors (id, depth) AS (
+    SELECT parent, 1
+    FROM folders
+    WHERE id = $1 AND parent IS NOT NULL
+    UNION ALL
+    SELECT folders.parent, ancestors.depth + 1
+    FROM folders JOIN ancestors ON folders.id = ancestors.id
+    WHERE folders.parent IS NOT NULL
+)
+SELECT folders.id, folders.title, folders.description, folders.role_required, folders.workspace, folders.parent,
+    folders.deleted_at, folders.deleted_with
+FROM folders JOIN ancestors ON folders.id = ancestors.id
+ORDER BY ancestors.depth DESC
@@ -0,0 +1,6 @@
+SELECT COUNT(*)
+FROM folders
+WHERE workspace = $1
+AND deleted_at IS NULL
+AND parent IS NOT DISTINCT FROM $2
+AND (role_required <> 'WORKSPACE_MEMBER' OR $3)
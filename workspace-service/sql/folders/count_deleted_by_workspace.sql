@@ -0,0 +1,4 @@
+SELECT COUNT(*)
+FROM folders
+WHERE workspace = $1
+AND deleted_with = id
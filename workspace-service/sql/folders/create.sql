@@ -1,3 +1,3 @@
-INSERT INTO folders (title, description, role_required, workspace)
-VALUES ($1, $2, $3, $4)
-RETURNING id, title, description, role_required, workspace
+INSERT INTO folders (title, description, role_required, workspace, parent)
+VALUES ($1, $2, $3, $4, $5)
+RETURNING id, title, description, role_required, workspace, parent, deleted_at, deleted_with
@@ -1,3 +1,13 @@
-DELETE FROM folders
-WHERE id = $1
-RETURNING id, title, description, role_required, workspace
+WITH RECURSIVE folder_tree (id) AS (
+    SELECT id
+    FROM folders
+    WHERE id = $1 AND deleted_at IS NULL
+    UNION ALL
+    SELECT folders.id
+    FROM folders JOIN folder_tree ON folders.parent = folder_tree.id
+    WHERE folders.deleted_at IS NULL
+)
+UPDATE folders
+SET deleted_at = NOW(), deleted_by = $2, deleted_with = $1
+WHERE id IN (SELECT id FROM folder_tree)
+RETURNING id, title, description, role_required, workspace, parent, deleted_at, deleted_with
@@ -1,3 +1,4 @@
-SELECT *
+SELECT id, title, description, role_required, workspace, parent, deleted_at, deleted_with
 FROM folders
 WHERE id = $1
+AND deleted_at IS NULL
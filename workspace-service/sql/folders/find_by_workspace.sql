@@ -1,3 +1,9 @@
-SELECT  id, title, description, role_required, workspace
+SELECT  id, title, description, role_required, workspace, parent, deleted_at, deleted_with
 FROM folders
 WHERE workspace = $1
+AND deleted_at IS NULL
+AND parent IS NOT DISTINCT FROM $2
+AND (role_required <> 'WORKSPACE_MEMBER' OR $3)
+AND ($4::text IS NULL OR (title, id) > ($4, $5))
+ORDER BY title, id
+LIMIT $6
@@ -0,0 +1,4 @@
+SELECT id, title, description, role_required, workspace, parent, deleted_at, deleted_with
+FROM folders
+WHERE id = $1
+AND deleted_with = id
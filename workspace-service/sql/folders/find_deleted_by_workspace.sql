@@ -0,0 +1,7 @@
+SELECT id, title, description, role_required, workspace, parent, deleted_at, deleted_with
+FROM folders
+WHERE workspace = $1
+AND deleted_with = id
+AND ($2::text IS NULL OR (deleted_at, id) < ($2::text::timestamptz, $3))
+ORDER BY deleted_at DESC, id DESC
+LIMIT $4
@@ -0,0 +1,6 @@
+UPDATE folders
+SET parent = $2,
+    role_required = $3
+WHERE id = $1
+AND deleted_at IS NULL
+RETURNING id, title, description, role_required, workspace, parent, deleted_at, deleted_with
@@ -0,0 +1,4 @@
+UPDATE folders
+SET deleted_at = NULL, deleted_by = NULL, deleted_with = NULL
+WHERE deleted_with = $1
+RETURNING id, title, description, role_required, workspace, parent, deleted_at, deleted_with
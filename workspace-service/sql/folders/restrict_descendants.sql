@@ -0,0 +1,11 @@
+WITH RECURSIVE descendants (id) AS (
+    SELECT id
+    FROM folders
+    WHERE parent = $1
+    UNION ALL
+    SELECT folders.id
+    FROM folders JOIN descendants ON folders.parent = descendants.id
+)
+UPDATE folders
+SET role_required = 'WORKSPACE_MEMBER'
+WHERE id IN (SELECT id FROM descendants)
@@ -3,4 +3,5 @@ SET title = COALESCE($2, title),
     description = COALESCE($3, description),
     role_required = COALESCE($4, role_required)
 WHERE id = $1
-RETURNING *
+AND deleted_at IS NULL
+RETURNING id, title, description, role_required, workspace, parent, deleted_at, deleted_with
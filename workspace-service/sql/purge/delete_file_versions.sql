@@ -0,0 +1,15 @@
+WITH purged AS (
+    DELETE FROM file_versions
+    WHERE file IN (SELECT id FROM files WHERE deleted_at < $1)
+    RETURNING blob_storage_path
+)
+-- Blobs can be shared by several versions of a file, so only return those that no other version
+-- uses. The rest of the statement still sees the versions deleted above.
+SELECT DISTINCT purged.blob_storage_path
+FROM purged
+WHERE NOT EXISTS (
+    SELECT 1
+    FROM file_versions
+    WHERE file_versions.blob_storage_path = purged.blob_storage_path
+    AND file_versions.file NOT IN (SELECT id FROM files WHERE deleted_at < $1)
+)
@@ -0,0 +1,3 @@
+DELETE FROM files
+WHERE deleted_at < $1
+RETURNING id
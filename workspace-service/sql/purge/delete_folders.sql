@@ -0,0 +1,12 @@
+DELETE FROM folders
+WHERE deleted_at < $1
+AND NOT EXISTS (
+    SELECT 1 FROM file_versions WHERE file_versions.folder = folders.id
+)
+AND NOT EXISTS (
+    SELECT 1
+    FROM folders AS children
+    WHERE children.parent = folders.id
+    AND (children.deleted_at IS NULL OR children.deleted_at >= $1)
+)
+RETURNING id
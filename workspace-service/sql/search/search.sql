@@ -0,0 +1,69 @@
+WITH search AS (
+    SELECT plainto_tsquery('english', $1) AS query,
+        -- control characters, which cannot be confused with the text, mark the matched words
+        'StartSel=' || chr(2) || ', StopSel=' || chr(3) AS headline_options
+),
+visible_folders AS (
+    SELECT folders.*
+    FROM folders JOIN workspaces ON folders.workspace = workspaces.id
+    WHERE folders.deleted_at IS NULL
+    AND ($2::uuid IS NULL OR folders.workspace = $2)
+    AND (
+        folders.role_required <> 'WORKSPACE_MEMBER'
+        OR $7
+        OR EXISTS (
+            SELECT 1 FROM link_users_teams
+            WHERE team_id = workspaces.members AND user_id = $6
+        )
+    )
+)
+SELECT 'WORKSPACE' AS kind,
+    workspaces.id,
+    ts_rank(workspaces.search_vector, search.query) AS rank,
+    ts_headline(
+        'english',
+        workspaces.title || ' ' || workspaces.description,
+        search.query,
+        search.headline_options
+    ) AS snippet
+FROM workspaces, search
+WHERE $3
+AND ($2::uuid IS NULL OR workspaces.id = $2)
+AND workspaces.search_vector @@ search.query
+
+UNION ALL
+
+SELECT 'FOLDER' AS kind,
+    visible_folders.id,
+    ts_rank(visible_folders.search_vector, search.query) AS rank,
+    ts_headline(
+        'english',
+        visible_folders.title || ' ' || visible_folders.description,
+        search.query,
+        search.headline_options
+    ) AS snippet
+FROM visible_folders, search
+WHERE $4
+AND visible_folders.search_vector @@ search.query
+
+UNION ALL
+
+SELECT 'FILE' AS kind,
+    files.id,
+    ts_rank(file_versions.search_vector, search.query) AS rank,
+    ts_headline(
+        'english',
+        file_versions.file_title || ' ' || file_versions.file_description || ' ' || file_versions.file_name,
+        search.query,
+        search.headline_options
+    ) AS snippet
+FROM files
+JOIN file_versions ON files.latest_version = file_versions.id
+JOIN visible_folders ON file_versions.folder = visible_folders.id,
+search
+WHERE $5
+AND files.deleted_at IS NULL
+AND file_versions.search_vector @@ search.query
+
+ORDER BY rank DESC, id
+LIMIT $8
@@ -0,0 +1,6 @@
+SELECT
+	COUNT(*)
+FROM
+	link_users_teams
+WHERE
+	team_id = $1
@@ -0,0 +1,9 @@
+WITH user_ids as (
+    SELECT user_id FROM link_users_teams WHERE team_id = $1
+    EXCEPT
+    SELECT user_id FROM link_users_teams WHERE team_id = $2
+)
+SELECT
+	COUNT(*)
+FROM
+	user_ids
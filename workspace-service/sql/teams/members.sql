@@ -5,5 +5,7 @@ FROM
 	JOIN link_users_teams ON users.id = link_users_teams.user_id
 WHERE
 	link_users_teams.team_id = $1
+	AND ($2::text IS NULL OR (users.name, users.id) > ($2, $3))
 ORDER BY
-	users.name
+	users.name, users.id
+LIMIT $4
@@ -8,5 +8,8 @@ SELECT
 FROM
 	users
 	JOIN user_ids ON users.id = user_ids.user_id
+WHERE
+	$3::text IS NULL OR (users.name, users.id) > ($3, $4)
 ORDER BY
-	users.name
+	users.name, users.id
+LIMIT $5
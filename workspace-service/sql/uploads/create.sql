@@ -0,0 +1,3 @@
+INSERT INTO uploads (id, file_name, file_type, file_size, created_by)
+VALUES ($1, $2, $3, $4, $5)
+RETURNING *;
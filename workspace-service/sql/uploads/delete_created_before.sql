@@ -0,0 +1 @@
+DELETE FROM uploads WHERE created_at < $1;
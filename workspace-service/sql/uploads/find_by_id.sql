@@ -0,0 +1 @@
+SELECT * FROM uploads WHERE id = $1;
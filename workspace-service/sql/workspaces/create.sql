@@ -1,3 +1,3 @@
 INSERT INTO workspaces (title, description, admins, members)
 VALUES ($1, $2, $3, $4)
-RETURNING *
+RETURNING id, title, description, admins, members
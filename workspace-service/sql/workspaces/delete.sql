@@ -1,3 +1,3 @@
 DELETE FROM workspaces
 WHERE id = $1
-RETURNING *
+RETURNING id, title, description, admins, members
@@ -1,3 +1,5 @@
-SELECT *
+SELECT id, title, description, admins, members
 FROM workspaces
+WHERE ($1::uuid IS NULL OR id > $1)
 ORDER BY id
+LIMIT $2
@@ -1,3 +1,3 @@
-SELECT *
+SELECT id, title, description, admins, members
 FROM workspaces
 WHERE id = $1
@@ -2,4 +2,4 @@ UPDATE workspaces
 SET title = COALESCE($2, title),
     description = COALESCE($3, description)
 WHERE id = $1
-RETURNING *
+RETURNING id, title, description, admins, members
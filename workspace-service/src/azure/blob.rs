@@ -1,12 +1,16 @@
+use crate::blob_store::{BlobProperties, BlobReader};
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use std::convert::{TryFrom, TryInto};
+#[cfg(test)]
+use std::{collections::HashMap, sync::Mutex};
 use url::Url;
 #[cfg(not(test))]
 use {
     anyhow::bail,
     async_compat::Compat,
-    azure_sdk_core::{errors::AzureError, prelude::*},
-    azure_sdk_storage_blob::{blob::CopyStatus, Blob},
+    azure_sdk_core::{errors::AzureError, prelude::*, DeleteSnapshotsMethod},
+    azure_sdk_storage_blob::{blob::CopyStatus, prelude::*, Blob},
 };
 
 #[derive(PartialEq, Debug)]
@@ -108,11 +112,246 @@ pub async fn copy_blob_from_url(url: &Url, azure_config: &super::Config) -> Resu
     }
 }
 
+/// Returns the name of a blob in the upload container.
+#[cfg(not(test))]
+fn upload_blob_name(url: &Url, azure_config: &super::Config) -> Result<String> {
+    let input: BlobUrlParts = url.try_into()?;
+
+    if input.account != azure_config.account {
+        bail!("upload is in an unsupported storage account");
+    }
+
+    if input.container != azure_config.upload_container {
+        bail!("upload is in an unsupported container");
+    }
+
+    input
+        .blob
+        .ok_or_else(|| anyhow!("cannot get blob name from url"))
+}
+
+/// Returns the size and content type of a blob in the upload container.
+#[cfg(not(test))]
+pub async fn get_upload_properties(
+    url: &Url,
+    azure_config: &super::Config,
+) -> Result<BlobProperties> {
+    let blob_name = upload_blob_name(url, azure_config)?;
+
+    // This version of the SDK cannot get the properties of a single blob, but listing the blobs
+    // with its name as the prefix includes them.
+    let response = Compat::new(
+        azure_config
+            .client()
+            .list_blobs()
+            .with_container_name(&azure_config.upload_container)
+            .with_prefix(&blob_name)
+            .finalize(),
+    )
+    .await?;
+    let blob = response
+        .incomplete_vector
+        .iter()
+        .find(|blob| blob.name == blob_name)
+        .ok_or_else(|| anyhow!("upload {} not found", blob_name))?;
+
+    Ok(BlobProperties {
+        size: blob.content_length,
+        content_type: blob.content_type.clone(),
+    })
+}
+
+/// Opens a blob in the files container for reading.
+#[cfg(not(test))]
+pub async fn open_file(url: &Url, azure_config: &super::Config) -> Result<BlobReader> {
+    let input: BlobUrlParts = url.try_into()?;
+
+    if input.account != azure_config.account {
+        bail!("file is in an unsupported storage account");
+    }
+
+    if input.container != azure_config.files_container {
+        bail!("file is in an unsupported container");
+    }
+
+    // This version of the SDK reads the whole blob into memory, so we stream it from a download
+    // URL instead.
+    let mut url = url.clone();
+    url.set_query(None);
+    let response = surf::get(super::create_download_sas(azure_config, &url)?)
+        .await
+        .map_err(|err| err.into_inner())?;
+    if !response.status().is_success() {
+        bail!("reading {} failed with {}", url, response.status());
+    }
+
+    Ok(Box::new(response))
+}
+
+/// Deletes a blob, and its snapshots, from the upload or files container.
+#[cfg(not(test))]
+pub async fn delete_blob(url: &Url, azure_config: &super::Config) -> Result<()> {
+    let input: BlobUrlParts = url.try_into()?;
+
+    if input.account != azure_config.account {
+        bail!("blob is in an unsupported storage account");
+    }
+
+    if input.container != azure_config.upload_container
+        && input.container != azure_config.files_container
+    {
+        bail!("blob is in an unsupported container");
+    }
+
+    let blob_name = input
+        .blob
+        .ok_or_else(|| anyhow!("cannot get blob name from url"))?;
+
+    Compat::new(
+        azure_config
+            .client()
+            .delete_blob()
+            .with_container_name(&input.container)
+            .with_blob_name(&blob_name)
+            .with_delete_snapshots_method(DeleteSnapshotsMethod::Include)
+            .finalize(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Lists the blobs in the upload container that were last modified before `before`.
+#[cfg(not(test))]
+pub async fn find_uploads_modified_before(
+    before: DateTime<Utc>,
+    azure_config: &super::Config,
+) -> Result<Vec<Url>> {
+    let client = azure_config.client();
+    let mut uploads = vec![];
+    let mut next_marker = None;
+    loop {
+        let request = client
+            .list_blobs()
+            .with_container_name(&azure_config.upload_container);
+        let response = match &next_marker {
+            Some(next_marker) => {
+                Compat::new(request.with_next_marker(next_marker).finalize()).await?
+            }
+            None => Compat::new(request.finalize()).await?,
+        };
+
+        for blob in response.incomplete_vector.iter() {
+            if blob
+                .last_modified
+                .map_or(false, |last_modified| last_modified < before)
+            {
+                uploads.push(Url::parse(&format!(
+                    "{}/{}",
+                    azure_config.upload_container_url, blob.name
+                ))?);
+            }
+        }
+
+        next_marker = response.incomplete_vector.token().map(str::to_owned);
+        if next_marker.is_none() {
+            return Ok(uploads);
+        }
+    }
+}
+
 // Fake implementation for tests. If you want integration tests that exercise the database,
 // see https://doc.rust-lang.org/rust-by-example/testing/integration_testing.html.
+/// Copies the fake upload to a fake file with the same name.
+#[cfg(test)]
+pub async fn copy_blob_from_url(url: &Url, azure_config: &super::Config) -> Result<String> {
+    let destination = format!(
+        "{}/{}",
+        azure_config.files_container_url,
+        crate::blob_store::upload_name(url)?
+    );
+    put_fake_file(&destination.parse()?, &fake_upload(url).1);
+    Ok(destination)
+}
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref FAKE_UPLOADS: Mutex<HashMap<Url, (String, Vec<u8>)>> = Mutex::new(HashMap::new());
+}
+
+/// Makes the fake upload at the URL have the content type and contents. Any other fake upload
+/// is a 42 byte text file.
+#[cfg(test)]
+pub fn put_fake_upload(url: &Url, content_type: &str, contents: &[u8]) {
+    FAKE_UPLOADS
+        .lock()
+        .unwrap()
+        .insert(url.clone(), (content_type.into(), contents.to_vec()));
+}
+
+#[cfg(test)]
+fn fake_upload(url: &Url) -> (String, Vec<u8>) {
+    FAKE_UPLOADS
+        .lock()
+        .unwrap()
+        .get(url)
+        .cloned()
+        .unwrap_or_else(|| {
+            (
+                "text/plain".into(),
+                b"This fake upload is a 42 byte text file.\r\n".to_vec(),
+            )
+        })
+}
+
+#[cfg(test)]
+pub async fn get_upload_properties(
+    url: &Url,
+    _azure_config: &super::Config,
+) -> Result<BlobProperties> {
+    let (content_type, contents) = fake_upload(url);
+    Ok(BlobProperties {
+        size: contents.len() as u64,
+        content_type: Some(content_type),
+    })
+}
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref FAKE_FILES: Mutex<HashMap<Url, Vec<u8>>> = Mutex::new(HashMap::new());
+}
+
+/// Makes the fake file at the URL have the contents. Any other fake file is a 42 byte text file.
+#[cfg(test)]
+pub fn put_fake_file(url: &Url, contents: &[u8]) {
+    FAKE_FILES
+        .lock()
+        .unwrap()
+        .insert(url.clone(), contents.to_vec());
+}
+
+#[cfg(test)]
+pub async fn open_file(url: &Url, _azure_config: &super::Config) -> Result<BlobReader> {
+    let contents = FAKE_FILES
+        .lock()
+        .unwrap()
+        .get(url)
+        .cloned()
+        .unwrap_or_else(|| b"This fake upload is a 42 byte text file.\r\n".to_vec());
+    Ok(Box::new(async_std::io::Cursor::new(contents)))
+}
+
+#[cfg(test)]
+pub async fn delete_blob(_url: &Url, _azure_config: &super::Config) -> Result<()> {
+    Ok(())
+}
+
 #[cfg(test)]
-pub async fn copy_blob_from_url(_url: &Url, _azure_config: &super::Config) -> Result<String> {
-    Ok("http://localhost:10000/devstoreaccount1/files/fake".into())
+pub async fn find_uploads_modified_before(
+    _before: DateTime<Utc>,
+    _azure_config: &super::Config,
+) -> Result<Vec<Url>> {
+    Ok(vec![])
 }
 
 #[cfg(test)]
@@ -1,13 +1,20 @@
 mod blob;
 mod sas;
 
+use crate::blob_store::{BlobProperties, BlobReader, BlobStore};
 use anyhow::{bail, Result};
 use azure_sdk_storage_core::{client, key_client::KeyClient, shared_access_signature::SasProtocol};
-pub use blob::copy_blob_from_url;
 use blob::BlobUrlParts;
+pub use blob::{
+    copy_blob_from_url, delete_blob, find_uploads_modified_before, get_upload_properties, open_file,
+};
+#[cfg(test)]
+pub use blob::{put_fake_file, put_fake_upload};
+use chrono::{DateTime, Utc};
 pub use sas::{create_download_sas, create_upload_sas};
 use std::convert::TryInto;
 use url::Url;
+use uuid::Uuid;
 
 /// Azurite (the local Azure Storage emulator) comes with a [default storage account], which has a
 /// hardcoded key. This key is also used by the Azure SDK for Rust when we create a client using
@@ -72,3 +79,34 @@ impl Config {
         }
     }
 }
+
+#[async_trait::async_trait]
+impl BlobStore for Config {
+    fn create_upload_url(&self, name: &Uuid) -> Result<Url> {
+        create_upload_sas(self, name)
+    }
+
+    fn create_download_url(&self, url: &Url) -> Result<Url> {
+        create_download_sas(self, url)
+    }
+
+    async fn upload_properties(&self, url: &Url) -> Result<BlobProperties> {
+        get_upload_properties(url, self).await
+    }
+
+    async fn open_file(&self, url: &Url) -> Result<BlobReader> {
+        open_file(url, self).await
+    }
+
+    async fn copy_upload(&self, url: &Url) -> Result<String> {
+        copy_blob_from_url(url, self).await
+    }
+
+    async fn delete_blob(&self, url: &Url) -> Result<()> {
+        delete_blob(url, self).await
+    }
+
+    async fn find_uploads_modified_before(&self, before: DateTime<Utc>) -> Result<Vec<Url>> {
+        find_uploads_modified_before(before, self).await
+    }
+}
@@ -1,4 +1,5 @@
 use super::Config;
+use crate::blob_store::{DOWNLOAD_URL_VALID_MINUTES, UPLOAD_URL_VALID_MINUTES};
 use anyhow::{anyhow, Result};
 use azure_sdk_storage_core::prelude::*;
 use chrono::*;
@@ -15,7 +16,7 @@ pub fn create_download_sas(config: &Config, url: &Url) -> Result<Url> {
 
 fn create_upload_sas_impl(config: &Config, name: &Uuid, now: DateTime<Utc>) -> Result<Url> {
     let start = now - Duration::minutes(15);
-    let end = now + Duration::minutes(15);
+    let end = now + Duration::minutes(UPLOAD_URL_VALID_MINUTES);
 
     let token = config
         .client()
@@ -40,7 +41,7 @@ fn create_upload_sas_impl(config: &Config, name: &Uuid, now: DateTime<Utc>) -> R
 
 fn create_download_sas_impl(config: &Config, url: &Url, now: DateTime<Utc>) -> Result<Url> {
     let start = now - Duration::minutes(15);
-    let end = now + Duration::minutes(15);
+    let end = now + Duration::minutes(DOWNLOAD_URL_VALID_MINUTES);
 
     let token = config
         .client()
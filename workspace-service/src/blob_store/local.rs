@@ -0,0 +1,528 @@
+use super::{
+    BlobProperties, BlobReader, BlobStore, DOWNLOAD_URL_VALID_MINUTES, UPLOAD_URL_VALID_MINUTES,
+};
+use anyhow::{anyhow, bail, Result};
+use async_std::{fs, io::ErrorKind, prelude::*};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac, NewMac};
+use lazy_static::lazy_static;
+use regex::Regex;
+use sha2::Sha256;
+use std::{path::PathBuf, sync::Arc};
+use tide::{
+    http::headers::HeaderValue, security::CorsMiddleware, Body, Request, Response, Server,
+    StatusCode,
+};
+use url::Url;
+use uuid::Uuid;
+
+const UPLOAD: &str = "upload";
+const FILES: &str = "files";
+
+lazy_static! {
+    static ref BLOCK_ID: Regex =
+        Regex::new(r"<(?:Committed|Uncommitted|Latest)>([^<]*)</").unwrap();
+}
+
+/// Stores blobs in a directory and serves signed upload and download URLs from the service
+/// itself, so that it can run without Azure Blob Storage, e.g. on a laptop or in CI.
+///
+/// Uploads accept the subset of the Azure Blob Storage REST API that the frontend uses (Put
+/// Blob, Put Block and Put Block List), so clients do not need to know which store is in use.
+pub struct LocalBlobStore {
+    root: PathBuf,
+    base_url: Url,
+    key: Vec<u8>,
+}
+
+impl LocalBlobStore {
+    /// Stores blobs in `root`. `service_url` is the URL clients use to reach the service. If no
+    /// key is given, signed URLs only work until the service restarts.
+    pub fn new(root: PathBuf, service_url: &Url, key: Option<&str>) -> Result<Self> {
+        let mut base_url = service_url.clone();
+        base_url
+            .path_segments_mut()
+            .map_err(|_| anyhow!("LOCAL_BLOB_STORE_URL cannot be a base"))?
+            .pop_if_empty()
+            .push("blobs");
+        let key = match key {
+            Some(key) => key.as_bytes().to_vec(),
+            None => format!("{}{}", Uuid::new_v4(), Uuid::new_v4()).into_bytes(),
+        };
+
+        Ok(Self {
+            root,
+            base_url,
+            key,
+        })
+    }
+
+    fn url(&self, container: &str, name: &Uuid) -> Url {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("checked in new")
+            .push(container)
+            .push(&name.to_string());
+        url
+    }
+
+    /// Extracts the container and name of a blob from its URL, ignoring any query.
+    fn parse(&self, url: &Url) -> Result<(&'static str, Uuid)> {
+        let path = if url.origin() == self.base_url.origin() {
+            url.path().strip_prefix(self.base_url.path())
+        } else {
+            None
+        };
+        let mut segments = path
+            .and_then(|path| path.strip_prefix('/'))
+            .ok_or_else(|| anyhow!("blob is not in the local blob store"))?
+            .splitn(2, '/');
+        let container = container(segments.next().unwrap_or_default())?;
+        let name = Uuid::parse_str(segments.next().unwrap_or_default())?;
+        Ok((container, name))
+    }
+
+    fn blob_path(&self, container: &str, name: &Uuid) -> PathBuf {
+        self.root.join(container).join(name.to_string())
+    }
+
+    /// The content type of a blob is kept next to it, if the client uploaded it with one.
+    fn content_type_path(&self, container: &str, name: &Uuid) -> PathBuf {
+        self.root
+            .join(container)
+            .join(format!("{}.content-type", name))
+    }
+
+    fn blocks_path(&self, name: &Uuid) -> PathBuf {
+        self.root.join("blocks").join(name.to_string())
+    }
+
+    fn block_path(&self, name: &Uuid, block_id: &str) -> PathBuf {
+        self.blocks_path(name)
+            .join(base64::encode_config(block_id, base64::URL_SAFE_NO_PAD))
+    }
+
+    fn mac(&self, method: &str, container: &str, name: &Uuid, expires: i64) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_varkey(&self.key).expect("HMAC takes keys of any size");
+        mac.update(format!("{}\n{}/{}\n{}", method, container, name, expires).as_bytes());
+        mac
+    }
+
+    fn signed_url(
+        &self,
+        method: &str,
+        container: &str,
+        name: &Uuid,
+        expires: DateTime<Utc>,
+    ) -> Url {
+        let expires = expires.timestamp();
+        let signature = self
+            .mac(method, container, name, expires)
+            .finalize()
+            .into_bytes();
+
+        let mut url = self.url(container, name);
+        url.query_pairs_mut()
+            .append_pair("expires", &expires.to_string())
+            .append_pair(
+                "sig",
+                &base64::encode_config(signature, base64::URL_SAFE_NO_PAD),
+            );
+        url
+    }
+
+    /// Checks that the URL was signed by `signed_url` for the method and has not expired.
+    fn verify(
+        &self,
+        method: &str,
+        container: &str,
+        name: &Uuid,
+        url: &Url,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut expires = None;
+        let mut signature = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "expires" => expires = Some(value.parse::<i64>()?),
+                "sig" => {
+                    signature = Some(base64::decode_config(
+                        value.as_ref(),
+                        base64::URL_SAFE_NO_PAD,
+                    )?)
+                }
+                _ => {}
+            }
+        }
+        let expires = expires.ok_or_else(|| anyhow!("URL has no expiry"))?;
+        let signature = signature.ok_or_else(|| anyhow!("URL has no signature"))?;
+
+        if expires < now.timestamp() {
+            bail!("URL has expired");
+        }
+        self.mac(method, container, name, expires)
+            .verify(&signature)
+            .map_err(|_| anyhow!("URL signature is not valid"))
+    }
+
+    fn authorize(&self, req: &Request<()>, method: &str) -> tide::Result<(&'static str, Uuid)> {
+        let container = container(req.param("container")?)
+            .map_err(|err| tide::Error::new(StatusCode::NotFound, err))?;
+        let name = Uuid::parse_str(req.param("name")?)
+            .map_err(|err| tide::Error::new(StatusCode::NotFound, err))?;
+        self.verify(method, container, &name, req.url(), Utc::now())
+            .map_err(|err| tide::Error::new(StatusCode::Forbidden, err))?;
+        Ok((container, name))
+    }
+
+    async fn write_blob(
+        &self,
+        container: &str,
+        name: &Uuid,
+        contents: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<()> {
+        fs::create_dir_all(self.root.join(container)).await?;
+        fs::write(self.blob_path(container, name), contents).await?;
+        match content_type {
+            Some(content_type) => {
+                fs::write(self.content_type_path(container, name), content_type).await?
+            }
+            None => remove_if_exists(self.content_type_path(container, name)).await?,
+        }
+        Ok(())
+    }
+
+    async fn read_content_type(&self, container: &str, name: &Uuid) -> Result<Option<String>> {
+        match fs::read_to_string(self.content_type_path(container, name)).await {
+            Ok(content_type) => Ok(Some(content_type)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn write_block(&self, name: &Uuid, block_id: &str, contents: &[u8]) -> Result<()> {
+        fs::create_dir_all(self.blocks_path(name)).await?;
+        fs::write(self.block_path(name, block_id), contents).await?;
+        Ok(())
+    }
+
+    /// Concatenates the blocks in the order of the block list, like Put Block List.
+    async fn commit_blocks(
+        &self,
+        name: &Uuid,
+        block_list: &[u8],
+        content_type: Option<&str>,
+    ) -> Result<()> {
+        let block_list = std::str::from_utf8(block_list)?;
+        let mut contents = vec![];
+        for block_id in BLOCK_ID.captures_iter(block_list) {
+            contents.extend(fs::read(self.block_path(name, &block_id[1])).await?);
+        }
+        self.write_blob(UPLOAD, name, &contents, content_type)
+            .await?;
+        fs::remove_dir_all(self.blocks_path(name)).await?;
+        Ok(())
+    }
+}
+
+async fn remove_if_exists(path: PathBuf) -> Result<()> {
+    match fs::remove_file(path).await {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+        _ => Ok(()),
+    }
+}
+
+fn container(name: &str) -> Result<&'static str> {
+    match name {
+        UPLOAD => Ok(UPLOAD),
+        FILES => Ok(FILES),
+        _ => bail!("container {} does not exist", name),
+    }
+}
+
+async fn handle_put(store: Arc<LocalBlobStore>, mut req: Request<()>) -> tide::Result {
+    let (container, name) = store.authorize(&req, "PUT")?;
+    if container != UPLOAD {
+        return Err(tide::Error::from_str(
+            StatusCode::Forbidden,
+            "blobs can only be uploaded to the upload container",
+        ));
+    }
+    let query = |name| {
+        req.url()
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    let operation = query("comp");
+    let block_id = query("blockid");
+    // The Azure SDK sends the content type of the blob in `x-ms-blob-content-type`. Other clients
+    // can send it as the content type of a Put Blob request, but the content type of a Put Block
+    // List request is the type of the block list.
+    let content_type = req
+        .header("x-ms-blob-content-type")
+        .or_else(|| req.header("content-type").filter(|_| operation.is_none()))
+        .map(|value| value.as_str().to_owned());
+    let body = req.body_bytes().await?;
+
+    match (operation.as_deref(), block_id) {
+        (None, _) => {
+            store
+                .write_blob(UPLOAD, &name, &body, content_type.as_deref())
+                .await?
+        }
+        (Some("block"), Some(block_id)) => store.write_block(&name, &block_id, &body).await?,
+        (Some("blocklist"), _) => {
+            store
+                .commit_blocks(&name, &body, content_type.as_deref())
+                .await?
+        }
+        _ => {
+            return Err(tide::Error::from_str(
+                StatusCode::BadRequest,
+                "unsupported operation",
+            ))
+        }
+    }
+
+    Ok(Response::new(StatusCode::Created))
+}
+
+async fn handle_get(store: Arc<LocalBlobStore>, req: Request<()>) -> tide::Result {
+    let (container, name) = store.authorize(&req, "GET")?;
+
+    match Body::from_file(store.blob_path(container, &name)).await {
+        Ok(body) => {
+            let mut response = Response::builder(StatusCode::Ok).body(body);
+            if let Some(content_type) = store.read_content_type(container, &name).await? {
+                response = response.header("content-type", content_type);
+            }
+            Ok(response.build())
+        }
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(Response::new(StatusCode::NotFound)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[async_trait::async_trait]
+impl BlobStore for LocalBlobStore {
+    fn create_upload_url(&self, name: &Uuid) -> Result<Url> {
+        Ok(self.signed_url(
+            "PUT",
+            UPLOAD,
+            name,
+            Utc::now() + Duration::minutes(UPLOAD_URL_VALID_MINUTES),
+        ))
+    }
+
+    fn create_download_url(&self, url: &Url) -> Result<Url> {
+        let (container, name) = self.parse(url)?;
+        Ok(self.signed_url(
+            "GET",
+            container,
+            &name,
+            Utc::now() + Duration::minutes(DOWNLOAD_URL_VALID_MINUTES),
+        ))
+    }
+
+    async fn upload_properties(&self, url: &Url) -> Result<BlobProperties> {
+        let (container, name) = self.parse(url)?;
+        if container != UPLOAD {
+            bail!("upload is in an unsupported container");
+        }
+
+        Ok(BlobProperties {
+            size: fs::metadata(self.blob_path(UPLOAD, &name)).await?.len(),
+            content_type: self.read_content_type(UPLOAD, &name).await?,
+        })
+    }
+
+    async fn open_file(&self, url: &Url) -> Result<BlobReader> {
+        let (container, name) = self.parse(url)?;
+        if container != FILES {
+            bail!("file is in an unsupported container");
+        }
+
+        Ok(Box::new(
+            fs::File::open(self.blob_path(FILES, &name)).await?,
+        ))
+    }
+
+    async fn copy_upload(&self, url: &Url) -> Result<String> {
+        let (container, name) = self.parse(url)?;
+        if container != UPLOAD {
+            bail!("source file is from an unsupported container");
+        }
+
+        fs::create_dir_all(self.root.join(FILES)).await?;
+        fs::copy(self.blob_path(UPLOAD, &name), self.blob_path(FILES, &name)).await?;
+        match self.read_content_type(UPLOAD, &name).await? {
+            Some(content_type) => {
+                fs::write(self.content_type_path(FILES, &name), content_type).await?
+            }
+            None => remove_if_exists(self.content_type_path(FILES, &name)).await?,
+        }
+
+        Ok(self.url(FILES, &name).to_string())
+    }
+
+    async fn delete_blob(&self, url: &Url) -> Result<()> {
+        let (container, name) = self.parse(url)?;
+        fs::remove_file(self.blob_path(container, &name)).await?;
+        remove_if_exists(self.content_type_path(container, &name)).await
+    }
+
+    async fn find_uploads_modified_before(&self, before: DateTime<Utc>) -> Result<Vec<Url>> {
+        let mut entries = match fs::read_dir(self.root.join(UPLOAD)).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut uploads = vec![];
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            let name = match entry.file_name().to_str().map(Uuid::parse_str) {
+                Some(Ok(name)) => name,
+                _ => continue,
+            };
+            let modified: DateTime<Utc> = entry.metadata().await?.modified()?.into();
+            if modified < before {
+                uploads.push(self.url(UPLOAD, &name));
+            }
+        }
+        Ok(uploads)
+    }
+
+    fn endpoint(self: Arc<Self>) -> Option<Server<()>> {
+        let mut server = tide::new();
+        server.with(
+            CorsMiddleware::new()
+                .allow_methods("GET, PUT, OPTIONS".parse::<HeaderValue>().unwrap()),
+        );
+        let store = self.clone();
+        server
+            .at("/:container/:name")
+            .put(move |req| handle_put(store.clone(), req))
+            .get(move |req| handle_get(self.clone(), req));
+        Some(server)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn store() -> LocalBlobStore {
+        LocalBlobStore::new(
+            std::env::temp_dir().join(format!("local-blob-store-{}", Uuid::new_v4())),
+            &Url::parse("http://localhost:3030").unwrap(),
+            Some("test key"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn signed_url_is_valid_until_it_expires() {
+        let store = store();
+        let name = Uuid::new_v4();
+        let now = Utc::now();
+
+        let url = store.signed_url("PUT", UPLOAD, &name, now + Duration::minutes(15));
+
+        assert_eq!(url.path(), format!("/blobs/upload/{}", name));
+        assert!(store.verify("PUT", UPLOAD, &name, &url, now).is_ok());
+        assert_eq!(
+            store
+                .verify("PUT", UPLOAD, &name, &url, now + Duration::minutes(16))
+                .unwrap_err()
+                .to_string(),
+            "URL has expired"
+        );
+    }
+
+    #[test]
+    fn signed_url_is_only_valid_for_its_method_and_blob() {
+        let store = store();
+        let name = Uuid::new_v4();
+        let now = Utc::now();
+
+        let url = store.signed_url("GET", FILES, &name, now + Duration::minutes(15));
+
+        assert!(store.verify("PUT", FILES, &name, &url, now).is_err());
+        assert!(store.verify("GET", UPLOAD, &name, &url, now).is_err());
+        assert!(store
+            .verify("GET", FILES, &Uuid::new_v4(), &url, now)
+            .is_err());
+    }
+
+    #[async_std::test]
+    async fn upload_is_copied_to_files() -> Result<()> {
+        let store = store();
+        let name = Uuid::new_v4();
+        store
+            .write_blob(UPLOAD, &name, b"hello", Some("text/plain"))
+            .await?;
+        let upload_url = store.create_upload_url(&name)?;
+
+        assert_eq!(
+            store.upload_properties(&upload_url).await?,
+            BlobProperties {
+                size: 5,
+                content_type: Some("text/plain".into())
+            }
+        );
+
+        let destination = store.copy_upload(&upload_url).await?;
+
+        assert_eq!(
+            destination,
+            format!("http://localhost:3030/blobs/files/{}", name)
+        );
+        assert_eq!(fs::read(store.blob_path(FILES, &name)).await?, b"hello");
+        assert_eq!(
+            store.read_content_type(FILES, &name).await?.as_deref(),
+            Some("text/plain")
+        );
+        assert_eq!(
+            store
+                .find_uploads_modified_before(Utc::now() + Duration::minutes(1))
+                .await?,
+            vec![store.url(UPLOAD, &name)]
+        );
+
+        store.delete_blob(&upload_url).await?;
+
+        assert!(store
+            .find_uploads_modified_before(Utc::now() + Duration::minutes(1))
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn blocks_are_committed_in_block_list_order() -> Result<()> {
+        let store = store();
+        let name = Uuid::new_v4();
+        store.write_block(&name, "Yg==", b"world").await?;
+        store.write_block(&name, "YQ==", b"hello ").await?;
+
+        store
+            .commit_blocks(
+                &name,
+                br#"<?xml version="1.0" encoding="utf-8"?><BlockList><Latest>YQ==</Latest><Latest>Yg==</Latest></BlockList>"#,
+                None,
+            )
+            .await?;
+
+        assert_eq!(
+            fs::read(store.blob_path(UPLOAD, &name)).await?,
+            b"hello world"
+        );
+        assert!(!store.blocks_path(&name).exists());
+
+        Ok(())
+    }
+}
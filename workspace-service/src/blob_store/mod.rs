@@ -0,0 +1,248 @@
+mod local;
+
+use crate::{
+    azure,
+    config::{BlobStoreKind, Config},
+    pdf::PageCounter,
+    s3,
+};
+use anyhow::{anyhow, Result};
+use async_std::prelude::*;
+use chrono::{DateTime, Utc};
+pub use local::LocalBlobStore;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use url::Url;
+use uuid::Uuid;
+
+/// How long an upload URL can be used for. Any upload older than this has either been copied to
+/// the files container already or was abandoned.
+pub const UPLOAD_URL_VALID_MINUTES: i64 = 15;
+
+/// How long a download URL can be used for.
+pub const DOWNLOAD_URL_VALID_MINUTES: i64 = 15;
+
+/// The contents of a blob, as they are read from the blob store.
+pub type BlobReader = Box<dyn async_std::io::Read + Unpin + Send>;
+
+/// What the blob store knows about an uploaded blob.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobProperties {
+    /// The size of the blob in bytes
+    pub size: u64,
+    /// The content type the client uploaded the blob with, if any
+    pub content_type: Option<String>,
+}
+
+/// The hash and size of the contents of a blob, and what else can be found out about them
+/// without parsing them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobDigest {
+    /// The SHA-256 hash of the contents, as lower case hex
+    pub sha256: String,
+    /// The size of the contents in bytes
+    pub size: u64,
+    /// An estimate of the number of pages, for a PDF whose pages could be counted
+    pub page_count: Option<u32>,
+}
+
+/// Reads up to `len` bytes from the start of a blob, and returns them with a reader that still
+/// reads all of its contents.
+pub async fn peek(mut contents: BlobReader, len: usize) -> Result<(Vec<u8>, BlobReader)> {
+    let mut prefix = Vec::with_capacity(len);
+    (&mut contents)
+        .take(len as u64)
+        .read_to_end(&mut prefix)
+        .await?;
+    let contents = Box::new(async_std::io::Cursor::new(prefix.clone()).chain(contents));
+    Ok((prefix, contents))
+}
+
+/// How much of a blob `digest` reads at a time.
+const DIGEST_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reads the contents of a blob of the MIME type to the end, and returns their hash and size.
+pub async fn digest(contents: &mut BlobReader, file_type: &str) -> Result<BlobDigest> {
+    let mut hasher = Sha256::new();
+    let mut page_counter = match file_type {
+        "application/pdf" => Some(PageCounter::new()),
+        _ => None,
+    };
+    let mut size = 0;
+    let mut chunk = vec![0; DIGEST_CHUNK_SIZE];
+    loop {
+        let len = contents.read(&mut chunk).await?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&chunk[..len]);
+        if let Some(page_counter) = &mut page_counter {
+            page_counter.update(&chunk[..len]);
+        }
+        size += len as u64;
+    }
+
+    Ok(BlobDigest {
+        sha256: format!("{:x}", hasher.finalize()),
+        size,
+        page_count: page_counter.and_then(PageCounter::finish),
+    })
+}
+
+/// Where files are stored. Clients upload a new file to a URL in the upload container, then the
+/// service copies it to the files container, where it stays until it is purged.
+///
+/// Blobs are identified by their URL, which is what we store as the `blob_storage_path` of a
+/// file version.
+#[async_trait::async_trait]
+pub trait BlobStore: Send + Sync {
+    /// Returns a URL in the upload container that a client can upload a blob to, without any
+    /// other credentials.
+    fn create_upload_url(&self, name: &Uuid) -> Result<Url>;
+
+    /// Returns a URL that a client can download the blob from, without any other credentials.
+    fn create_download_url(&self, url: &Url) -> Result<Url>;
+
+    /// Returns the size and content type of a blob in the upload container.
+    async fn upload_properties(&self, url: &Url) -> Result<BlobProperties>;
+
+    /// Opens a blob in the files container for reading. Files can be as large as the maximum
+    /// upload size, so they are streamed rather than read into memory.
+    async fn open_file(&self, url: &Url) -> Result<BlobReader>;
+
+    /// Copies a blob from the upload container to the files container and returns the URL of
+    /// the copy.
+    async fn copy_upload(&self, url: &Url) -> Result<String>;
+
+    /// Deletes a blob from the upload or files container.
+    async fn delete_blob(&self, url: &Url) -> Result<()>;
+
+    /// Lists the blobs in the upload container that were last modified before `before`.
+    async fn find_uploads_modified_before(&self, before: DateTime<Utc>) -> Result<Vec<Url>>;
+
+    /// The endpoints the service has to serve under `/blobs`, if clients talk to the service
+    /// itself to upload and download blobs.
+    fn endpoint(self: Arc<Self>) -> Option<tide::Server<()>> {
+        None
+    }
+}
+
+/// Returns the name an upload URL was created with, which is the last segment of its path in
+/// every blob store.
+pub fn upload_name(url: &Url) -> Result<Uuid> {
+    let name = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .ok_or_else(|| anyhow!("cannot get blob name from url"))?;
+    Ok(Uuid::parse_str(name)?)
+}
+
+/// Creates the blob store selected with `--blob-store`.
+pub fn from_config(config: &Config) -> Result<Arc<dyn BlobStore>> {
+    match config.blob_store {
+        BlobStoreKind::Azure => Ok(Arc::new(azure::Config::new(
+            config
+                .file_storage_access_key
+                .clone()
+                .ok_or_else(|| anyhow!("FILE_STORAGE_ACCESS_KEY is required"))?,
+            config
+                .upload_container_url
+                .clone()
+                .ok_or_else(|| anyhow!("UPLOAD_CONTAINER_URL is required"))?,
+            config
+                .files_container_url
+                .clone()
+                .ok_or_else(|| anyhow!("FILES_CONTAINER_URL is required"))?,
+        )?)),
+        BlobStoreKind::S3 => Ok(Arc::new(s3::Config::new(
+            config
+                .s3_access_key_id
+                .clone()
+                .ok_or_else(|| anyhow!("S3_ACCESS_KEY_ID is required"))?,
+            config
+                .s3_secret_access_key
+                .clone()
+                .ok_or_else(|| anyhow!("S3_SECRET_ACCESS_KEY is required"))?,
+            config.s3_region.clone(),
+            config
+                .s3_upload_bucket_url
+                .clone()
+                .ok_or_else(|| anyhow!("S3_UPLOAD_BUCKET_URL is required"))?,
+            config
+                .s3_files_bucket_url
+                .clone()
+                .ok_or_else(|| anyhow!("S3_FILES_BUCKET_URL is required"))?,
+        )?)),
+        BlobStoreKind::Local => Ok(Arc::new(LocalBlobStore::new(
+            config
+                .local_blob_store_path
+                .clone()
+                .ok_or_else(|| anyhow!("LOCAL_BLOB_STORE_PATH is required"))?,
+            &config.local_blob_store_url,
+            config.local_blob_store_key.as_deref(),
+        )?)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[async_std::test]
+    async fn digest_hashes_and_counts_the_contents() -> Result<()> {
+        let contents = vec![b'a'; DIGEST_CHUNK_SIZE + 1];
+        let mut reader: BlobReader = Box::new(async_std::io::Cursor::new(contents));
+
+        let digest = digest(&mut reader, "text/plain").await?;
+
+        assert_eq!(digest.size, DIGEST_CHUNK_SIZE as u64 + 1);
+        assert_eq!(
+            digest.sha256,
+            format!("{:x}", Sha256::digest(&vec![b'a'; DIGEST_CHUNK_SIZE + 1]))
+        );
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn peek_returns_the_prefix_and_all_the_contents() -> Result<()> {
+        let reader: BlobReader = Box::new(async_std::io::Cursor::new(b"hello".to_vec()));
+
+        let (prefix, mut reader) = peek(reader, 4).await?;
+
+        let mut contents = vec![];
+        reader.read_to_end(&mut contents).await?;
+        assert_eq!(prefix, b"hell");
+        assert_eq!(contents, b"hello");
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn digest_of_an_empty_blob() -> Result<()> {
+        let mut reader: BlobReader = Box::new(async_std::io::Cursor::new(vec![]));
+
+        let digest = digest(&mut reader, "text/plain").await?;
+
+        assert_eq!(
+            digest,
+            BlobDigest {
+                sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".into(),
+                size: 0,
+                page_count: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn digest_counts_the_pages_of_a_pdf() -> Result<()> {
+        let pdf = b"%PDF-1.4\n1 0 obj << /Type /Pages /Count 2 >> endobj\n\
+            2 0 obj << /Type /Page /Parent 1 0 R >> endobj\n\
+            3 0 obj << /Type /Page /Parent 1 0 R >> endobj\n%%EOF\n";
+        let mut reader: BlobReader = Box::new(async_std::io::Cursor::new(pdf.to_vec()));
+
+        let digest = digest(&mut reader, "application/pdf").await?;
+
+        assert_eq!(digest.page_count, Some(2));
+        Ok(())
+    }
+}
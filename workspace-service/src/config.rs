@@ -1,8 +1,113 @@
+use crate::auth::JwksSource;
+use anyhow::{bail, Result};
+use std::{path::PathBuf, str::FromStr};
 use structopt::StructOpt;
 use url::Url;
 
+#[derive(Debug, Clone, StructOpt)]
+pub enum Command {
+    /// Permanently deletes files and folders that were deleted longer ago than the retention
+    /// period, the blobs that only they used, and abandoned uploads, then exits
+    Purge(PurgeConfig),
+}
+
+#[derive(Debug, Clone, StructOpt)]
+pub struct PurgeConfig {
+    /// How many days to keep deleted files and folders for, so that they can be restored
+    #[structopt(long, env = "PURGE_RETENTION_DAYS", default_value = "30")]
+    pub retention_days: u32,
+
+    /// Report what would be removed, without removing anything
+    #[structopt(long)]
+    pub dry_run: bool,
+}
+
+/// Where to store files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlobStoreKind {
+    Azure,
+    Local,
+    S3,
+}
+
+impl FromStr for BlobStoreKind {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "azure" => Ok(BlobStoreKind::Azure),
+            "local" => Ok(BlobStoreKind::Local),
+            "s3" => Ok(BlobStoreKind::S3),
+            _ => bail!("unknown blob store {}", input),
+        }
+    }
+}
+
+/// How to scan files for malware.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScannerKind {
+    Clamd,
+    None,
+}
+
+impl FromStr for ScannerKind {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "clamd" => Ok(ScannerKind::Clamd),
+            "none" => Ok(ScannerKind::None),
+            _ => bail!("unknown scanner {}", input),
+        }
+    }
+}
+
+/// Where to publish domain events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventPublisherKind {
+    EventGrid,
+    Webhook,
+    KafkaRest,
+}
+
+impl FromStr for EventPublisherKind {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "eventgrid" => Ok(EventPublisherKind::EventGrid),
+            "webhook" => Ok(EventPublisherKind::Webhook),
+            "kafka-rest" => Ok(EventPublisherKind::KafkaRest),
+            _ => bail!("unknown event publisher {}", input),
+        }
+    }
+}
+
+/// How to encode published events.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventSchemaKind {
+    EventGrid,
+    CloudEvents,
+}
+
+impl FromStr for EventSchemaKind {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "eventgrid" => Ok(EventSchemaKind::EventGrid),
+            "cloudevents" => Ok(EventSchemaKind::CloudEvents),
+            _ => bail!("unknown event schema {}", input),
+        }
+    }
+}
+
 #[derive(Debug, Clone, StructOpt)]
 pub struct Config {
+    /// Run a maintenance task instead of serving the API
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+
     /// Immediately exit, can be used to check that the service is configured and will run
     #[structopt(long)]
     pub selfcheck_only: bool,
@@ -25,6 +130,27 @@ pub struct Config {
     )]
     pub database_url: Option<Url>,
 
+    /// Where to publish events, separated by commas: `eventgrid` for an Azure EventGrid topic,
+    /// `webhook` for an HTTP endpoint, and `kafka-rest` for a Kafka topic through a Kafka REST
+    /// Proxy. Every event is published to all of them.
+    #[structopt(
+        long,
+        env = "EVENT_PUBLISHERS",
+        default_value = "eventgrid",
+        possible_values = &["eventgrid", "webhook", "kafka-rest"],
+        use_delimiter = true,
+    )]
+    pub event_publishers: Vec<EventPublisherKind>,
+
+    /// Check every event against the event schema before publishing it, and fail instead of
+    /// publishing events that do not match
+    #[structopt(long, env = "VALIDATE_EVENTS")]
+    pub validate_events: bool,
+
+    /// The `source` of events published in the CloudEvents schema
+    #[structopt(long, env = "CLOUDEVENTS_SOURCE", default_value = "/workspace-service")]
+    pub cloudevents_source: String,
+
     /// Endpoint for the Azure EventGrid topic
     #[structopt(
         long,
@@ -37,30 +163,161 @@ pub struct Config {
     #[structopt(long, env = "EVENTGRID_TOPIC_KEY", hide_env_values = true)]
     pub eventgrid_topic_key: Option<String>,
 
-    /// The Azure Blob Storage Account key for files
+    /// The input schema of the EventGrid topic: `eventgrid` or `cloudevents`
     #[structopt(
         long,
-        env = "FILE_STORAGE_ACCESS_KEY",
-        hide_env_values = true,
-        required_unless_one(&["selfcheck-only", "generate-schema-only"])
+        env = "EVENTGRID_SCHEMA",
+        default_value = "eventgrid",
+        possible_values = &["eventgrid", "cloudevents"],
     )]
-    pub file_storage_access_key: Option<String>,
+    pub eventgrid_schema: EventSchemaKind,
+
+    /// How many times to retry publishing events to EventGrid when it is throttling requests or
+    /// unavailable
+    #[structopt(long, env = "EVENTGRID_MAX_RETRIES", default_value = "3")]
+    pub eventgrid_max_retries: u32,
 
-    /// The Azure Blob Storage Container URL for file uploads
+    /// How long to wait for EventGrid to respond to each request, in seconds
+    #[structopt(long, env = "EVENTGRID_TIMEOUT_SECONDS", default_value = "30")]
+    pub eventgrid_timeout_seconds: u64,
+
+    /// The URL to POST events to. Required with the webhook event publisher.
+    #[structopt(long, env = "WEBHOOK_URL", parse(try_from_str = str::parse))]
+    pub webhook_url: Option<Url>,
+
+    /// The secret used to sign the events sent to the webhook, which the receiver uses to check
+    /// that they came from this service. Required with the webhook event publisher.
+    #[structopt(long, env = "WEBHOOK_SECRET", hide_env_values = true)]
+    pub webhook_secret: Option<String>,
+
+    /// How to encode the events sent to the webhook: `eventgrid` or `cloudevents`
     #[structopt(
         long,
-        env = "UPLOAD_CONTAINER_URL",
-        parse(try_from_str = str::parse),
-        required_unless_one(&["selfcheck-only", "generate-schema-only"])
+        env = "WEBHOOK_SCHEMA",
+        default_value = "eventgrid",
+        possible_values = &["eventgrid", "cloudevents"],
+    )]
+    pub webhook_schema: EventSchemaKind,
+
+    /// The URL of the Kafka REST Proxy, e.g. `http://kafka-rest:8082`. Required with the
+    /// kafka-rest event publisher.
+    #[structopt(long, env = "KAFKA_REST_PROXY_URL", parse(try_from_str = str::parse))]
+    pub kafka_rest_proxy_url: Option<Url>,
+
+    /// The Kafka topic to produce events to
+    #[structopt(long, env = "KAFKA_TOPIC", default_value = "domain-events")]
+    pub kafka_topic: String,
+
+    /// How to encode the events produced to Kafka: `eventgrid` or `cloudevents`
+    #[structopt(
+        long,
+        env = "KAFKA_SCHEMA",
+        default_value = "eventgrid",
+        possible_values = &["eventgrid", "cloudevents"],
     )]
+    pub kafka_schema: EventSchemaKind,
+
+    /// Where to store files: `azure` for Azure Blob Storage, `s3` for an S3-compatible object
+    /// store, or `local` for a directory that is served by the service itself
+    #[structopt(
+        long,
+        env = "BLOB_STORE",
+        default_value = "azure",
+        possible_values = &["azure", "local", "s3"],
+    )]
+    pub blob_store: BlobStoreKind,
+
+    /// The Azure Blob Storage Account key for files. Required with the azure blob store.
+    #[structopt(long, env = "FILE_STORAGE_ACCESS_KEY", hide_env_values = true)]
+    pub file_storage_access_key: Option<String>,
+
+    /// The Azure Blob Storage Container URL for file uploads. Required with the azure blob store.
+    #[structopt(long, env = "UPLOAD_CONTAINER_URL", parse(try_from_str = str::parse))]
     pub upload_container_url: Option<Url>,
 
-    /// The Azure Blob Storage Container URL for files
+    /// The Azure Blob Storage Container URL for files. Required with the azure blob store.
+    #[structopt(long, env = "FILES_CONTAINER_URL", parse(try_from_str = str::parse))]
+    pub files_container_url: Option<Url>,
+
+    /// The access key id for the S3 buckets. Required with the s3 blob store.
+    #[structopt(long, env = "S3_ACCESS_KEY_ID")]
+    pub s3_access_key_id: Option<String>,
+
+    /// The secret access key for the S3 buckets. Required with the s3 blob store.
+    #[structopt(long, env = "S3_SECRET_ACCESS_KEY", hide_env_values = true)]
+    pub s3_secret_access_key: Option<String>,
+
+    /// The region of the S3 buckets. MinIO accepts any region.
+    #[structopt(long, env = "S3_REGION", default_value = "us-east-1")]
+    pub s3_region: String,
+
+    /// The URL of the S3 bucket for file uploads, e.g. `http://localhost:9000/upload` or
+    /// `https://upload.s3.eu-west-2.amazonaws.com`. Required with the s3 blob store.
+    #[structopt(long, env = "S3_UPLOAD_BUCKET_URL", parse(try_from_str = str::parse))]
+    pub s3_upload_bucket_url: Option<Url>,
+
+    /// The URL of the S3 bucket for files. Required with the s3 blob store.
+    #[structopt(long, env = "S3_FILES_BUCKET_URL", parse(try_from_str = str::parse))]
+    pub s3_files_bucket_url: Option<Url>,
+
+    /// The directory to store files in. Required with the local blob store.
+    #[structopt(long, env = "LOCAL_BLOB_STORE_PATH", parse(from_os_str))]
+    pub local_blob_store_path: Option<PathBuf>,
+
+    /// The URL clients use to reach the service, for the upload and download URLs of the local
+    /// blob store
     #[structopt(
         long,
-        env = "FILES_CONTAINER_URL",
+        env = "LOCAL_BLOB_STORE_URL",
         parse(try_from_str = str::parse),
-        required_unless_one(&["selfcheck-only", "generate-schema-only"])
+        default_value = "http://localhost:3030",
     )]
-    pub files_container_url: Option<Url>,
+    pub local_blob_store_url: Url,
+
+    /// The key used to sign the upload and download URLs of the local blob store. If it is not
+    /// set, a random key is used and URLs stop working when the service restarts.
+    #[structopt(long, env = "LOCAL_BLOB_STORE_KEY", hide_env_values = true)]
+    pub local_blob_store_key: Option<String>,
+
+    /// The largest file, in bytes, that clients can upload
+    #[structopt(long, env = "MAX_UPLOAD_SIZE", default_value = "104857600")]
+    pub max_upload_size: u64,
+
+    /// How to scan new files for malware before they can be downloaded: `clamd` for a ClamAV
+    /// daemon, or `none` to treat every file as clean. Only use `none` for local development!
+    #[structopt(
+        long,
+        env = "SCANNER",
+        default_value = "clamd",
+        possible_values = &["clamd", "none"],
+    )]
+    pub scanner: ScannerKind,
+
+    /// The address of the ClamAV daemon, either `host:port` or the path of a unix socket
+    #[structopt(long, env = "CLAMD_ADDRESS", default_value = "localhost:3310")]
+    pub clamd_address: String,
+
+    /// How often to look for new files to scan, in seconds
+    #[structopt(long, env = "SCAN_INTERVAL_SECONDS", default_value = "5")]
+    pub scan_interval_seconds: u64,
+
+    /// The expected issuer (`iss` claim) of identity tokens. Required to serve the API, unless
+    /// --trust-auth-id-header is set.
+    #[structopt(long, env = "AUTH_ISSUER")]
+    pub auth_issuer: Option<String>,
+
+    /// The expected audience (`aud` claim) of identity tokens. Required to serve the API, unless
+    /// --trust-auth-id-header is set.
+    #[structopt(long, env = "AUTH_AUDIENCE")]
+    pub auth_audience: Option<String>,
+
+    /// The URL or file path of the JSON Web Key Set used to verify identity tokens. Required to
+    /// serve the API, unless --trust-auth-id-header is set.
+    #[structopt(long, env = "AUTH_JWKS", parse(try_from_str = str::parse))]
+    pub auth_jwks: Option<JwksSource>,
+
+    /// Trust the x-user-auth-id header instead of verifying identity tokens. Only use this for
+    /// local development, as it allows anyone to impersonate any user!
+    #[structopt(long, env = "TRUST_AUTH_ID_HEADER")]
+    pub trust_auth_id_header: bool,
 }
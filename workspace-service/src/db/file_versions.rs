@@ -3,7 +3,48 @@
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use sqlx::{types::Uuid, Executor, Postgres};
+use sqlx::{types::Uuid, Executor, PgPool, Postgres};
+use std::str::FromStr;
+
+/// Whether a file version has been scanned for malware. A version can only be downloaded once it
+/// is clean.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanStatus {
+    /// The version has not been scanned yet
+    Pending,
+    /// The scanner found nothing
+    Clean,
+    /// The scanner found malware
+    Infected,
+    /// The blob could not be scanned, however many times it was tried
+    Failed,
+}
+
+impl ScanStatus {
+    /// The value stored in the `scan_status` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScanStatus::Pending => "PENDING",
+            ScanStatus::Clean => "CLEAN",
+            ScanStatus::Infected => "INFECTED",
+            ScanStatus::Failed => "FAILED",
+        }
+    }
+}
+
+impl FromStr for ScanStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "PENDING" => Ok(ScanStatus::Pending),
+            "CLEAN" => Ok(ScanStatus::Clean),
+            "INFECTED" => Ok(ScanStatus::Infected),
+            "FAILED" => Ok(ScanStatus::Failed),
+            _ => anyhow::bail!("unknown scan status {}", input),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct FileVersion {
@@ -19,6 +60,23 @@ pub struct FileVersion {
     pub created_by: Uuid,
     pub version_number: i16,
     pub version_label: String,
+    pub scan_status: String,
+    /// The SHA-256 hash of the blob as hex, or `None` for versions created before hashes were
+    /// recorded
+    pub sha256: Option<String>,
+    /// The size of the blob in bytes, or `None` for versions created before sizes were recorded
+    pub file_size: Option<i64>,
+    /// An estimate of the number of pages, for a PDF whose pages could be counted
+    pub page_count: Option<i32>,
+}
+
+/// A version whose scan status was set by `FileVersionRepo::update_scan_status`.
+#[derive(Clone, Debug)]
+pub struct ScannedVersion {
+    pub id: Uuid,
+    pub file: Uuid,
+    pub version_number: i16,
+    pub workspace: Uuid,
 }
 
 #[cfg_attr(test, allow(dead_code))]
@@ -39,6 +97,10 @@ impl FileVersionRepo {
         created_by: Uuid,
         version_number: i16,
         version_label: &str,
+        scan_status: ScanStatus,
+        sha256: Option<&str>,
+        file_size: Option<i64>,
+        page_count: Option<i32>,
         executor: E,
     ) -> Result<FileVersion>
     where
@@ -58,10 +120,315 @@ impl FileVersionRepo {
             created_by,
             version_number,
             version_label,
+            scan_status.as_str(),
+            sha256,
+            file_size,
+            page_count,
         )
         .fetch_one(executor)
         .await?;
 
         Ok(file_version)
     }
+
+    pub async fn find_by_id(id: Uuid, pool: &PgPool) -> Result<FileVersion> {
+        let file_version =
+            sqlx::query_file_as!(FileVersion, "sql/file_versions/find_by_id.sql", id)
+                .fetch_one(pool)
+                .await?;
+
+        Ok(file_version)
+    }
+
+    pub async fn find_by_file_id(file: Uuid, pool: &PgPool) -> Result<Vec<FileVersion>> {
+        let file_versions =
+            sqlx::query_file_as!(FileVersion, "sql/file_versions/find_by_file_id.sql", file)
+                .fetch_all(pool)
+                .await?;
+
+        Ok(file_versions)
+    }
+
+    /// Finds up to `limit` blobs that have versions waiting to be scanned and are due to be
+    /// scanned, the ones that have been due the longest first.
+    pub async fn find_blobs_pending_scan(limit: i64, pool: &PgPool) -> Result<Vec<String>> {
+        let blobs = sqlx::query_file!("sql/file_versions/find_blobs_pending_scan.sql", limit)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(blobs
+            .into_iter()
+            .map(|blob| blob.blob_storage_path)
+            .collect())
+    }
+
+    /// Records that the blob of the versions that are waiting to be scanned could not be
+    /// scanned, so that it is only tried again after a delay, which doubles with every attempt.
+    /// Once it has been tried `max_attempts` times, the versions are marked as failed instead.
+    /// Returns whether they were.
+    pub async fn record_scan_failure(
+        blob_storage_path: &str,
+        max_attempts: i32,
+        pool: &PgPool,
+    ) -> Result<bool> {
+        let versions = sqlx::query_file!(
+            "sql/file_versions/record_scan_failure.sql",
+            blob_storage_path,
+            max_attempts
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(versions
+            .iter()
+            .any(|version| version.scan_status == ScanStatus::Failed.as_str()))
+    }
+
+    /// Sets the scan status of the versions of the blob that are waiting to be scanned, and
+    /// returns them.
+    pub async fn update_scan_status(
+        blob_storage_path: &str,
+        scan_status: ScanStatus,
+        pool: &PgPool,
+    ) -> Result<Vec<ScannedVersion>> {
+        let versions = sqlx::query_file_as!(
+            ScannedVersion,
+            "sql/file_versions/update_scan_status.sql",
+            blob_storage_path,
+            scan_status.as_str()
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(versions)
+    }
+
+    /// Moves the latest version of the file into the folder. The earlier versions stay in the
+    /// folders they were in.
+    pub async fn move_to_folder<'c, E>(file: Uuid, folder: Uuid, executor: E) -> Result<()>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        let moved = sqlx::query_file!("sql/file_versions/move_to_folder.sql", file, folder)
+            .fetch_all(executor)
+            .await?;
+        if moved.is_empty() {
+            anyhow::bail!("file {} not found", file);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub struct FileVersionRepoFake {}
+
+#[cfg(test)]
+use std::collections::HashMap;
+#[cfg(test)]
+use std::sync::Mutex;
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref FILE_VERSIONS: Mutex<HashMap<Uuid, FileVersion>> = Mutex::new(HashMap::new());
+    /// How many times each blob could not be scanned, and when it is due to be tried again
+    static ref SCAN_FAILURES: Mutex<HashMap<String, (i32, DateTime<Utc>)>> =
+        Mutex::new(HashMap::new());
+}
+
+// Fake implementation for tests. If you want integration tests that exercise the database,
+// see https://doc.rust-lang.org/rust-by-example/testing/integration_testing.html.
+#[cfg(test)]
+impl FileVersionRepoFake {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create<'c, E>(
+        id: Uuid,
+        folder: Uuid,
+        file: Uuid,
+        file_title: &str,
+        file_description: &str,
+        file_name: &str,
+        file_type: &str,
+        blob_storage_path: &str,
+        created_by: Uuid,
+        version_number: i16,
+        version_label: &str,
+        scan_status: ScanStatus,
+        sha256: Option<&str>,
+        file_size: Option<i64>,
+        page_count: Option<i32>,
+        _executor: E,
+    ) -> Result<FileVersion>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        let file_version = FileVersion {
+            id,
+            folder,
+            file,
+            file_title: file_title.into(),
+            file_description: file_description.into(),
+            file_name: file_name.into(),
+            file_type: file_type.into(),
+            blob_storage_path: blob_storage_path.into(),
+            created_at: Utc::now(),
+            created_by,
+            version_number,
+            version_label: version_label.into(),
+            scan_status: scan_status.as_str().into(),
+            sha256: sha256.map(String::from),
+            file_size,
+            page_count,
+        };
+        let mut file_versions = FILE_VERSIONS.lock().unwrap();
+        file_versions.insert(file_version.id, file_version.clone());
+        Ok(file_version)
+    }
+
+    pub async fn find_by_id(id: Uuid, _pool: &PgPool) -> Result<FileVersion> {
+        let file_versions = FILE_VERSIONS.lock().unwrap();
+        if let Some(file_version) = file_versions.get(&id) {
+            return Ok(file_version.clone());
+        }
+
+        let file_version = FileVersion {
+            id,
+            folder: Uuid::parse_str("805eaaca-6c7d-40ac-bede-99b83ef838e4")?,
+            file: Uuid::parse_str("18a5ab56-2e6f-4a16-8ba9-7d5e8b7d8f53")?,
+            file_title: "fake file version".into(),
+            file_description: "fake file version for tests".into(),
+            file_name: "old.txt".into(),
+            file_type: "text/plain".into(),
+            blob_storage_path: "http://localhost:10000/devstoreaccount1/files/old-fake".into(),
+            created_at: Utc::now(),
+            created_by: Uuid::new_v4(),
+            version_number: 1,
+            version_label: "".into(),
+            scan_status: ScanStatus::Clean.as_str().into(),
+            sha256: None,
+            file_size: None,
+            page_count: None,
+        };
+        Ok(file_version)
+    }
+
+    pub async fn find_by_file_id(file: Uuid, _pool: &PgPool) -> Result<Vec<FileVersion>> {
+        let file_versions = FILE_VERSIONS.lock().unwrap();
+        let mut file_versions: Vec<FileVersion> = file_versions
+            .values()
+            .filter(|file_version| file_version.file == file)
+            .cloned()
+            .collect();
+        file_versions.sort_by_key(|file_version| -file_version.version_number);
+        Ok(file_versions)
+    }
+
+    pub async fn find_blobs_pending_scan(limit: i64, _pool: &PgPool) -> Result<Vec<String>> {
+        let file_versions = FILE_VERSIONS.lock().unwrap();
+        let scan_failures = SCAN_FAILURES.lock().unwrap();
+        let next_scan_at = |file_version: &FileVersion| {
+            scan_failures
+                .get(&file_version.blob_storage_path)
+                .map(|(_, next_scan_at)| *next_scan_at)
+                .unwrap_or(file_version.created_at)
+        };
+        let now = Utc::now();
+        let mut pending: Vec<&FileVersion> = file_versions
+            .values()
+            .filter(|file_version| {
+                file_version.scan_status == ScanStatus::Pending.as_str()
+                    && next_scan_at(file_version) <= now
+            })
+            .collect();
+        pending.sort_by_key(|file_version| next_scan_at(file_version));
+        let mut blobs: Vec<String> = vec![];
+        for file_version in pending {
+            if !blobs.contains(&file_version.blob_storage_path) {
+                blobs.push(file_version.blob_storage_path.clone());
+            }
+        }
+        blobs.truncate(limit as usize);
+        Ok(blobs)
+    }
+
+    pub async fn record_scan_failure(
+        blob_storage_path: &str,
+        max_attempts: i32,
+        _pool: &PgPool,
+    ) -> Result<bool> {
+        let attempts = {
+            let mut scan_failures = SCAN_FAILURES.lock().unwrap();
+            let (attempts, next_scan_at) = scan_failures
+                .entry(blob_storage_path.into())
+                .or_insert((0, Utc::now()));
+            *next_scan_at = Utc::now() + chrono::Duration::minutes(2i64.pow(*attempts as u32));
+            *attempts += 1;
+            *attempts
+        };
+        if attempts < max_attempts {
+            return Ok(false);
+        }
+
+        let mut failed = false;
+        for file_version in FILE_VERSIONS
+            .lock()
+            .unwrap()
+            .values_mut()
+            .filter(|file_version| {
+                file_version.blob_storage_path == blob_storage_path
+                    && file_version.scan_status == ScanStatus::Pending.as_str()
+            })
+        {
+            file_version.scan_status = ScanStatus::Failed.as_str().into();
+            failed = true;
+        }
+        Ok(failed)
+    }
+
+    pub async fn update_scan_status(
+        blob_storage_path: &str,
+        scan_status: ScanStatus,
+        pool: &PgPool,
+    ) -> Result<Vec<ScannedVersion>> {
+        let updated: Vec<FileVersion> = FILE_VERSIONS
+            .lock()
+            .unwrap()
+            .values_mut()
+            .filter(|file_version| {
+                file_version.blob_storage_path == blob_storage_path
+                    && file_version.scan_status == ScanStatus::Pending.as_str()
+            })
+            .map(|file_version| {
+                file_version.scan_status = scan_status.as_str().into();
+                file_version.clone()
+            })
+            .collect();
+        let mut versions = vec![];
+        for file_version in updated {
+            let folder = super::FolderRepo::find_by_id(file_version.folder, pool).await?;
+            versions.push(ScannedVersion {
+                id: file_version.id,
+                file: file_version.file,
+                version_number: file_version.version_number,
+                workspace: folder.workspace,
+            });
+        }
+        Ok(versions)
+    }
+
+    pub async fn move_to_folder<'c, E>(file: Uuid, folder: Uuid, _executor: E) -> Result<()>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        // The latest version of a fake file is the one with the highest version number.
+        let mut file_versions = FILE_VERSIONS.lock().unwrap();
+        let latest = file_versions
+            .values_mut()
+            .filter(|file_version| file_version.file == file)
+            .max_by_key(|file_version| file_version.version_number)
+            .ok_or_else(|| anyhow::anyhow!("file {} not found", file))?;
+        latest.folder = folder;
+
+        Ok(())
+    }
 }
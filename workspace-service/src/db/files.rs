@@ -13,6 +13,9 @@ pub struct File {
     pub deleted_at: Option<DateTime<Utc>>,
     pub deleted_by: Option<Uuid>,
     pub latest_version: Uuid,
+    /// The folder whose deletion also deleted this file, or `None` if the file was deleted
+    /// directly
+    pub deleted_with: Option<Uuid>,
 }
 
 #[cfg_attr(test, allow(dead_code))]
@@ -68,6 +71,10 @@ pub struct FileWithVersion {
     pub created_at: DateTime<Utc>,
     pub modified_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
+    pub scan_status: String,
+    pub sha256: Option<String>,
+    pub file_size: Option<i64>,
+    pub page_count: Option<i32>,
 }
 
 impl From<(File, super::FileVersion)> for FileWithVersion {
@@ -86,10 +93,27 @@ impl From<(File, super::FileVersion)> for FileWithVersion {
             created_at: file.created_at,
             modified_at: file_version.created_at,
             deleted_at: file.deleted_at,
+            scan_status: file_version.scan_status,
+            sha256: file_version.sha256,
+            file_size: file_version.file_size,
+            page_count: file_version.page_count,
         }
     }
 }
 
+/// How many files there are in a workspace and how much space they take up. Versions uploaded
+/// before sizes were recorded count as empty.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileTotals {
+    /// The number of undeleted files
+    pub file_count: i64,
+    /// The total size of the latest versions of the undeleted files, in bytes
+    pub total_file_size: i64,
+    /// The total size of every blob that any version of any file in the workspace refers to,
+    /// including deleted files and old versions, in bytes
+    pub stored_size: i64,
+}
+
 #[derive(Clone)]
 pub struct CreateFileArgs<'a> {
     pub user_id: Uuid,
@@ -99,6 +123,12 @@ pub struct CreateFileArgs<'a> {
     pub file_name: &'a str,
     pub file_type: &'a str,
     pub blob_storage_path: &'a str,
+    /// The SHA-256 hash of the blob, as hex
+    pub sha256: &'a str,
+    /// The size of the blob in bytes
+    pub file_size: i64,
+    /// An estimate of the number of pages, for a PDF whose pages could be counted
+    pub page_count: Option<i32>,
 }
 
 #[derive(Clone)]
@@ -113,6 +143,13 @@ pub struct CreateFileVersionArgs<'a> {
     pub file_type: &'a str,
     pub blob_storage_path: &'a str,
     pub version_number: i16,
+    /// `Pending` for a new upload, or the status of the version whose blob is reused
+    pub scan_status: super::ScanStatus,
+    /// The hash and size of the blob, which are unknown for a blob reused from a version created
+    /// before they were recorded
+    pub sha256: Option<&'a str>,
+    pub file_size: Option<i64>,
+    pub page_count: Option<i32>,
 }
 
 #[cfg_attr(test, allow(dead_code))]
@@ -126,7 +163,7 @@ impl FileWithVersionRepo {
         let mut tx = pool.begin().await?;
         super::defer_all_constraints(&mut tx).await?;
         let file = FileRepo::create(args.user_id, version_id, &mut tx).await?;
-        let file_version = super::FileVersionRepo::create(
+        let file_version = super::file_versions::FileVersionRepo::create(
             version_id,
             args.folder_id,
             file.id,
@@ -138,6 +175,10 @@ impl FileWithVersionRepo {
             args.user_id,
             1,
             "",
+            super::ScanStatus::Pending,
+            Some(args.sha256),
+            Some(args.file_size),
+            args.page_count,
             &mut tx,
         )
         .await?;
@@ -151,7 +192,7 @@ impl FileWithVersionRepo {
         pool: &PgPool,
     ) -> Result<FileWithVersion> {
         let mut tx = pool.begin().await?;
-        let file_version = super::FileVersionRepo::create(
+        let file_version = super::file_versions::FileVersionRepo::create(
             Uuid::new_v4(),
             args.folder_id,
             args.file_id,
@@ -163,6 +204,10 @@ impl FileWithVersionRepo {
             args.user_id,
             args.version_number,
             "",
+            args.scan_status,
+            args.sha256,
+            args.file_size,
+            args.page_count,
             &mut tx,
         )
         .await?;
@@ -178,14 +223,33 @@ impl FileWithVersionRepo {
         Ok((file, file_version).into())
     }
 
-    pub async fn find_by_folder(folder: Uuid, pool: &PgPool) -> Result<Vec<FileWithVersion>> {
-        let files = sqlx::query_file_as!(FileWithVersion, "sql/files/find_by_folder.sql", folder)
-            .fetch_all(pool)
-            .await?;
+    pub async fn find_by_folder(
+        folder: Uuid,
+        page: super::PageArgs<'_>,
+        pool: &PgPool,
+    ) -> Result<Vec<FileWithVersion>> {
+        let files = sqlx::query_file_as!(
+            FileWithVersion,
+            "sql/files/find_by_folder.sql",
+            folder,
+            page.after_sort_key,
+            page.after_id,
+            page.limit
+        )
+        .fetch_all(pool)
+        .await?;
 
         Ok(files)
     }
 
+    pub async fn count_by_folder(folder: Uuid, pool: &PgPool) -> Result<i64> {
+        let count = sqlx::query_file!("sql/files/count_by_folder.sql", folder)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(count.count.unwrap_or(0))
+    }
+
     pub async fn find_by_id(id: Uuid, pool: &PgPool) -> Result<FileWithVersion> {
         let file = sqlx::query_file_as!(FileWithVersion, "sql/files/find_by_id.sql", id)
             .fetch_one(pool)
@@ -194,6 +258,69 @@ impl FileWithVersionRepo {
         Ok(file)
     }
 
+    /// Finds the file and locks it until the transaction ends, so that it cannot be changed,
+    /// moved or deleted in the meantime.
+    pub async fn find_by_id_for_update(
+        id: Uuid,
+        tx: &mut super::Transaction,
+    ) -> Result<FileWithVersion> {
+        let file = sqlx::query_file_as!(FileWithVersion, "sql/files/find_by_id_for_update.sql", id)
+            .fetch_one(tx)
+            .await?;
+
+        Ok(file)
+    }
+
+    /// Finds the other undeleted files in the workspace whose latest version has the hash, ordered
+    /// by title. Files in folders that only workspace members can see are only included if
+    /// `workspace_member` is true.
+    pub async fn find_duplicates(
+        workspace: Uuid,
+        sha256: &str,
+        file: Uuid,
+        workspace_member: bool,
+        pool: &PgPool,
+    ) -> Result<Vec<FileWithVersion>> {
+        let files = sqlx::query_file_as!(
+            FileWithVersion,
+            "sql/files/find_duplicates.sql",
+            workspace,
+            sha256,
+            file,
+            workspace_member
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    pub async fn totals_by_workspace(workspace: Uuid, pool: &PgPool) -> Result<FileTotals> {
+        let totals = sqlx::query_file!("sql/files/totals_by_workspace.sql", workspace)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(FileTotals {
+            file_count: totals.file_count.unwrap_or(0),
+            total_file_size: totals.total_file_size.unwrap_or(0),
+            stored_size: totals.stored_size.unwrap_or(0),
+        })
+    }
+
+    /// Moves the files into the folder. None of them are moved unless the transaction is
+    /// committed.
+    pub async fn move_to_folder(
+        files: &[Uuid],
+        folder: Uuid,
+        tx: &mut super::Transaction,
+    ) -> Result<()> {
+        for file in files {
+            super::file_versions::FileVersionRepo::move_to_folder(*file, folder, &mut *tx).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn delete(id: Uuid, deleted_by: Uuid, pool: &PgPool) -> Result<FileWithVersion> {
         let file = sqlx::query_file_as!(FileWithVersion, "sql/files/delete.sql", id, deleted_by)
             .fetch_one(pool)
@@ -201,59 +328,232 @@ impl FileWithVersionRepo {
 
         Ok(file)
     }
+
+    /// Finds the deleted files in the workspace, most recently deleted first. The sort key of the
+    /// page is the RFC 3339 time that the last file on the previous page was deleted.
+    pub async fn find_deleted_by_workspace(
+        workspace: Uuid,
+        page: super::PageArgs<'_>,
+        pool: &PgPool,
+    ) -> Result<Vec<FileWithVersion>> {
+        let files = sqlx::query_file_as!(
+            FileWithVersion,
+            "sql/files/find_deleted_by_workspace.sql",
+            workspace,
+            page.after_sort_key,
+            page.after_id,
+            page.limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    pub async fn count_deleted_by_workspace(workspace: Uuid, pool: &PgPool) -> Result<i64> {
+        let count = sqlx::query_file!("sql/files/count_deleted_by_workspace.sql", workspace)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(count.count.unwrap_or(0))
+    }
+
+    pub async fn find_deleted_by_id(id: Uuid, pool: &PgPool) -> Result<Option<FileWithVersion>> {
+        let file = sqlx::query_file_as!(FileWithVersion, "sql/files/find_deleted_by_id.sql", id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(file)
+    }
+
+    pub async fn restore(id: Uuid, pool: &PgPool) -> Result<FileWithVersion> {
+        let file = sqlx::query_file_as!(FileWithVersion, "sql/files/restore.sql", id)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(file)
+    }
+
+    /// Deletes the files in the folder and in the folders in it, as part of deleting the folder.
+    /// Returns the number of files deleted.
+    pub(super) async fn delete_by_folder<'c, E>(
+        folder: Uuid,
+        deleted_by: Uuid,
+        executor: E,
+    ) -> Result<i64>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        let deleted = sqlx::query_file!("sql/files/delete_by_folder.sql", folder, deleted_by)
+            .fetch_all(executor)
+            .await?;
+
+        Ok(deleted.len() as i64)
+    }
+
+    /// Restores the files that were deleted with the folder. Returns the number of files restored.
+    pub(super) async fn restore_by_folder<'c, E>(folder: Uuid, executor: E) -> Result<i64>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        let restored = sqlx::query_file!("sql/files/restore_by_folder.sql", folder)
+            .fetch_all(executor)
+            .await?;
+
+        Ok(restored.len() as i64)
+    }
 }
 
-#[allow(dead_code)]
+#[cfg(test)]
 pub struct FileWithVersionRepoFake {}
 
-#[allow(dead_code)]
+#[cfg(test)]
+use std::collections::HashMap;
+#[cfg(test)]
+use std::sync::Mutex;
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref FILES: Mutex<HashMap<Uuid, File>> = Mutex::new(HashMap::new());
+}
+
 // Fake implementation for tests. If you want integration tests that exercise the database,
 // see https://doc.rust-lang.org/rust-by-example/testing/integration_testing.html.
+#[cfg(test)]
 impl FileWithVersionRepoFake {
-    pub async fn create(args: CreateFileArgs<'_>, _pool: &PgPool) -> Result<FileWithVersion> {
-        let file = FileWithVersion {
-            id: Uuid::new_v4(),
-            title: args.title.into(),
-            description: args.description.into(),
-            folder: args.folder_id,
-            file_name: args.file_name.into(),
-            file_type: args.file_type.into(),
-            blob_storage_path: args.blob_storage_path.into(),
-            version: Uuid::new_v4(),
-            version_number: 1,
+    pub async fn create(args: CreateFileArgs<'_>, pool: &PgPool) -> Result<FileWithVersion> {
+        let file_id = Uuid::new_v4();
+        let file_version = super::FileVersionRepo::create(
+            Uuid::new_v4(),
+            args.folder_id,
+            file_id,
+            args.title,
+            args.description,
+            args.file_name,
+            args.file_type,
+            args.blob_storage_path,
+            args.user_id,
+            1,
+            "",
+            super::ScanStatus::Pending,
+            Some(args.sha256),
+            Some(args.file_size),
+            args.page_count,
+            pool,
+        )
+        .await?;
+        let file = File {
+            id: file_id,
             created_at: Utc::now(),
-            modified_at: Utc::now(),
+            created_by: args.user_id,
             deleted_at: None,
+            deleted_by: None,
+            latest_version: file_version.id,
+            deleted_with: None,
         };
-        Ok(file)
+        let mut files = FILES.lock().unwrap();
+        files.insert(file.id, file.clone());
+        Ok((file, file_version).into())
     }
 
     pub async fn create_version(
         args: CreateFileVersionArgs<'_>,
-        _pool: &PgPool,
+        pool: &PgPool,
     ) -> Result<FileWithVersion> {
-        let file = FileWithVersion {
-            id: args.file_id,
-            title: args.title.into(),
-            description: args.description.into(),
-            folder: args.folder_id,
-            file_name: args.file_name.into(),
-            file_type: args.file_type.into(),
-            blob_storage_path: args.blob_storage_path.into(),
-            version: Uuid::new_v4(),
-            version_number: args.version_number,
-            created_at: Utc::now(),
-            modified_at: Utc::now(),
-            deleted_at: None,
+        let current_file = FILES.lock().unwrap().get(&args.file_id).cloned();
+        if let Some(current_file) = &current_file {
+            if current_file.latest_version != args.latest_version {
+                anyhow::bail!(
+                    "no rows returned by a query that expected to return at least one row"
+                );
+            }
+        }
+
+        let file_version = super::FileVersionRepo::create(
+            Uuid::new_v4(),
+            args.folder_id,
+            args.file_id,
+            args.title,
+            args.description,
+            args.file_name,
+            args.file_type,
+            args.blob_storage_path,
+            args.user_id,
+            args.version_number,
+            "",
+            args.scan_status,
+            args.sha256,
+            args.file_size,
+            args.page_count,
+            pool,
+        )
+        .await?;
+        let file = File {
+            latest_version: file_version.id,
+            ..current_file.unwrap_or(File {
+                id: args.file_id,
+                created_at: Utc::now(),
+                created_by: args.user_id,
+                deleted_at: None,
+                deleted_by: None,
+                latest_version: file_version.id,
+                deleted_with: None,
+            })
         };
-        Ok(file)
+        let mut files = FILES.lock().unwrap();
+        files.insert(file.id, file.clone());
+        Ok((file, file_version).into())
     }
 
-    pub async fn find_by_folder(_folder: Uuid, _pool: &PgPool) -> Result<Vec<FileWithVersion>> {
-        Ok(vec![])
+    async fn all_in_folder(folder: Uuid, pool: &PgPool) -> Result<Vec<FileWithVersion>> {
+        let files: Vec<File> = FILES
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|file| file.deleted_at.is_none())
+            .cloned()
+            .collect();
+        let mut files_in_folder = Vec::new();
+        for file in files {
+            let file_version =
+                super::FileVersionRepo::find_by_id(file.latest_version, pool).await?;
+            if file_version.folder == folder {
+                files_in_folder.push((file, file_version).into());
+            }
+        }
+        files_in_folder.sort_by(|a: &FileWithVersion, b| (&a.title, a.id).cmp(&(&b.title, b.id)));
+        Ok(files_in_folder)
     }
 
-    pub async fn find_by_id(id: Uuid, _pool: &PgPool) -> Result<FileWithVersion> {
+    pub async fn find_by_folder(
+        folder: Uuid,
+        page: super::PageArgs<'_>,
+        pool: &PgPool,
+    ) -> Result<Vec<FileWithVersion>> {
+        let files = Self::all_in_folder(folder, pool)
+            .await?
+            .into_iter()
+            .filter(|file| match (page.after_sort_key, page.after_id) {
+                (Some(title), Some(id)) => (file.title.as_str(), file.id) > (title, id),
+                _ => true,
+            })
+            .take(page.limit as usize)
+            .collect();
+        Ok(files)
+    }
+
+    pub async fn count_by_folder(folder: Uuid, pool: &PgPool) -> Result<i64> {
+        Ok(Self::all_in_folder(folder, pool).await?.len() as i64)
+    }
+
+    pub async fn find_by_id(id: Uuid, pool: &PgPool) -> Result<FileWithVersion> {
+        let file = FILES.lock().unwrap().get(&id).cloned();
+        if let Some(file) = file {
+            let file_version =
+                super::FileVersionRepo::find_by_id(file.latest_version, pool).await?;
+            return Ok((file, file_version).into());
+        }
+
         let file = FileWithVersion {
             id,
             title: "fake file".into(),
@@ -267,11 +567,94 @@ impl FileWithVersionRepoFake {
             created_at: Utc::now(),
             modified_at: Utc::now(),
             deleted_at: None,
+            scan_status: super::ScanStatus::Clean.as_str().into(),
+            sha256: None,
+            file_size: None,
+            page_count: None,
         };
         Ok(file)
     }
 
-    pub async fn delete(id: Uuid, _deleted_by: Uuid, _pool: &PgPool) -> Result<FileWithVersion> {
+    pub async fn find_by_id_for_update(
+        id: Uuid,
+        tx: &mut super::Transaction,
+    ) -> Result<FileWithVersion> {
+        Self::find_by_id(id, &tx.pool).await
+    }
+
+    pub async fn find_duplicates(
+        workspace: Uuid,
+        sha256: &str,
+        file: Uuid,
+        workspace_member: bool,
+        pool: &PgPool,
+    ) -> Result<Vec<FileWithVersion>> {
+        let files: Vec<File> = FILES
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|f| f.deleted_at.is_none() && f.id != file)
+            .cloned()
+            .collect();
+        let mut duplicates = Vec::new();
+        for file in files {
+            let file_version =
+                super::FileVersionRepo::find_by_id(file.latest_version, pool).await?;
+            if file_version.sha256.as_deref() != Some(sha256) {
+                continue;
+            }
+            let folder = super::FolderRepo::find_by_id_if_exists(file_version.folder, pool).await?;
+            if folder.map_or(false, |folder| {
+                folder.workspace == workspace
+                    && (workspace_member || folder.role_required != "WORKSPACE_MEMBER")
+            }) {
+                duplicates.push((file, file_version).into());
+            }
+        }
+        duplicates.sort_by(|a: &FileWithVersion, b| (&a.title, a.id).cmp(&(&b.title, b.id)));
+        Ok(duplicates)
+    }
+
+    pub async fn totals_by_workspace(workspace: Uuid, pool: &PgPool) -> Result<FileTotals> {
+        let files: Vec<File> = FILES.lock().unwrap().values().cloned().collect();
+        let mut totals = FileTotals {
+            file_count: 0,
+            total_file_size: 0,
+            stored_size: 0,
+        };
+        let mut blobs = std::collections::HashSet::new();
+        for file in files {
+            for file_version in super::FileVersionRepo::find_by_file_id(file.id, pool).await? {
+                let folder =
+                    super::FolderRepo::find_by_id_if_exists(file_version.folder, pool).await?;
+                if folder.map_or(true, |folder| folder.workspace != workspace) {
+                    continue;
+                }
+                let file_size = file_version.file_size.unwrap_or(0);
+                if file_version.id == file.latest_version && file.deleted_at.is_none() {
+                    totals.file_count += 1;
+                    totals.total_file_size += file_size;
+                }
+                if blobs.insert(file_version.blob_storage_path) {
+                    totals.stored_size += file_size;
+                }
+            }
+        }
+        Ok(totals)
+    }
+
+    pub async fn delete(id: Uuid, deleted_by: Uuid, pool: &PgPool) -> Result<FileWithVersion> {
+        let file = FILES.lock().unwrap().get_mut(&id).map(|file| {
+            file.deleted_at = Some(Utc::now());
+            file.deleted_by = Some(deleted_by);
+            file.clone()
+        });
+        if let Some(file) = file {
+            let file_version =
+                super::FileVersionRepo::find_by_id(file.latest_version, pool).await?;
+            return Ok((file, file_version).into());
+        }
+
         let file = FileWithVersion {
             id,
             title: "fake file".into(),
@@ -285,7 +668,156 @@ impl FileWithVersionRepoFake {
             created_at: Utc::now(),
             modified_at: Utc::now(),
             deleted_at: Some(Utc::now()),
+            scan_status: super::ScanStatus::Clean.as_str().into(),
+            sha256: None,
+            file_size: None,
+            page_count: None,
         };
         Ok(file)
     }
+
+    pub async fn find_deleted_by_workspace(
+        workspace: Uuid,
+        page: super::PageArgs<'_>,
+        pool: &PgPool,
+    ) -> Result<Vec<FileWithVersion>> {
+        let after = match (page.after_sort_key, page.after_id) {
+            (Some(deleted_at), Some(id)) => Some((
+                DateTime::parse_from_rfc3339(deleted_at)?.with_timezone(&Utc),
+                id,
+            )),
+            _ => None,
+        };
+        let files = Self::all_deleted_in_workspace(workspace, pool)
+            .await?
+            .into_iter()
+            .filter(|file| after.map_or(true, |after| (file.deleted_at.unwrap(), file.id) < after))
+            .take(page.limit as usize)
+            .collect();
+        Ok(files)
+    }
+
+    pub async fn count_deleted_by_workspace(workspace: Uuid, pool: &PgPool) -> Result<i64> {
+        Ok(Self::all_deleted_in_workspace(workspace, pool).await?.len() as i64)
+    }
+
+    async fn all_deleted_in_workspace(
+        workspace: Uuid,
+        pool: &PgPool,
+    ) -> Result<Vec<FileWithVersion>> {
+        let files: Vec<File> = FILES
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|file| file.deleted_at.is_some() && file.deleted_with.is_none())
+            .cloned()
+            .collect();
+        let mut deleted_files = Vec::new();
+        for file in files {
+            let file_version =
+                super::FileVersionRepo::find_by_id(file.latest_version, pool).await?;
+            let folder = super::FolderRepo::find_by_id_if_exists(file_version.folder, pool).await?;
+            if folder.map_or(false, |folder| folder.workspace == workspace) {
+                deleted_files.push((file, file_version).into());
+            }
+        }
+        deleted_files
+            .sort_by(|a: &FileWithVersion, b| (b.deleted_at, b.id).cmp(&(a.deleted_at, a.id)));
+        Ok(deleted_files)
+    }
+
+    pub async fn find_deleted_by_id(id: Uuid, pool: &PgPool) -> Result<Option<FileWithVersion>> {
+        let file = FILES
+            .lock()
+            .unwrap()
+            .get(&id)
+            .filter(|file| file.deleted_at.is_some())
+            .cloned();
+        match file {
+            Some(file) => {
+                let file_version =
+                    super::FileVersionRepo::find_by_id(file.latest_version, pool).await?;
+                Ok(Some((file, file_version).into()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn restore(id: Uuid, pool: &PgPool) -> Result<FileWithVersion> {
+        let file = FILES
+            .lock()
+            .unwrap()
+            .get_mut(&id)
+            .filter(|file| file.deleted_at.is_some())
+            .map(|file| {
+                file.deleted_at = None;
+                file.deleted_by = None;
+                file.deleted_with = None;
+                file.clone()
+            })
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "no rows returned by a query that expected to return at least one row"
+                )
+            })?;
+        let file_version = super::FileVersionRepo::find_by_id(file.latest_version, pool).await?;
+        Ok((file, file_version).into())
+    }
+
+    pub async fn delete_by_folder(folder: Uuid, deleted_by: Uuid, pool: &PgPool) -> Result<i64> {
+        let folders = super::FolderRepo::undeleted_tree(folder);
+        let files: Vec<File> = FILES
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|file| file.deleted_at.is_none())
+            .cloned()
+            .collect();
+        let mut deleted = vec![];
+        for file in files {
+            let file_version =
+                super::FileVersionRepo::find_by_id(file.latest_version, pool).await?;
+            if folders.contains(&file_version.folder) {
+                deleted.push(file.id);
+            }
+        }
+
+        let mut files = FILES.lock().unwrap();
+        let deleted_at = Utc::now();
+        for id in &deleted {
+            if let Some(file) = files.get_mut(id) {
+                file.deleted_at = Some(deleted_at);
+                file.deleted_by = Some(deleted_by);
+                file.deleted_with = Some(folder);
+            }
+        }
+        Ok(deleted.len() as i64)
+    }
+
+    pub async fn restore_by_folder(folder: Uuid, _pool: &PgPool) -> Result<i64> {
+        let mut files = FILES.lock().unwrap();
+        let mut restored = 0;
+        for file in files
+            .values_mut()
+            .filter(|file| file.deleted_with == Some(folder))
+        {
+            file.deleted_at = None;
+            file.deleted_by = None;
+            file.deleted_with = None;
+            restored += 1;
+        }
+        Ok(restored)
+    }
+
+    pub async fn move_to_folder(
+        files: &[Uuid],
+        folder: Uuid,
+        tx: &mut super::Transaction,
+    ) -> Result<()> {
+        for file in files {
+            super::FileVersionRepo::move_to_folder(*file, folder, &tx.pool).await?;
+        }
+
+        Ok(())
+    }
 }
@@ -2,6 +2,7 @@
 #![allow(clippy::suspicious_else_formatting)]
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use sqlx::{types::Uuid, PgPool};
 
 #[derive(Clone)]
@@ -11,6 +12,12 @@ pub struct Folder {
     pub description: String,
     pub role_required: String,
     pub workspace: Uuid,
+    /// The folder that this folder is in, or `None` if it is at the top level of the workspace
+    pub parent: Option<Uuid>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// The folder whose deletion also deleted this folder. A folder that was deleted directly is
+    /// deleted with itself.
+    pub deleted_with: Option<Uuid>,
 }
 
 #[cfg_attr(test, allow(dead_code))]
@@ -23,6 +30,7 @@ impl FolderRepo {
         description: &str,
         role_required: &str,
         workspace: Uuid,
+        parent: Option<Uuid>,
         pool: &PgPool,
     ) -> Result<Folder> {
         let folder = sqlx::query_file_as!(
@@ -32,6 +40,7 @@ impl FolderRepo {
             description,
             role_required,
             workspace,
+            parent,
         )
         .fetch_one(pool)
         .await?;
@@ -39,14 +48,49 @@ impl FolderRepo {
         Ok(folder)
     }
 
-    pub async fn find_by_workspace(workspace: Uuid, pool: &PgPool) -> Result<Vec<Folder>> {
-        let folders = sqlx::query_file_as!(Folder, "sql/folders/find_by_workspace.sql", workspace)
-            .fetch_all(pool)
-            .await?;
+    /// Finds the folders in `parent`, or the top-level folders of the workspace if `parent` is
+    /// `None`.
+    pub async fn find_by_workspace(
+        workspace: Uuid,
+        parent: Option<Uuid>,
+        include_workspace_member_folders: bool,
+        page: super::PageArgs<'_>,
+        pool: &PgPool,
+    ) -> Result<Vec<Folder>> {
+        let folders = sqlx::query_file_as!(
+            Folder,
+            "sql/folders/find_by_workspace.sql",
+            workspace,
+            parent,
+            include_workspace_member_folders,
+            page.after_sort_key,
+            page.after_id,
+            page.limit
+        )
+        .fetch_all(pool)
+        .await?;
 
         Ok(folders)
     }
 
+    pub async fn count_by_workspace(
+        workspace: Uuid,
+        parent: Option<Uuid>,
+        include_workspace_member_folders: bool,
+        pool: &PgPool,
+    ) -> Result<i64> {
+        let count = sqlx::query_file!(
+            "sql/folders/count_by_workspace.sql",
+            workspace,
+            parent,
+            include_workspace_member_folders
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count.count.unwrap_or(0))
+    }
+
     pub async fn find_by_id(id: Uuid, pool: &PgPool) -> Result<Folder> {
         let folder = sqlx::query_file_as!(Folder, "sql/folders/find_by_id.sql", id)
             .fetch_one(pool)
@@ -55,6 +99,34 @@ impl FolderRepo {
         Ok(folder)
     }
 
+    pub async fn find_by_id_if_exists(id: Uuid, pool: &PgPool) -> Result<Option<Folder>> {
+        let folder = sqlx::query_file_as!(Folder, "sql/folders/find_by_id.sql", id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(folder)
+    }
+
+    /// Finds the folder and locks it until the transaction ends, so that it cannot be changed,
+    /// moved or deleted in the meantime.
+    pub async fn find_by_id_for_update(id: Uuid, tx: &mut super::Transaction) -> Result<Folder> {
+        let folder = sqlx::query_file_as!(Folder, "sql/folders/find_by_id_for_update.sql", id)
+            .fetch_one(tx)
+            .await?;
+
+        Ok(folder)
+    }
+
+    /// Returns the folders that this folder is in, starting at the top level of the workspace.
+    pub async fn ancestors(id: Uuid, pool: &PgPool) -> Result<Vec<Folder>> {
+        let folders = sqlx::query_file_as!(Folder, "sql/folders/ancestors.sql", id)
+            .fetch_all(pool)
+            .await?;
+
+        Ok(folders)
+    }
+
+    /// Updates the folder and, if it now requires workspace membership, all of the folders in it.
     pub async fn update(
         id: Uuid,
         title: &str,
@@ -62,6 +134,7 @@ impl FolderRepo {
         role_required: &str,
         pool: &PgPool,
     ) -> Result<Folder> {
+        let mut tx = pool.begin().await?;
         let folder = sqlx::query_file_as!(
             Folder,
             "sql/folders/update.sql",
@@ -70,24 +143,132 @@ impl FolderRepo {
             description,
             role_required,
         )
-        .fetch_one(pool)
+        .fetch_one(&mut tx)
         .await?;
 
+        if folder.role_required == "WORKSPACE_MEMBER" {
+            sqlx::query_file!("sql/folders/restrict_descendants.sql", id)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+
         Ok(folder)
     }
 
-    pub async fn delete(id: Uuid, pool: &PgPool) -> Result<Folder> {
-        let folder = sqlx::query_file_as!(Folder, "sql/folders/delete.sql", id)
-            .fetch_one(pool)
+    /// Moves the folder into `parent`, or to the top level of the workspace if `parent` is
+    /// `None`, and gives it the role it requires there. If it now requires workspace membership,
+    /// so do all of the folders in it.
+    pub async fn move_to(
+        id: Uuid,
+        parent: Option<Uuid>,
+        role_required: &str,
+        pool: &PgPool,
+    ) -> Result<Folder> {
+        let mut tx = pool.begin().await?;
+        let folder =
+            sqlx::query_file_as!(Folder, "sql/folders/move.sql", id, parent, role_required)
+                .fetch_one(&mut tx)
+                .await?;
+
+        if folder.role_required == "WORKSPACE_MEMBER" {
+            sqlx::query_file!("sql/folders/restrict_descendants.sql", id)
+                .execute(&mut tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(folder)
+    }
+
+    /// Deletes the folder, and everything in it, in a way that can be undone with `restore`.
+    /// Returns the folder and the number of files that were deleted with it.
+    pub async fn delete(id: Uuid, deleted_by: Uuid, pool: &PgPool) -> Result<(Folder, i64)> {
+        let mut tx = pool.begin().await?;
+        let file_count =
+            super::files::FileWithVersionRepo::delete_by_folder(id, deleted_by, &mut tx).await?;
+        let folders = sqlx::query_file_as!(Folder, "sql/folders/delete.sql", id, deleted_by)
+            .fetch_all(&mut tx)
+            .await?;
+        let folder = folders
+            .into_iter()
+            .find(|folder| folder.id == id)
+            .ok_or_else(|| anyhow::anyhow!("folder {} not found", id))?;
+        tx.commit().await?;
+
+        Ok((folder, file_count))
+    }
+
+    /// Finds a folder that was deleted directly, rather than with the folder it was in.
+    pub async fn find_deleted_by_id(id: Uuid, pool: &PgPool) -> Result<Option<Folder>> {
+        let folder = sqlx::query_file_as!(Folder, "sql/folders/find_deleted_by_id.sql", id)
+            .fetch_optional(pool)
             .await?;
 
         Ok(folder)
     }
+
+    /// Finds the folders that were deleted directly from the workspace, most recently deleted
+    /// first. The sort key of the page is the RFC 3339 time that the last folder on the previous
+    /// page was deleted.
+    pub async fn find_deleted_by_workspace(
+        workspace: Uuid,
+        page: super::PageArgs<'_>,
+        pool: &PgPool,
+    ) -> Result<Vec<Folder>> {
+        let folders = sqlx::query_file_as!(
+            Folder,
+            "sql/folders/find_deleted_by_workspace.sql",
+            workspace,
+            page.after_sort_key,
+            page.after_id,
+            page.limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(folders)
+    }
+
+    pub async fn count_deleted_by_workspace(workspace: Uuid, pool: &PgPool) -> Result<i64> {
+        let count = sqlx::query_file!("sql/folders/count_deleted_by_workspace.sql", workspace)
+            .fetch_one(pool)
+            .await?;
+
+        Ok(count.count.unwrap_or(0))
+    }
+
+    /// Restores a deleted folder, and the folders and files that were deleted with it. Returns
+    /// the folder and the number of files that were restored.
+    pub async fn restore(id: Uuid, pool: &PgPool) -> Result<(Folder, i64)> {
+        let mut tx = pool.begin().await?;
+        let file_count = super::files::FileWithVersionRepo::restore_by_folder(id, &mut tx).await?;
+        let folders = sqlx::query_file_as!(Folder, "sql/folders/restore.sql", id)
+            .fetch_all(&mut tx)
+            .await?;
+        let folder = folders
+            .into_iter()
+            .find(|folder| folder.id == id)
+            .ok_or_else(|| anyhow::anyhow!("folder {} has not been deleted", id))?;
+        tx.commit().await?;
+
+        Ok((folder, file_count))
+    }
 }
 
 #[cfg(test)]
 pub struct FolderRepoFake {}
 
+#[cfg(test)]
+use std::collections::HashMap;
+#[cfg(test)]
+use std::sync::Mutex;
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref FOLDERS: Mutex<HashMap<Uuid, Folder>> = Mutex::new(HashMap::new());
+}
+
 #[cfg(test)]
 impl FolderRepoFake {
     pub async fn create(
@@ -95,6 +276,7 @@ impl FolderRepoFake {
         description: &str,
         role_required: &str,
         workspace: Uuid,
+        parent: Option<Uuid>,
         _pool: &PgPool,
     ) -> Result<Folder> {
         let folder = Folder {
@@ -103,25 +285,112 @@ impl FolderRepoFake {
             workspace,
             description: description.to_string(),
             role_required: role_required.to_string(),
+            parent,
+            deleted_at: None,
+            deleted_with: None,
         };
+        let mut folders = FOLDERS.lock().unwrap();
+        folders.insert(folder.id, folder.clone());
         Ok(folder)
     }
 
-    pub async fn find_by_workspace(_workspace: Uuid, _pool: &PgPool) -> Result<Vec<Folder>> {
-        Ok(Vec::new())
+    fn all_in_workspace(
+        workspace: Uuid,
+        parent: Option<Uuid>,
+        include_workspace_member_folders: bool,
+    ) -> Vec<Folder> {
+        let folders = FOLDERS.lock().unwrap();
+        let mut folders: Vec<Folder> = folders
+            .values()
+            .filter(|folder| folder.workspace == workspace && folder.parent == parent)
+            .filter(|folder| folder.deleted_at.is_none())
+            .filter(|folder| {
+                include_workspace_member_folders || folder.role_required != "WORKSPACE_MEMBER"
+            })
+            .cloned()
+            .collect();
+        folders.sort_by(|a, b| (&a.title, a.id).cmp(&(&b.title, b.id)));
+        folders
+    }
+
+    pub async fn find_by_workspace(
+        workspace: Uuid,
+        parent: Option<Uuid>,
+        include_workspace_member_folders: bool,
+        page: super::PageArgs<'_>,
+        _pool: &PgPool,
+    ) -> Result<Vec<Folder>> {
+        let folders = Self::all_in_workspace(workspace, parent, include_workspace_member_folders)
+            .into_iter()
+            .filter(|folder| match (page.after_sort_key, page.after_id) {
+                (Some(title), Some(id)) => (folder.title.as_str(), folder.id) > (title, id),
+                _ => true,
+            })
+            .take(page.limit as usize)
+            .collect();
+        Ok(folders)
+    }
+
+    pub async fn count_by_workspace(
+        workspace: Uuid,
+        parent: Option<Uuid>,
+        include_workspace_member_folders: bool,
+        _pool: &PgPool,
+    ) -> Result<i64> {
+        Ok(
+            Self::all_in_workspace(workspace, parent, include_workspace_member_folders).len()
+                as i64,
+        )
     }
 
     pub async fn find_by_id(id: Uuid, _pool: &PgPool) -> Result<Folder> {
+        let folders = FOLDERS.lock().unwrap();
+        if let Some(folder) = folders.get(&id) {
+            if folder.deleted_at.is_some() {
+                anyhow::bail!(
+                    "no rows returned by a query that expected to return at least one row"
+                );
+            }
+            return Ok(folder.clone());
+        }
+
         let folder = Folder {
             id,
             title: "fake folder".into(),
             workspace: Uuid::new_v4(),
             description: "fake folder for testing".into(),
             role_required: "PLATFORM_MEMBER".into(),
+            parent: None,
+            deleted_at: None,
+            deleted_with: None,
         };
         Ok(folder)
     }
 
+    pub async fn find_by_id_for_update(id: Uuid, tx: &mut super::Transaction) -> Result<Folder> {
+        Self::find_by_id(id, &tx.pool).await
+    }
+
+    pub async fn find_by_id_if_exists(id: Uuid, _pool: &PgPool) -> Result<Option<Folder>> {
+        Ok(FOLDERS
+            .lock()
+            .unwrap()
+            .get(&id)
+            .filter(|folder| folder.deleted_at.is_none())
+            .cloned())
+    }
+
+    pub async fn ancestors(id: Uuid, _pool: &PgPool) -> Result<Vec<Folder>> {
+        let folders = FOLDERS.lock().unwrap();
+        let mut ancestors = vec![];
+        let mut parent = folders.get(&id).and_then(|folder| folder.parent);
+        while let Some(folder) = parent.and_then(|id| folders.get(&id)) {
+            ancestors.insert(0, folder.clone());
+            parent = folder.parent;
+        }
+        Ok(ancestors)
+    }
+
     pub async fn update(
         id: Uuid,
         title: &str,
@@ -129,24 +398,179 @@ impl FolderRepoFake {
         role_required: &str,
         _pool: &PgPool,
     ) -> Result<Folder> {
+        let mut folders = FOLDERS.lock().unwrap();
+        let (workspace, parent) = folders
+            .get(&id)
+            .map(|folder| (folder.workspace, folder.parent))
+            .unwrap_or_else(|| (Uuid::new_v4(), None));
         let folder = Folder {
             id,
             title: title.to_string(),
-            workspace: Uuid::new_v4(),
+            workspace,
             description: description.to_string(),
             role_required: role_required.to_string(),
+            parent,
+            deleted_at: None,
+            deleted_with: None,
         };
+        if folders.contains_key(&id) {
+            folders.insert(id, folder.clone());
+        }
+
+        if folder.role_required == "WORKSPACE_MEMBER" {
+            Self::restrict_descendants(&mut folders, id);
+        }
+        Ok(folder)
+    }
+
+    pub async fn move_to(
+        id: Uuid,
+        parent: Option<Uuid>,
+        role_required: &str,
+        _pool: &PgPool,
+    ) -> Result<Folder> {
+        let mut folders = FOLDERS.lock().unwrap();
+        let folder = folders
+            .get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("folder {} not found", id))?;
+        folder.parent = parent;
+        folder.role_required = role_required.to_string();
+        let folder = folder.clone();
+
+        if folder.role_required == "WORKSPACE_MEMBER" {
+            Self::restrict_descendants(&mut folders, id);
+        }
         Ok(folder)
     }
 
-    pub async fn delete(id: Uuid, _pool: &PgPool) -> Result<Folder> {
+    fn restrict_descendants(folders: &mut HashMap<Uuid, Folder>, id: Uuid) {
+        let mut restricted = vec![id];
+        while let Some(parent) = restricted.pop() {
+            for child in folders
+                .values_mut()
+                .filter(|folder| folder.parent == Some(parent))
+            {
+                child.role_required = "WORKSPACE_MEMBER".into();
+                restricted.push(child.id);
+            }
+        }
+    }
+
+    /// The ids of the folder and of the folders in it that have not been deleted.
+    pub fn undeleted_tree(id: Uuid) -> Vec<Uuid> {
+        let folders = FOLDERS.lock().unwrap();
+        let mut tree = vec![];
+        let mut parents = vec![id];
+        while let Some(parent) = parents.pop() {
+            if folders
+                .get(&parent)
+                .map_or(false, |folder| folder.deleted_at.is_some())
+            {
+                continue;
+            }
+            tree.push(parent);
+            parents.extend(
+                folders
+                    .values()
+                    .filter(|folder| folder.parent == Some(parent))
+                    .map(|folder| folder.id),
+            );
+        }
+        tree
+    }
+
+    pub async fn delete(id: Uuid, deleted_by: Uuid, pool: &PgPool) -> Result<(Folder, i64)> {
+        let file_count = super::FileWithVersionRepo::delete_by_folder(id, deleted_by, pool).await?;
+        let tree = Self::undeleted_tree(id);
+        let mut folders = FOLDERS.lock().unwrap();
+        let deleted_at = Utc::now();
+        for folder_id in tree {
+            if let Some(folder) = folders.get_mut(&folder_id) {
+                folder.deleted_at = Some(deleted_at);
+                folder.deleted_with = Some(id);
+            }
+        }
+        if let Some(folder) = folders.get(&id) {
+            return Ok((folder.clone(), file_count));
+        }
+
         let folder = Folder {
             id,
             title: "fake folder".into(),
             workspace: Uuid::new_v4(),
             description: "fake folder for testing".into(),
             role_required: "PLATFORM_MEMBER".into(),
+            parent: None,
+            deleted_at: Some(deleted_at),
+            deleted_with: Some(id),
         };
-        Ok(folder)
+        Ok((folder, file_count))
+    }
+
+    pub async fn find_deleted_by_id(id: Uuid, _pool: &PgPool) -> Result<Option<Folder>> {
+        Ok(FOLDERS
+            .lock()
+            .unwrap()
+            .get(&id)
+            .filter(|folder| folder.deleted_with == Some(folder.id))
+            .cloned())
+    }
+
+    fn all_deleted_in_workspace(workspace: Uuid) -> Vec<Folder> {
+        let folders = FOLDERS.lock().unwrap();
+        let mut folders: Vec<Folder> = folders
+            .values()
+            .filter(|folder| {
+                folder.workspace == workspace && folder.deleted_with == Some(folder.id)
+            })
+            .cloned()
+            .collect();
+        folders.sort_by(|a, b| (b.deleted_at, b.id).cmp(&(a.deleted_at, a.id)));
+        folders
+    }
+
+    pub async fn find_deleted_by_workspace(
+        workspace: Uuid,
+        page: super::PageArgs<'_>,
+        _pool: &PgPool,
+    ) -> Result<Vec<Folder>> {
+        let after = match (page.after_sort_key, page.after_id) {
+            (Some(deleted_at), Some(id)) => Some((
+                DateTime::parse_from_rfc3339(deleted_at)?.with_timezone(&Utc),
+                id,
+            )),
+            _ => None,
+        };
+        let folders = Self::all_deleted_in_workspace(workspace)
+            .into_iter()
+            .filter(|folder| {
+                after.map_or(true, |after| {
+                    (folder.deleted_at.unwrap(), folder.id) < after
+                })
+            })
+            .take(page.limit as usize)
+            .collect();
+        Ok(folders)
+    }
+
+    pub async fn count_deleted_by_workspace(workspace: Uuid, _pool: &PgPool) -> Result<i64> {
+        Ok(Self::all_deleted_in_workspace(workspace).len() as i64)
+    }
+
+    pub async fn restore(id: Uuid, pool: &PgPool) -> Result<(Folder, i64)> {
+        let file_count = super::FileWithVersionRepo::restore_by_folder(id, pool).await?;
+        let mut folders = FOLDERS.lock().unwrap();
+        for folder in folders
+            .values_mut()
+            .filter(|folder| folder.deleted_with == Some(id))
+        {
+            folder.deleted_at = None;
+            folder.deleted_with = None;
+        }
+        let folder = folders
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("folder {} has not been deleted", id))?;
+        Ok((folder, file_count))
     }
 }
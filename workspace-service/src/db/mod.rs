@@ -1,18 +1,28 @@
 mod file_versions;
 mod files;
 mod folders;
+mod purge;
+mod search;
 mod teams;
+mod uploads;
 mod users;
 mod workspaces;
 
-pub use file_versions::*;
+#[cfg(not(test))]
+pub use file_versions::FileVersionRepo;
+#[cfg(test)]
+pub use file_versions::FileVersionRepoFake as FileVersionRepo;
+pub use file_versions::{FileVersion, ScanStatus, ScannedVersion};
+
 pub use workspaces::Role;
 
 #[cfg(not(test))]
 pub use files::FileWithVersionRepo;
 #[cfg(test)]
 pub use files::FileWithVersionRepoFake as FileWithVersionRepo;
-pub use files::{CreateFileArgs, CreateFileVersionArgs, File, FileRepo, FileWithVersion};
+pub use files::{
+    CreateFileArgs, CreateFileVersionArgs, File, FileRepo, FileTotals, FileWithVersion,
+};
 
 pub use folders::Folder;
 #[cfg(not(test))]
@@ -20,12 +30,26 @@ pub use folders::FolderRepo;
 #[cfg(test)]
 pub use folders::FolderRepoFake as FolderRepo;
 
+pub use purge::{PurgeRepo, PurgedContent};
+
+#[cfg(not(test))]
+pub use search::SearchRepo;
+#[cfg(test)]
+pub use search::SearchRepoFake as SearchRepo;
+pub use search::{SearchArgs, SearchHit, SearchHitKind};
+
 pub use teams::Team;
 #[cfg(not(test))]
 pub use teams::TeamRepo;
 #[cfg(test)]
 pub use teams::TeamRepoFake as TeamRepo;
 
+#[cfg(not(test))]
+pub use uploads::UploadRepo;
+#[cfg(test)]
+pub use uploads::UploadRepoFake as UploadRepo;
+pub use uploads::{CreateUploadArgs, Upload};
+
 pub use users::User;
 #[cfg(not(test))]
 pub use users::UserRepo;
@@ -39,7 +63,48 @@ pub use workspaces::WorkspaceRepo;
 pub use workspaces::WorkspaceRepoFake as WorkspaceRepo;
 
 use anyhow::Result;
-use sqlx::{Executor, Postgres};
+use sqlx::{types::Uuid, Executor, PgPool, Postgres};
+
+/// A transaction, for repo methods that must see and change the database in one step. Nothing
+/// it changes is kept unless it is committed.
+#[cfg(not(test))]
+pub type Transaction = sqlx::Transaction<'static, Postgres>;
+#[cfg(test)]
+pub type Transaction = TransactionFake;
+
+#[cfg(not(test))]
+pub async fn begin(pool: &PgPool) -> Result<Transaction> {
+    Ok(pool.begin().await?)
+}
+
+/// The fake repos do not have transactions, so they use the pool of a fake transaction instead.
+#[cfg(test)]
+pub struct TransactionFake {
+    pool: PgPool,
+}
+
+#[cfg(test)]
+pub async fn begin(pool: &PgPool) -> Result<Transaction> {
+    Ok(TransactionFake { pool: pool.clone() })
+}
+
+#[cfg(test)]
+impl TransactionFake {
+    pub async fn commit(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Selects a page of a list ordered by a sort key and then by id, for keyset pagination.
+#[derive(Clone, Copy)]
+pub struct PageArgs<'a> {
+    /// The sort key of the last item on the previous page
+    pub after_sort_key: Option<&'a str>,
+    /// The id of the last item on the previous page
+    pub after_id: Option<Uuid>,
+    /// The maximum number of items to return
+    pub limit: i64,
+}
 
 async fn defer_all_constraints<'c, E>(executor: E) -> Result<()>
 where
@@ -0,0 +1,52 @@
+// sqlx::query_file_as!() causes spurious errors with this lint enabled
+#![allow(clippy::suspicious_else_formatting)]
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{types::Uuid, PgPool};
+
+/// What was permanently deleted from the database.
+pub struct PurgedContent {
+    pub files: Vec<Uuid>,
+    pub folders: Vec<Uuid>,
+    /// The blobs that were only used by the purged files, which can now be deleted
+    pub blob_storage_paths: Vec<String>,
+}
+
+pub struct PurgeRepo {}
+
+impl PurgeRepo {
+    /// Permanently deletes the files and folders that were deleted before `deleted_before`, with
+    /// all of their versions. With `dry_run`, nothing is deleted, but the result is the same.
+    pub async fn purge_deleted(
+        deleted_before: DateTime<Utc>,
+        dry_run: bool,
+        pool: &PgPool,
+    ) -> Result<PurgedContent> {
+        let mut tx = pool.begin().await?;
+        super::defer_all_constraints(&mut tx).await?;
+        let blobs = sqlx::query_file!("sql/purge/delete_file_versions.sql", deleted_before)
+            .fetch_all(&mut tx)
+            .await?;
+        let files = sqlx::query_file!("sql/purge/delete_files.sql", deleted_before)
+            .fetch_all(&mut tx)
+            .await?;
+        let folders = sqlx::query_file!("sql/purge/delete_folders.sql", deleted_before)
+            .fetch_all(&mut tx)
+            .await?;
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+
+        Ok(PurgedContent {
+            files: files.into_iter().map(|file| file.id).collect(),
+            folders: folders.into_iter().map(|folder| folder.id).collect(),
+            blob_storage_paths: blobs
+                .into_iter()
+                .map(|blob| blob.blob_storage_path)
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,239 @@
+// sqlx::query_file_as!() causes spurious errors with this lint enabled
+#![allow(clippy::suspicious_else_formatting)]
+
+use anyhow::{anyhow, Result};
+use sqlx::{types::Uuid, PgPool};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SearchHitKind {
+    Workspace,
+    Folder,
+    File,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    pub id: Uuid,
+    pub rank: f32,
+    /// The matching text, HTML-escaped, with matched words wrapped in `<mark>` and `</mark>`
+    pub snippet: String,
+}
+
+/// `ts_headline()` wraps matched words in these instead of HTML, so that the text can be escaped
+/// before it is highlighted.
+const START_SEL: char = '\u{2}';
+const STOP_SEL: char = '\u{3}';
+
+/// HTML-escapes a snippet from `ts_headline()` and wraps the matched words in `<mark>`.
+fn highlight(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len());
+    let mut marking = false;
+    for c in snippet.chars() {
+        match c {
+            START_SEL if !marking => {
+                html.push_str("<mark>");
+                marking = true;
+            }
+            STOP_SEL if marking => {
+                html.push_str("</mark>");
+                marking = false;
+            }
+            START_SEL | STOP_SEL => {}
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    if marking {
+        html.push_str("</mark>");
+    }
+    html
+}
+
+pub struct SearchArgs<'a> {
+    /// Words that must all match, e.g. `annual report`. Punctuation is ignored.
+    pub query: &'a str,
+    pub workspace: Option<Uuid>,
+    pub include_workspaces: bool,
+    pub include_folders: bool,
+    pub include_files: bool,
+    pub user_id: Uuid,
+    /// Whether the user can see folders that require workspace membership in every workspace
+    pub is_platform_admin: bool,
+    pub limit: i64,
+}
+
+// Every column of a UNION is nullable as far as sqlx can tell.
+#[cfg_attr(test, allow(dead_code))]
+struct SearchRow {
+    kind: Option<String>,
+    id: Option<Uuid>,
+    rank: Option<f32>,
+    snippet: Option<String>,
+}
+
+#[cfg_attr(test, allow(dead_code))]
+impl SearchRow {
+    fn into_hit(self) -> Result<SearchHit> {
+        let kind = match self.kind.as_deref() {
+            Some("WORKSPACE") => SearchHitKind::Workspace,
+            Some("FOLDER") => SearchHitKind::Folder,
+            Some("FILE") => SearchHitKind::File,
+            other => return Err(anyhow!("unexpected search hit kind {:?}", other)),
+        };
+        Ok(SearchHit {
+            kind,
+            id: self.id.ok_or_else(|| anyhow!("search hit has no id"))?,
+            rank: self.rank.unwrap_or_default(),
+            snippet: highlight(&self.snippet.unwrap_or_default()),
+        })
+    }
+}
+
+#[cfg_attr(test, allow(dead_code))]
+pub struct SearchRepo {}
+
+#[cfg_attr(test, allow(dead_code))]
+impl SearchRepo {
+    /// Finds workspaces, folders and the latest versions of files that match the query, best
+    /// matches first. Folders that require workspace membership, and the files in them, are only
+    /// returned to members of the workspace.
+    pub async fn search(args: SearchArgs<'_>, pool: &PgPool) -> Result<Vec<SearchHit>> {
+        let rows = sqlx::query_file_as!(
+            SearchRow,
+            "sql/search/search.sql",
+            args.query,
+            args.workspace,
+            args.include_workspaces,
+            args.include_folders,
+            args.include_files,
+            args.user_id,
+            args.is_platform_admin,
+            args.limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        rows.into_iter().map(SearchRow::into_hit).collect()
+    }
+}
+
+#[cfg(test)]
+pub struct SearchRepoFake {}
+
+#[cfg(test)]
+impl SearchRepoFake {
+    /// Matches case-insensitively on every word of the query, without the stemming of Postgres
+    /// full-text search.
+    pub async fn search(args: SearchArgs<'_>, pool: &PgPool) -> Result<Vec<SearchHit>> {
+        let words: Vec<String> = args
+            .query
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        let hit = |kind, id, text: String| {
+            let lowercase = text.to_lowercase();
+            if !words.is_empty() && words.iter().all(|word| lowercase.contains(word)) {
+                Some(SearchHit {
+                    kind,
+                    id,
+                    rank: 1.0,
+                    snippet: highlight(&text),
+                })
+            } else {
+                None
+            }
+        };
+        let everything = super::PageArgs {
+            after_sort_key: None,
+            after_id: None,
+            limit: i64::MAX,
+        };
+
+        let mut hits = vec![];
+        for workspace in super::WorkspaceRepo::find_all(everything, pool).await? {
+            if args.workspace.map_or(false, |id| id != workspace.id) {
+                continue;
+            }
+            if args.include_workspaces {
+                hits.extend(hit(
+                    SearchHitKind::Workspace,
+                    workspace.id,
+                    format!("{} {}", workspace.title, workspace.description),
+                ));
+            }
+
+            let include_workspace_member_folders = args.is_platform_admin
+                || super::TeamRepo::is_member(workspace.members, args.user_id, pool).await?;
+            let mut parents = vec![None];
+            while let Some(parent) = parents.pop() {
+                let folders = super::FolderRepo::find_by_workspace(
+                    workspace.id,
+                    parent,
+                    include_workspace_member_folders,
+                    everything,
+                    pool,
+                )
+                .await?;
+                parents.extend(folders.iter().map(|folder| Some(folder.id)));
+                for folder in folders {
+                    if args.include_folders {
+                        hits.extend(hit(
+                            SearchHitKind::Folder,
+                            folder.id,
+                            format!("{} {}", folder.title, folder.description),
+                        ));
+                    }
+                    if args.include_files {
+                        for file in
+                            super::FileWithVersionRepo::find_by_folder(folder.id, everything, pool)
+                                .await?
+                        {
+                            hits.extend(hit(
+                                SearchHitKind::File,
+                                file.id,
+                                format!("{} {} {}", file.title, file.description, file.file_name),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        hits.truncate(args.limit as usize);
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn highlight_wraps_matched_words_in_mark() {
+        assert_eq!(
+            highlight("the \u{2}annual\u{3} \u{2}report\u{3}"),
+            "the <mark>annual</mark> <mark>report</mark>"
+        );
+    }
+
+    #[test]
+    fn highlight_escapes_the_text() {
+        assert_eq!(
+            highlight("<script>alert('\u{2}report\u{3}' & \"x\")</script>"),
+            "&lt;script&gt;alert(&#39;<mark>report</mark>&#39; &amp; &quot;x&quot;)&lt;/script&gt;"
+        );
+    }
+
+    #[test]
+    fn highlight_balances_marks() {
+        assert_eq!(
+            highlight("\u{3}a \u{2}\u{2}b\u{3}\u{3} \u{2}c"),
+            "a <mark>b</mark> <mark>c</mark>"
+        );
+    }
+}
@@ -28,21 +28,45 @@ impl TeamRepo {
         Ok(group)
     }
 
-    pub async fn members<'c, E>(id: Uuid, executor: E) -> Result<Vec<User>>
+    pub async fn members<'c, E>(
+        id: Uuid,
+        page: crate::db::PageArgs<'_>,
+        executor: E,
+    ) -> Result<Vec<User>>
     where
         E: Executor<'c, Database = Postgres>,
     {
-        let users = sqlx::query_file_as!(User, "sql/teams/members.sql", id)
-            .fetch_all(executor)
-            .await
-            .context("get team members")?;
+        let users = sqlx::query_file_as!(
+            User,
+            "sql/teams/members.sql",
+            id,
+            page.after_sort_key,
+            page.after_id,
+            page.limit
+        )
+        .fetch_all(executor)
+        .await
+        .context("get team members")?;
 
         Ok(users)
     }
 
+    pub async fn count_members<'c, E>(id: Uuid, executor: E) -> Result<i64>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        let count = sqlx::query_file!("sql/teams/count_members.sql", id)
+            .fetch_one(executor)
+            .await
+            .context("count team members")?;
+
+        Ok(count.count.unwrap_or(0))
+    }
+
     pub async fn members_difference<'c, E>(
         team_a_id: Uuid,
         team_b_id: Uuid,
+        page: crate::db::PageArgs<'_>,
         executor: E,
     ) -> Result<Vec<User>>
     where
@@ -52,7 +76,10 @@ impl TeamRepo {
             User,
             "sql/teams/members_difference.sql",
             team_a_id,
-            team_b_id
+            team_b_id,
+            page.after_sort_key,
+            page.after_id,
+            page.limit
         )
         .fetch_all(executor)
         .await
@@ -61,6 +88,26 @@ impl TeamRepo {
         Ok(users)
     }
 
+    pub async fn count_members_difference<'c, E>(
+        team_a_id: Uuid,
+        team_b_id: Uuid,
+        executor: E,
+    ) -> Result<i64>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        let count = sqlx::query_file!(
+            "sql/teams/count_members_difference.sql",
+            team_a_id,
+            team_b_id
+        )
+        .fetch_one(executor)
+        .await
+        .context("count members of team A that aren't in team B")?;
+
+        Ok(count.count.unwrap_or(0))
+    }
+
     pub async fn is_member<'c, E>(team_id: Uuid, user_id: Uuid, executor: E) -> Result<bool>
     where
         E: Executor<'c, Database = Postgres>,
@@ -126,25 +173,88 @@ impl TeamRepoFake {
         Ok(team)
     }
 
-    pub async fn members<'c, E>(id: Uuid, executor: E) -> Result<Vec<User>>
+    async fn all_members(team_id: Uuid) -> Result<Vec<User>> {
+        let user_ids: Vec<Uuid> = TEAM_MEMBERS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(team, _)| *team == team_id)
+            .map(|(_, user)| *user)
+            .collect();
+        let mut users = Vec::new();
+        for user_id in user_ids {
+            if let Some(user) = crate::db::UserRepo::find_by_id(&user_id, ()).await? {
+                users.push(user);
+            }
+        }
+        users.sort_by(|a, b| (&a.name, a.id).cmp(&(&b.name, b.id)));
+        Ok(users)
+    }
+
+    fn page_of(users: Vec<User>, page: crate::db::PageArgs<'_>) -> Vec<User> {
+        users
+            .into_iter()
+            .filter(|user| match (page.after_sort_key, page.after_id) {
+                (Some(name), Some(id)) => (user.name.as_str(), user.id) > (name, id),
+                _ => true,
+            })
+            .take(page.limit as usize)
+            .collect()
+    }
+
+    pub async fn members<'c, E>(
+        id: Uuid,
+        page: crate::db::PageArgs<'_>,
+        _executor: E,
+    ) -> Result<Vec<User>>
     where
         E: Executor<'c, Database = Postgres>,
     {
-        if let Some(user) = crate::db::UserRepo::find_by_auth_id(&id, executor).await? {
-            Ok(vec![user])
-        } else {
-            Ok(vec![])
-        }
+        Ok(Self::page_of(Self::all_members(id).await?, page))
+    }
+
+    pub async fn count_members<'c, E>(id: Uuid, _executor: E) -> Result<i64>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        Ok(Self::all_members(id).await?.len() as i64)
+    }
+
+    async fn all_members_difference(team_a_id: Uuid, team_b_id: Uuid) -> Result<Vec<User>> {
+        let team_b_members = Self::all_members(team_b_id).await?;
+        Ok(Self::all_members(team_a_id)
+            .await?
+            .into_iter()
+            .filter(|user| !team_b_members.iter().any(|member| member.id == user.id))
+            .collect())
     }
+
     pub async fn members_difference<'c, E>(
-        _team_a_id: Uuid,
-        _team_b_id: Uuid,
+        team_a_id: Uuid,
+        team_b_id: Uuid,
+        page: crate::db::PageArgs<'_>,
         _executor: E,
     ) -> Result<Vec<User>>
     where
         E: Executor<'c, Database = Postgres>,
     {
-        todo!()
+        Ok(Self::page_of(
+            Self::all_members_difference(team_a_id, team_b_id).await?,
+            page,
+        ))
+    }
+
+    pub async fn count_members_difference<'c, E>(
+        team_a_id: Uuid,
+        team_b_id: Uuid,
+        _executor: E,
+    ) -> Result<i64>
+    where
+        E: Executor<'c, Database = Postgres>,
+    {
+        Ok(Self::all_members_difference(team_a_id, team_b_id)
+            .await?
+            .len() as i64)
     }
 
     pub async fn is_member<'c, E>(team_id: Uuid, user_id: Uuid, _executor: E) -> Result<bool>
@@ -0,0 +1,108 @@
+// sqlx::query_file_as!() causes spurious errors with this lint enabled
+#![allow(clippy::suspicious_else_formatting)]
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{types::Uuid, PgPool};
+
+/// A file that a user is going to upload to the blob with the same id.
+#[derive(Clone, Debug)]
+pub struct Upload {
+    pub id: Uuid,
+    pub file_name: String,
+    pub file_type: String,
+    pub file_size: i64,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct CreateUploadArgs<'a> {
+    pub id: Uuid,
+    pub file_name: &'a str,
+    pub file_type: &'a str,
+    pub file_size: i64,
+    pub created_by: Uuid,
+}
+
+#[cfg_attr(test, allow(dead_code))]
+pub struct UploadRepo {}
+
+#[cfg_attr(test, allow(dead_code))]
+impl UploadRepo {
+    pub async fn create(args: CreateUploadArgs<'_>, pool: &PgPool) -> Result<Upload> {
+        let upload = sqlx::query_file_as!(
+            Upload,
+            "sql/uploads/create.sql",
+            args.id,
+            args.file_name,
+            args.file_type,
+            args.file_size,
+            args.created_by
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(upload)
+    }
+
+    pub async fn find_by_id(id: Uuid, pool: &PgPool) -> Result<Option<Upload>> {
+        let upload = sqlx::query_file_as!(Upload, "sql/uploads/find_by_id.sql", id)
+            .fetch_optional(pool)
+            .await?;
+
+        Ok(upload)
+    }
+
+    /// Deletes the uploads that were created before `before` and returns how many there were.
+    pub async fn delete_created_before(before: DateTime<Utc>, pool: &PgPool) -> Result<u64> {
+        let result = sqlx::query_file!("sql/uploads/delete_created_before.sql", before)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+pub struct UploadRepoFake {}
+
+#[cfg(test)]
+use std::collections::HashMap;
+#[cfg(test)]
+use std::sync::Mutex;
+
+#[cfg(test)]
+lazy_static::lazy_static! {
+    static ref UPLOADS: Mutex<HashMap<Uuid, Upload>> = Mutex::new(HashMap::new());
+}
+
+// Fake implementation for tests. If you want integration tests that exercise the database,
+// see https://doc.rust-lang.org/rust-by-example/testing/integration_testing.html.
+#[cfg(test)]
+impl UploadRepoFake {
+    pub async fn create(args: CreateUploadArgs<'_>, _pool: &PgPool) -> Result<Upload> {
+        let upload = Upload {
+            id: args.id,
+            file_name: args.file_name.to_string(),
+            file_type: args.file_type.to_string(),
+            file_size: args.file_size,
+            created_by: args.created_by,
+            created_at: Utc::now(),
+        };
+        let mut uploads = UPLOADS.lock().unwrap();
+        uploads.insert(upload.id, upload.clone());
+        Ok(upload)
+    }
+
+    pub async fn find_by_id(id: Uuid, _pool: &PgPool) -> Result<Option<Upload>> {
+        let uploads = UPLOADS.lock().unwrap();
+        Ok(uploads.get(&id).cloned())
+    }
+
+    pub async fn delete_created_before(before: DateTime<Utc>, _pool: &PgPool) -> Result<u64> {
+        let mut uploads = UPLOADS.lock().unwrap();
+        let count = uploads.len();
+        uploads.retain(|_, upload| upload.created_at >= before);
+        Ok((count - uploads.len()) as u64)
+    }
+}
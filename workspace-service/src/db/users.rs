@@ -85,7 +85,7 @@ impl UserRepoFake {
         Ok(users.get(auth_id).cloned())
     }
 
-    pub async fn find_by_id(id: &Uuid, _pool: &PgPool) -> Result<Option<User>> {
+    pub async fn find_by_id(id: &Uuid, _pool: impl Sized) -> Result<Option<User>> {
         let users = USERS_BY_ID.lock().unwrap();
         Ok(users.get(id).cloned())
     }
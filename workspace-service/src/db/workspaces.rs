@@ -65,15 +65,29 @@ impl WorkspaceRepo {
         Ok(workspace)
     }
 
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<Workspace>> {
-        let workspaces = sqlx::query_file_as!(Workspace, "sql/workspaces/find_all.sql")
-            .fetch_all(pool)
-            .await
-            .context("find all workspaces")?;
+    pub async fn find_all(page: db::PageArgs<'_>, pool: &PgPool) -> Result<Vec<Workspace>> {
+        let workspaces = sqlx::query_file_as!(
+            Workspace,
+            "sql/workspaces/find_all.sql",
+            page.after_id,
+            page.limit
+        )
+        .fetch_all(pool)
+        .await
+        .context("find all workspaces")?;
 
         Ok(workspaces)
     }
 
+    pub async fn count_all(pool: &PgPool) -> Result<i64> {
+        let count = sqlx::query_file!("sql/workspaces/count_all.sql")
+            .fetch_one(pool)
+            .await
+            .context("count all workspaces")?;
+
+        Ok(count.count.unwrap_or(0))
+    }
+
     pub async fn find_by_id(id: Uuid, pool: &PgPool) -> Result<Workspace> {
         let workspace = sqlx::query_file_as!(Workspace, "sql/workspaces/find_by_id.sql", id)
             .fetch_one(pool)
@@ -201,8 +215,21 @@ impl WorkspaceRepoFake {
         Ok(workspace)
     }
 
-    pub async fn find_all(_pool: &PgPool) -> Result<Vec<Workspace>> {
-        Ok(vec![])
+    pub async fn find_all(page: db::PageArgs<'_>, _pool: &PgPool) -> Result<Vec<Workspace>> {
+        let teams = WORKSPACES.lock().unwrap();
+        let mut workspaces: Vec<Workspace> = teams
+            .values()
+            .filter(|workspace| page.after_id.map_or(true, |after| workspace.id > after))
+            .cloned()
+            .collect();
+        workspaces.sort_by_key(|workspace| workspace.id);
+        workspaces.truncate(page.limit as usize);
+        Ok(workspaces)
+    }
+
+    pub async fn count_all(_pool: &PgPool) -> Result<i64> {
+        let teams = WORKSPACES.lock().unwrap();
+        Ok(teams.len() as i64)
     }
 
     pub async fn find_by_id(id: Uuid, _pool: &PgPool) -> Result<Workspace> {
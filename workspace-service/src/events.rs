@@ -0,0 +1,85 @@
+use crate::config::{Config, EventPublisherKind, EventSchemaKind};
+use anyhow::{anyhow, Result};
+use fnhs_event_models::{
+    EventClient, EventGridPublisher, EventPublisher, EventSchema, KafkaRestPublisher, RetryPolicy,
+    ValidatingPublisher, WebhookPublisher,
+};
+use std::{sync::Arc, time::Duration};
+
+/// Creates the event client for the publishers selected with `--event-publishers`.
+///
+/// EventGrid is skipped if its topic is not configured, as for local development. If no
+/// publishers are left, events are not published and `/healthz` fails. With
+/// `--validate-events`, events are checked against the event schema before they are published.
+pub fn from_config(config: &Config) -> Result<EventClient> {
+    let mut publishers: Vec<Arc<dyn EventPublisher + Send + Sync>> = vec![];
+    for kind in &config.event_publishers {
+        match kind {
+            EventPublisherKind::EventGrid => {
+                if let (Some(topic_endpoint), Some(topic_key)) = (
+                    &config.eventgrid_topic_endpoint,
+                    &config.eventgrid_topic_key,
+                ) {
+                    publishers.push(Arc::new(
+                        EventGridPublisher::new(
+                            topic_endpoint.host_str().ok_or_else(|| {
+                                anyhow!("EVENTGRID_TOPIC_ENDPOINT does not contain host name")
+                            })?,
+                            topic_key.clone(),
+                        )
+                        .with_retry_policy(RetryPolicy {
+                            max_retries: config.eventgrid_max_retries,
+                            ..RetryPolicy::default()
+                        })
+                        .with_timeout(Duration::from_secs(config.eventgrid_timeout_seconds))
+                        .with_schema(schema(config.eventgrid_schema, config)),
+                    ));
+                }
+            }
+            EventPublisherKind::Webhook => publishers.push(Arc::new(
+                WebhookPublisher::new(
+                    config
+                        .webhook_url
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("WEBHOOK_URL is required"))?
+                        .to_string(),
+                    config
+                        .webhook_secret
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("WEBHOOK_SECRET is required"))?
+                        .as_bytes(),
+                )
+                .with_schema(schema(config.webhook_schema, config)),
+            )),
+            EventPublisherKind::KafkaRest => publishers.push(Arc::new(
+                KafkaRestPublisher::new(
+                    config
+                        .kafka_rest_proxy_url
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("KAFKA_REST_PROXY_URL is required"))?
+                        .as_str(),
+                    &config.kafka_topic,
+                )
+                .with_schema(schema(config.kafka_schema, config)),
+            )),
+        }
+    }
+
+    let event_client = EventClient::with_publishers(publishers);
+    if config.validate_events && event_client.is_configured() {
+        return Ok(EventClient::with_publishers(vec![Arc::new(
+            ValidatingPublisher::new(Arc::new(event_client)),
+        )]));
+    }
+
+    Ok(event_client)
+}
+
+fn schema(kind: EventSchemaKind, config: &Config) -> EventSchema {
+    match kind {
+        EventSchemaKind::EventGrid => EventSchema::EventGrid,
+        EventSchemaKind::CloudEvents => EventSchema::CloudEvents {
+            source: config.cloudevents_source.clone(),
+        },
+    }
+}
@@ -0,0 +1,200 @@
+use super::{
+    db,
+    folders::RoleRequired,
+    workspaces::{requesting_user_workspace_rights, WorkspaceMembership},
+    RequestingUser,
+};
+use async_graphql::{ErrorExtensions, FieldResult};
+use fnhs_event_models::EventClient;
+use sqlx::PgPool;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// What the requesting user is trying to do with the contents of a folder.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FolderAccess {
+    /// List, view or download files
+    Read,
+    /// Create, change or delete files
+    Write,
+}
+
+impl Display for FolderAccess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FolderAccess::Read => "read",
+                FolderAccess::Write => "write to",
+            }
+        )
+    }
+}
+
+/// Fails unless the requesting user has the specified access to the folder.
+///
+/// Any platform member can read a folder that only requires `PLATFORM_MEMBER`. Reading any other
+/// folder, or writing to any folder, requires membership of the folder's workspace. Platform
+/// admins can do anything.
+pub async fn require_folder_access(
+    folder: &db::Folder,
+    access: FolderAccess,
+    requesting_user: &RequestingUser,
+    pool: &PgPool,
+    event_client: &EventClient,
+) -> FieldResult<()> {
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+    let role_required = RoleRequired::from_str(&folder.role_required).map_err(|_| {
+        anyhow::anyhow!(
+            "folder {} has an unknown role_required {}",
+            folder.id,
+            folder.role_required
+        )
+    })?;
+
+    if access == FolderAccess::Read && role_required == RoleRequired::PlatformMember {
+        return Ok(());
+    }
+
+    let user_rights =
+        requesting_user_workspace_rights(folder.workspace, requesting_user, pool, event_client)
+            .await?;
+
+    if user_rights == WorkspaceMembership::NonMember {
+        return Err(anyhow::anyhow!(
+            "user with auth_id {} does not have permission to {} folder {}",
+            user.auth_id,
+            access,
+            folder.id,
+        )
+        .extend_with(|_, extensions| {
+            extensions.set("problem", "You do not have permission to do this.");
+            extensions.set("suggestion", "Please contact a workspace administrator.");
+        }));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graphql::test_mocks::*;
+
+    #[async_std::test]
+    async fn anyone_can_read_platform_member_folder() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+
+        let result = require_folder_access(
+            &folder,
+            FolderAccess::Read,
+            &requesting_user,
+            &pool,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn non_member_cannot_read_workspace_member_folder() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+
+        let result = require_folder_access(
+            &folder,
+            FolderAccess::Read,
+            &requesting_user,
+            &pool,
+            &event_client,
+        )
+        .await;
+
+        let error = result.err().unwrap();
+        assert_eq!(
+            error.message,
+            format!(
+                "user with auth_id {} does not have permission to read folder {}",
+                requesting_user.auth_id, folder.id
+            )
+        );
+        assert!(error.extensions.is_some());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn non_member_cannot_write_to_platform_member_folder() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+
+        let result = require_folder_access(
+            &folder,
+            FolderAccess::Write,
+            &requesting_user,
+            &pool,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn member_can_write_to_workspace_member_folder() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+
+        let result = require_folder_access(
+            &folder,
+            FolderAccess::Write,
+            &requesting_user,
+            &pool,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn platform_admin_can_write_to_any_folder() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+        let requesting_user = mock_admin_requesting_user().await?;
+
+        let result = require_folder_access(
+            &folder,
+            FolderAccess::Write,
+            &requesting_user,
+            &pool,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
-use super::{azure, db};
+use super::{
+    authorization::{require_folder_access, FolderAccess},
+    db, BlobStore,
+};
 use async_graphql::{Context, FieldResult, Object, ID};
 use fnhs_event_models::{Event, EventClient, EventPublisher as _, FileDownloadedData};
 use sqlx::PgPool;
+use std::sync::Arc;
 use url::Url;
 use uuid::Uuid;
 
@@ -11,20 +15,37 @@ pub struct FileDownloadUrlsMutation;
 #[Object]
 impl FileDownloadUrlsMutation {
     /// Get a file download URL
-    async fn file_download_url(&self, context: &Context<'_>, id: ID) -> FieldResult<Url> {
+    ///
+    /// Returns a URL for the latest version of the file, unless a version is specified. Versions
+    /// that have not been scanned for malware yet, or that contain malware, cannot be downloaded.
+    async fn file_download_url(
+        &self,
+        context: &Context<'_>,
+        id: ID,
+        version: Option<ID>,
+    ) -> FieldResult<Url> {
         let pool = context.data()?;
-        let config = context.data()?;
+        let blob_store: &Arc<dyn BlobStore> = context.data()?;
         let event_client = context.data()?;
         let requesting_user = context.data()?;
 
-        file_download_url(id, pool, config, event_client, requesting_user).await
+        file_download_url(
+            id,
+            version,
+            pool,
+            blob_store.as_ref(),
+            event_client,
+            requesting_user,
+        )
+        .await
     }
 }
 
 async fn file_download_url(
     id: ID,
+    version: Option<ID>,
     pool: &PgPool,
-    config: &azure::Config,
+    blob_store: &dyn BlobStore,
     event_client: &EventClient,
     requesting_user: &super::RequestingUser,
 ) -> FieldResult<Url> {
@@ -34,6 +55,49 @@ async fn file_download_url(
     let id = Uuid::parse_str(&id)?;
     let file = db::FileWithVersionRepo::find_by_id(id, pool).await?;
     let folder = db::FolderRepo::find_by_id(file.folder, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Read,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    let (version_id, version_number, blob_storage_path, scan_status) = match version {
+        Some(version) => {
+            let version = db::FileVersionRepo::find_by_id(Uuid::parse_str(&version)?, pool).await?;
+            if version.file != file.id {
+                return Err("specified version is not a version of the file".into());
+            }
+            (
+                version.id,
+                version.version_number,
+                version.blob_storage_path,
+                version.scan_status,
+            )
+        }
+        None => (
+            file.version,
+            file.version_number,
+            file.blob_storage_path,
+            file.scan_status,
+        ),
+    };
+    match scan_status.parse()? {
+        db::ScanStatus::Clean => {}
+        db::ScanStatus::Pending => {
+            return Err("the file has not been scanned for malware yet, try again later".into())
+        }
+        db::ScanStatus::Infected => {
+            return Err("the file cannot be downloaded because it contains malware".into())
+        }
+        db::ScanStatus::Failed => {
+            return Err(
+                "the file cannot be downloaded because it could not be scanned for malware".into(),
+            )
+        }
+    }
 
     event_client
         .publish_events(&[Event::new(
@@ -41,17 +105,14 @@ async fn file_download_url(
             FileDownloadedData {
                 file_id: file.id.to_string(),
                 user_id: user.id.to_string(),
-                version_number: file.version_number.into(),
-                version_id: file.version.to_string(),
+                version_number: version_number.into(),
+                version_id: version_id.to_string(),
                 workspace_id: folder.workspace.to_string(),
             },
         )])
         .await?;
 
-    Ok(azure::create_download_sas(
-        config,
-        &file.blob_storage_path.parse()?,
-    )?)
+    Ok(blob_store.create_download_url(&blob_storage_path.parse()?)?)
 }
 
 #[cfg(test)]
@@ -70,6 +131,7 @@ mod test {
 
         file_download_url(
             "1f20fa4c-543c-45b4-93bb-a6e21a8e4de5".into(),
+            None,
             &pool,
             &azure_config,
             &event_client,
@@ -84,4 +146,169 @@ mod test {
 
         Ok(())
     }
+
+    #[async_std::test]
+    async fn file_download_url_for_version_emits_event_with_version() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let (events, event_client) = mock_event_emitter();
+        let version_id = Uuid::new_v4();
+
+        let url = file_download_url(
+            "18a5ab56-2e6f-4a16-8ba9-7d5e8b7d8f53".into(),
+            Some(version_id.into()),
+            &pool,
+            &azure_config,
+            &event_client,
+            &requesting_user,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            url.path(),
+            "/devstoreaccount1/files/old-fake",
+            "should sign the blob of the version"
+        );
+        assert!(events.try_iter().any(|e| matches!(
+            e.data,
+            EventData::FileDownloaded(data)
+                if data.version_id == version_id.to_string() && data.version_number == 1
+        )));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_download_url_fails_if_version_belongs_to_another_file() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = file_download_url(
+            "1f20fa4c-543c-45b4-93bb-a6e21a8e4de5".into(),
+            Some(Uuid::new_v4().into()),
+            &pool,
+            &azure_config,
+            &event_client,
+            &requesting_user,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "specified version is not a version of the file"
+        );
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_download_url_fails_if_not_a_workspace_member() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let admin = mock_admin_requesting_user().await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+        let file = mock_file(&folder, &admin).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = file_download_url(
+            file.id.into(),
+            None,
+            &pool,
+            &azure_config,
+            &event_client,
+            &requesting_user,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_download_url_fails_if_file_has_not_been_scanned() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let file = mock_scanned_file(&folder, &requesting_user, db::ScanStatus::Pending).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = file_download_url(
+            file.id.into(),
+            None,
+            &pool,
+            &azure_config,
+            &event_client,
+            &requesting_user,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "the file has not been scanned for malware yet, try again later"
+        );
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_download_url_fails_if_file_contains_malware() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let file = mock_scanned_file(&folder, &requesting_user, db::ScanStatus::Infected).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = file_download_url(
+            file.id.into(),
+            Some(file.version.into()),
+            &pool,
+            &azure_config,
+            &event_client,
+            &requesting_user,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "the file cannot be downloaded because it contains malware"
+        );
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_download_url_works_once_file_is_clean() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let file = mock_scanned_file(&folder, &requesting_user, db::ScanStatus::Clean).await?;
+        let (_, event_client) = mock_event_emitter();
+
+        let result = file_download_url(
+            file.id.into(),
+            None,
+            &pool,
+            &azure_config,
+            &event_client,
+            &requesting_user,
+        )
+        .await;
+
+        assert!(result.is_ok());
+
+        Ok(())
+    }
 }
@@ -1,25 +1,172 @@
-use super::azure;
-use anyhow::Result;
+use super::{db, files::NewFileUpload, validation, BlobStore, RequestingUser};
 use async_graphql::{Context, FieldResult, Object};
+use sqlx::PgPool;
+use std::sync::Arc;
 use url::Url;
 use uuid::Uuid;
+use validator::Validate;
+
+/// The largest file, in bytes, that a client can get an upload URL for.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxUploadSize(pub u64);
 
 #[derive(Default)]
 pub struct FileUploadUrlsMutation;
 
 #[Object]
 impl FileUploadUrlsMutation {
-    /// Get URLs for uploading files
-    async fn file_upload_urls(&self, context: &Context<'_>, count: u8) -> FieldResult<Vec<Url>> {
-        let config = context.data()?;
-
-        let urls = (0..count)
-            .map(|_i| {
-                let name = Uuid::new_v4();
-                azure::create_upload_sas(config, &name)
-            })
-            .collect::<Result<Vec<Url>>>()?;
-
-        Ok(urls)
+    /// Get URLs for uploading files, one for each file in the order given
+    ///
+    /// Each file has to be uploaded with the declared size and with its type as the content type,
+    /// or it cannot be used to create a file or file version.
+    async fn file_upload_urls(
+        &self,
+        context: &Context<'_>,
+        files: Vec<NewFileUpload>,
+    ) -> FieldResult<Vec<Url>> {
+        let pool = context.data()?;
+        let blob_store: &Arc<dyn BlobStore> = context.data()?;
+        let max_upload_size: &MaxUploadSize = context.data()?;
+        let requesting_user = context.data()?;
+
+        file_upload_urls(
+            files,
+            pool,
+            blob_store.as_ref(),
+            *max_upload_size,
+            requesting_user,
+        )
+        .await
+    }
+}
+
+async fn file_upload_urls(
+    files: Vec<NewFileUpload>,
+    pool: &PgPool,
+    blob_store: &dyn BlobStore,
+    max_upload_size: MaxUploadSize,
+    requesting_user: &RequestingUser,
+) -> FieldResult<Vec<Url>> {
+    for file in &files {
+        file.validate().map_err(validation::ValidationError::from)?;
+        if file.file_size as u64 > max_upload_size.0 {
+            return Err(format!(
+                "the file {} is {} bytes, which is larger than the maximum of {} bytes",
+                file.file_name, file.file_size, max_upload_size.0
+            )
+            .into());
+        }
+    }
+
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+    let mut urls = Vec::with_capacity(files.len());
+    for file in files {
+        let upload = db::UploadRepo::create(
+            db::CreateUploadArgs {
+                id: Uuid::new_v4(),
+                file_name: &file.file_name,
+                file_type: &file.file_type,
+                file_size: file.file_size,
+                created_by: user.id,
+            },
+            pool,
+        )
+        .await?;
+        urls.push(blob_store.create_upload_url(&upload.id)?);
+    }
+
+    Ok(urls)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{blob_store::upload_name, graphql::test_mocks::*};
+
+    fn new_file_upload(file_name: &str, file_type: &str, file_size: i64) -> NewFileUpload {
+        NewFileUpload {
+            file_name: file_name.into(),
+            file_type: file_type.into(),
+            file_size,
+        }
+    }
+
+    #[async_std::test]
+    async fn file_upload_urls_works() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+
+        let result = file_upload_urls(
+            vec![
+                new_file_upload("file.txt", "text/plain", 42),
+                new_file_upload("image.png", "image/png", 1000),
+            ],
+            &pool,
+            &azure_config,
+            MaxUploadSize(1000),
+            &requesting_user,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.len(), 2);
+        let upload = db::UploadRepo::find_by_id(upload_name(&result[1])?, &pool)
+            .await?
+            .unwrap();
+        assert_eq!(upload.file_name, "image.png");
+        assert_eq!(upload.file_type, "image/png");
+        assert_eq!(upload.file_size, 1000);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_upload_urls_fails_if_file_is_too_large() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+
+        let result = file_upload_urls(
+            vec![new_file_upload("file.txt", "text/plain", 1001)],
+            &pool,
+            &azure_config,
+            MaxUploadSize(1000),
+            &requesting_user,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "the file file.txt is 1001 bytes, which is larger than the maximum of 1000 bytes"
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_upload_urls_fails_if_extension_does_not_match_type() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+
+        let result = file_upload_urls(
+            vec![new_file_upload("image.png", "text/plain", 42)],
+            &pool,
+            &azure_config,
+            MaxUploadSize(1000),
+            &requesting_user,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "the file extension is not valid for the specified MIME type"
+        );
+
+        Ok(())
     }
 }
@@ -1,13 +1,27 @@
-use super::{azure, db, validation, RequestingUser};
-use async_graphql::{Context, FieldResult, InputObject, Object, SimpleObject, ID};
+use super::{
+    authorization::{require_folder_access, FolderAccess},
+    db,
+    pagination::{Cursor, Page, PaginatedConnection},
+    users::User,
+    validation,
+    workspaces::{requesting_user_workspace_rights, require_workspace_admin, WorkspaceMembership},
+    BlobStore, RequestingUser,
+};
+use crate::{
+    blob_store::{upload_name, BlobDigest, BlobProperties},
+    sniff,
+};
+use async_graphql::{Context, Enum, ErrorExtensions, FieldResult, InputObject, Object, ID};
 use chrono::{DateTime, Utc};
 use fnhs_event_models::{
-    Event, EventClient, EventPublisher as _, FileCreatedData, FileDeletedData, FileUpdatedData,
+    Event, EventClient, EventPublisher as _, FileCreatedData, FileDeletedData, FileMovedData,
+    FileRejectedData, FileRestoredData, FileUpdatedData,
 };
 use lazy_static::lazy_static;
 use mime_db::extensions2;
 use regex::Regex;
 use sqlx::PgPool;
+use std::sync::Arc;
 use url::Url;
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
@@ -42,29 +56,210 @@ lazy_static! {
     .expect("bad regex");
 }
 
-/// A file
-#[derive(SimpleObject)]
+/// Whether a file version has been scanned for malware
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ScanStatus {
+    /// The version has not been scanned yet, so it cannot be downloaded
+    Pending,
+    /// No malware was found, so the version can be downloaded
+    Clean,
+    /// Malware was found, so the version can never be downloaded
+    Infected,
+    /// The version could not be scanned, so it can never be downloaded
+    Failed,
+}
+
+impl From<db::ScanStatus> for ScanStatus {
+    fn from(d: db::ScanStatus) -> Self {
+        match d {
+            db::ScanStatus::Pending => ScanStatus::Pending,
+            db::ScanStatus::Clean => ScanStatus::Clean,
+            db::ScanStatus::Infected => ScanStatus::Infected,
+            db::ScanStatus::Failed => ScanStatus::Failed,
+        }
+    }
+}
+
 pub struct File {
-    /// The id of the file
     pub id: ID,
-    /// The title of the file
     pub title: String,
-    /// The description of the file
     pub description: String,
-    /// The id of the parent folder
     pub folder: ID,
-    /// The name of the file
     pub file_name: String,
-    /// The type of the file
     pub file_type: String,
-    /// ID of the latest version of the file
     pub latest_version: ID,
-    /// The time the file was created
     pub created_at: DateTime<Utc>,
-    /// The time the file was modified
     pub modified_at: DateTime<Utc>,
-    /// The time the file was deleted
     pub deleted_at: Option<DateTime<Utc>>,
+    pub scan_status: ScanStatus,
+    pub sha256: Option<String>,
+    pub file_size: Option<i64>,
+    pub page_count: Option<i32>,
+}
+
+#[Object]
+/// A file
+impl File {
+    /// The id of the file
+    async fn id(&self) -> ID {
+        self.id.clone()
+    }
+    /// The title of the file
+    async fn title(&self) -> String {
+        self.title.clone()
+    }
+    /// The description of the file
+    async fn description(&self) -> String {
+        self.description.clone()
+    }
+    /// The id of the parent folder
+    async fn folder(&self) -> ID {
+        self.folder.clone()
+    }
+    /// The name of the file
+    async fn file_name(&self) -> String {
+        self.file_name.clone()
+    }
+    /// The type of the file
+    async fn file_type(&self) -> String {
+        self.file_type.clone()
+    }
+    /// The size of the latest version of the file in bytes. Files uploaded before sizes were
+    /// recorded do not have one.
+    async fn file_size(&self) -> Option<i64> {
+        self.file_size
+    }
+    /// An estimate of the number of pages in the latest version of the file, if it is a PDF whose
+    /// pages could be counted
+    async fn page_count(&self) -> Option<i32> {
+        self.page_count
+    }
+    /// ID of the latest version of the file
+    async fn latest_version(&self) -> ID {
+        self.latest_version.clone()
+    }
+    /// The time the file was created
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    /// The time the file was modified
+    async fn modified_at(&self) -> DateTime<Utc> {
+        self.modified_at
+    }
+    /// The time the file was deleted
+    async fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at
+    }
+    /// Whether the latest version of the file has been scanned for malware. It can only be
+    /// downloaded once it is clean.
+    async fn scan_status(&self) -> ScanStatus {
+        self.scan_status
+    }
+    /// The SHA-256 hash of the contents of the latest version of the file, as hex. Files uploaded
+    /// before hashes were recorded do not have one.
+    async fn sha256(&self) -> Option<String> {
+        self.sha256.clone()
+    }
+
+    /// All versions of the file, newest first
+    async fn versions(&self, context: &Context<'_>) -> FieldResult<Vec<FileVersion>> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        file_versions(
+            Uuid::parse_str(&self.id)?,
+            pool,
+            requesting_user,
+            event_client,
+        )
+        .await
+    }
+}
+
+pub struct FileVersion {
+    pub id: ID,
+    pub file: ID,
+    pub folder: ID,
+    pub version_number: i16,
+    pub version_label: String,
+    pub title: String,
+    pub description: String,
+    pub file_name: String,
+    pub file_type: String,
+    pub created_at: DateTime<Utc>,
+    pub created_by: Uuid,
+    pub scan_status: ScanStatus,
+    pub file_size: Option<i64>,
+    pub page_count: Option<i32>,
+}
+
+#[Object]
+/// A version of a file
+impl FileVersion {
+    /// The id of the file version
+    async fn id(&self) -> ID {
+        self.id.clone()
+    }
+    /// The id of the file
+    async fn file(&self) -> ID {
+        self.file.clone()
+    }
+    /// The id of the folder the file was in at this version
+    async fn folder(&self) -> ID {
+        self.folder.clone()
+    }
+    /// The version number, starting at 1
+    async fn version_number(&self) -> i16 {
+        self.version_number
+    }
+    /// The label of the version
+    async fn version_label(&self) -> String {
+        self.version_label.clone()
+    }
+    /// The title of the file at this version
+    async fn title(&self) -> String {
+        self.title.clone()
+    }
+    /// The description of the file at this version
+    async fn description(&self) -> String {
+        self.description.clone()
+    }
+    /// The name of the file at this version
+    async fn file_name(&self) -> String {
+        self.file_name.clone()
+    }
+    /// The type of the file at this version
+    async fn file_type(&self) -> String {
+        self.file_type.clone()
+    }
+    /// The size of the file at this version in bytes. Versions uploaded before sizes were
+    /// recorded do not have one.
+    async fn file_size(&self) -> Option<i64> {
+        self.file_size
+    }
+    /// An estimate of the number of pages in the file at this version, if it is a PDF whose pages
+    /// could be counted
+    async fn page_count(&self) -> Option<i32> {
+        self.page_count
+    }
+    /// The time the version was created
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+    /// Whether the version has been scanned for malware
+    async fn scan_status(&self) -> ScanStatus {
+        self.scan_status
+    }
+    /// The user who created the version
+    async fn created_by(&self, context: &Context<'_>) -> FieldResult<User> {
+        let pool = context.data()?;
+        let user = db::UserRepo::find_by_id(&self.created_by, pool)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+        Ok(user.into())
+    }
 }
 
 #[derive(InputObject, Debug, Validate)]
@@ -131,6 +326,37 @@ pub struct NewFileVersion {
     pub temporary_blob_storage_path: Option<String>,
 }
 
+#[derive(InputObject, Debug, Validate)]
+#[validate(schema(
+    function = "new_file_upload_name_matches_type",
+    message = "the file extension is not valid for the specified MIME type",
+))]
+pub struct NewFileUpload {
+    #[validate(
+        length(
+            min = 5,
+            max = 255,
+            message = "the file name must be between 5 and 255 characters long"
+        ),
+        regex(
+            path = "ALLOWED_FILENAME_CHARS",
+            message = "the file name contains characters that are not alphanumeric, space, period, hyphen or underscore"
+        ),
+        regex(
+            path = "ALLOWED_EXTENSIONS",
+            message = "the file name does not have an allowed extension"
+        )
+    )]
+    pub file_name: String,
+    pub file_type: String,
+    #[validate(range(min = 0, message = "the file size cannot be negative"))]
+    pub file_size: i64,
+}
+
+fn new_file_upload_name_matches_type(new_file: &NewFileUpload) -> Result<(), ValidationError> {
+    file_name_matches_file_type(&new_file.file_name, &new_file.file_type)
+}
+
 fn new_file_version_name_matches_type(new_file: &NewFileVersion) -> Result<(), ValidationError> {
     match (&new_file.file_name, &new_file.file_type) {
         (Some(file_name), Some(file_type)) => file_name_matches_file_type(file_name, file_type),
@@ -168,6 +394,31 @@ impl From<db::FileWithVersion> for File {
             created_at: d.created_at,
             modified_at: d.modified_at,
             deleted_at: d.deleted_at,
+            scan_status: d.scan_status.parse::<db::ScanStatus>().unwrap().into(),
+            sha256: d.sha256,
+            file_size: d.file_size,
+            page_count: d.page_count,
+        }
+    }
+}
+
+impl From<db::FileVersion> for FileVersion {
+    fn from(d: db::FileVersion) -> Self {
+        Self {
+            id: d.id.into(),
+            file: d.file.into(),
+            folder: d.folder.into(),
+            version_number: d.version_number,
+            version_label: d.version_label,
+            title: d.file_title,
+            description: d.file_description,
+            file_name: d.file_name,
+            file_type: d.file_type,
+            created_at: d.created_at,
+            created_by: d.created_by,
+            scan_status: d.scan_status.parse::<db::ScanStatus>().unwrap().into(),
+            file_size: d.file_size,
+            page_count: d.page_count,
         }
     }
 }
@@ -177,13 +428,26 @@ pub struct FilesQuery;
 
 #[Object]
 impl FilesQuery {
-    /// Get all Files in a Folder
-    async fn files_by_folder(&self, context: &Context<'_>, folder: ID) -> FieldResult<Vec<File>> {
+    /// Get Files in a Folder, ordered by title
+    async fn files_by_folder(
+        &self,
+        context: &Context<'_>,
+        folder: ID,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> FieldResult<PaginatedConnection<File>> {
         let pool = context.data()?;
-        let folder = Uuid::parse_str(&folder)?;
-        let files = db::FileWithVersionRepo::find_by_folder(folder, pool).await?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
 
-        Ok(files.into_iter().map(Into::into).collect())
+        files_by_folder(
+            folder,
+            Page::new(after, first)?,
+            pool,
+            requesting_user,
+            event_client,
+        )
+        .await
     }
 
     /// Get file by ID
@@ -191,12 +455,51 @@ impl FilesQuery {
         self.get_file(context, id).await
     }
 
+    /// Get all versions of a file, newest first
+    async fn file_versions(
+        &self,
+        context: &Context<'_>,
+        file: ID,
+    ) -> FieldResult<Vec<FileVersion>> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        file_versions(Uuid::parse_str(&file)?, pool, requesting_user, event_client).await
+    }
+
+    /// Get the other files in the same workspace as a file that have the same contents, ordered
+    /// by title
+    async fn duplicates(&self, context: &Context<'_>, file: ID) -> FieldResult<Vec<File>> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        duplicates(Uuid::parse_str(&file)?, pool, requesting_user, event_client).await
+    }
+
+    /// Get the files that have been deleted from a workspace, most recently deleted first. Only
+    /// workspace admins can see these.
+    async fn deleted_files(
+        &self,
+        context: &Context<'_>,
+        workspace: ID,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> FieldResult<PaginatedConnection<File>> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+
+        deleted_files(workspace, Page::new(after, first)?, pool, requesting_user).await
+    }
+
     #[graphql(entity)]
     async fn get_file(&self, context: &Context<'_>, id: ID) -> FieldResult<File> {
         let pool = context.data()?;
-        let id = Uuid::parse_str(&id)?;
-        let file = db::FileWithVersionRepo::find_by_id(id, pool).await?;
-        Ok(file.into())
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        get_file(id, pool, requesting_user, event_client).await
     }
 }
 
@@ -208,11 +511,18 @@ impl FilesMutation {
     /// Create a new file (returns the created file)
     async fn create_file(&self, context: &Context<'_>, new_file: NewFile) -> FieldResult<File> {
         let pool = context.data()?;
-        let azure_config = context.data()?;
+        let blob_store: &Arc<dyn BlobStore> = context.data()?;
         let requesting_user = context.data()?;
         let event_client = context.data()?;
 
-        create_file(new_file, pool, azure_config, requesting_user, event_client).await
+        create_file(
+            new_file,
+            pool,
+            blob_store.as_ref(),
+            requesting_user,
+            event_client,
+        )
+        .await
     }
 
     /// Create a new file version (returns the updated file)
@@ -228,14 +538,43 @@ impl FilesMutation {
         new_version: NewFileVersion,
     ) -> FieldResult<File> {
         let pool = context.data()?;
-        let azure_config = context.data()?;
+        let blob_store: &Arc<dyn BlobStore> = context.data()?;
         let requesting_user = context.data()?;
         let event_client = context.data()?;
 
         create_file_version(
             new_version,
             pool,
-            azure_config,
+            blob_store.as_ref(),
+            requesting_user,
+            event_client,
+        )
+        .await
+    }
+
+    /// Restore a previous version of a file (returns the updated file)
+    ///
+    /// This will create a new version of the file, which copies the title, description, file
+    /// name, type and contents of the specified version.
+    ///
+    /// Both file and latest version are required. The operation will fail if the specified latest
+    /// version is no longer the latest version of the file.
+    async fn restore_file_version(
+        &self,
+        context: &Context<'_>,
+        file: ID,
+        version: ID,
+        latest_version: ID,
+    ) -> FieldResult<File> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        restore_file_version(
+            file,
+            version,
+            latest_version,
+            pool,
             requesting_user,
             event_client,
         )
@@ -250,12 +589,163 @@ impl FilesMutation {
 
         delete_file(id, pool, requesting_user, event_client).await
     }
+
+    /// Restores a deleted file to the folder it was deleted from (returns the restored file)
+    async fn restore_file(&self, context: &Context<'_>, id: ID) -> FieldResult<File> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        restore_file(id, pool, requesting_user, event_client).await
+    }
+
+    /// Moves files into another folder, which can be in another workspace (returns the moved
+    /// files)
+    async fn move_files(
+        &self,
+        context: &Context<'_>,
+        files: Vec<ID>,
+        target_folder: ID,
+    ) -> FieldResult<Vec<File>> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        move_files(files, target_folder, pool, requesting_user, event_client).await
+    }
+}
+
+async fn files_by_folder(
+    folder: ID,
+    page: Page,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<PaginatedConnection<File>> {
+    let folder = db::FolderRepo::find_by_id(Uuid::parse_str(&folder)?, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Read,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    let files = db::FileWithVersionRepo::find_by_folder(folder.id, page.args(), pool).await?;
+    let total_count = db::FileWithVersionRepo::count_by_folder(folder.id, pool).await?;
+
+    Ok(page.connection(files, total_count, |file| Cursor {
+        sort_key: file.title.clone(),
+        id: file.id,
+    }))
+}
+
+async fn get_file(
+    id: ID,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<File> {
+    let file = db::FileWithVersionRepo::find_by_id(Uuid::parse_str(&id)?, pool).await?;
+    let folder = db::FolderRepo::find_by_id(file.folder, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Read,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    Ok(file.into())
+}
+
+async fn file_versions(
+    file: Uuid,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<Vec<FileVersion>> {
+    let current_file = db::FileWithVersionRepo::find_by_id(file, pool).await?;
+    let folder = db::FolderRepo::find_by_id(current_file.folder, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Read,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    let file_versions = db::FileVersionRepo::find_by_file_id(file, pool).await?;
+
+    Ok(file_versions.into_iter().map(Into::into).collect())
+}
+
+async fn duplicates(
+    file: Uuid,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<Vec<File>> {
+    let file = db::FileWithVersionRepo::find_by_id(file, pool).await?;
+    let folder = db::FolderRepo::find_by_id(file.folder, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Read,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    // Files uploaded before hashes were recorded cannot be compared.
+    let sha256 = match &file.sha256 {
+        Some(sha256) => sha256,
+        None => return Ok(vec![]),
+    };
+    let user_role =
+        requesting_user_workspace_rights(folder.workspace, requesting_user, pool, event_client)
+            .await?;
+    let duplicates = db::FileWithVersionRepo::find_duplicates(
+        folder.workspace,
+        sha256,
+        file.id,
+        user_role != WorkspaceMembership::NonMember,
+        pool,
+    )
+    .await?;
+
+    Ok(duplicates.into_iter().map(Into::into).collect())
+}
+
+async fn deleted_files(
+    workspace: ID,
+    page: Page,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+) -> FieldResult<PaginatedConnection<File>> {
+    let workspace = Uuid::parse_str(&workspace)?;
+    require_workspace_admin(workspace, "list deleted files", requesting_user, pool).await?;
+
+    let files =
+        db::FileWithVersionRepo::find_deleted_by_workspace(workspace, page.args(), pool).await?;
+    let total_count = db::FileWithVersionRepo::count_deleted_by_workspace(workspace, pool).await?;
+
+    Ok(page.connection(files, total_count, |file| Cursor {
+        sort_key: file
+            .deleted_at
+            .map(|deleted_at| deleted_at.to_rfc3339())
+            .unwrap_or_default(),
+        id: file.id,
+    }))
 }
 
 async fn create_file(
     new_file: NewFile,
     pool: &PgPool,
-    azure_config: &azure::Config,
+    blob_store: &dyn BlobStore,
     requesting_user: &RequestingUser,
     event_client: &EventClient,
 ) -> FieldResult<File> {
@@ -267,13 +757,29 @@ async fn create_file(
     let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
         .await?
         .ok_or_else(|| anyhow::anyhow!("user not found"))?;
-    let destination = azure::copy_blob_from_url(
-        &Url::parse(&new_file.temporary_blob_storage_path)?,
-        azure_config,
+    let folder = db::FolderRepo::find_by_id(folder_id, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Write,
+        requesting_user,
+        pool,
+        event_client,
     )
     .await?;
 
-    let folder = db::FolderRepo::find_by_id(folder_id, pool).await?;
+    let (destination, digest) = accept_upload(
+        &new_file.temporary_blob_storage_path,
+        &new_file.file_type,
+        UploadDestination {
+            file: None,
+            folder: &folder,
+            user: &user,
+        },
+        pool,
+        blob_store,
+        event_client,
+    )
+    .await?;
 
     let file = db::FileWithVersionRepo::create(
         db::CreateFileArgs {
@@ -284,6 +790,9 @@ async fn create_file(
             file_name: &new_file.file_name,
             file_type: &new_file.file_type,
             blob_storage_path: &destination,
+            sha256: &digest.sha256,
+            file_size: digest.size as i64,
+            page_count: digest.page_count.map(|pages| pages as i32),
         },
         pool,
     )
@@ -313,7 +822,7 @@ async fn create_file(
 async fn create_file_version(
     new_version: NewFileVersion,
     pool: &PgPool,
-    azure_config: &azure::Config,
+    blob_store: &dyn BlobStore,
     requesting_user: &RequestingUser,
     event_client: &EventClient,
 ) -> FieldResult<File> {
@@ -325,6 +834,16 @@ async fn create_file_version(
     let current_latest_version_id = Uuid::parse_str(&new_version.latest_version)?;
 
     let current_file = db::FileWithVersionRepo::find_by_id(current_file_id, pool).await?;
+    let current_folder = db::FolderRepo::find_by_id(current_file.folder, pool).await?;
+    require_folder_access(
+        &current_folder,
+        FolderAccess::Write,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
     if current_file.version != current_latest_version_id {
         // Early check to see if the latest version matches to avoid potentially copying the
         // file unnecessarily. There is still a chance someone else creates a new version in
@@ -341,16 +860,58 @@ async fn create_file_version(
         None => current_file.folder,
     };
 
-    let folder = db::FolderRepo::find_by_id(folder_id, pool).await?;
-
-    let destination = match &new_version.temporary_blob_storage_path {
-        Some(temporary_blob_storage_path) => {
-            azure::copy_blob_from_url(&Url::parse(temporary_blob_storage_path)?, azure_config)
-                .await?
-        }
-        None => current_file.blob_storage_path,
+    let folder = if folder_id == current_folder.id {
+        current_folder
+    } else {
+        let folder = db::FolderRepo::find_by_id(folder_id, pool).await?;
+        require_folder_access(
+            &folder,
+            FolderAccess::Write,
+            requesting_user,
+            pool,
+            event_client,
+        )
+        .await?;
+        folder
     };
 
+    let (destination, scan_status, sha256, file_size, page_count) =
+        match &new_version.temporary_blob_storage_path {
+            Some(temporary_blob_storage_path) => {
+                let (destination, digest) = accept_upload(
+                    temporary_blob_storage_path,
+                    new_version
+                        .file_type
+                        .as_ref()
+                        .unwrap_or(&current_file.file_type),
+                    UploadDestination {
+                        file: Some(current_file.id),
+                        folder: &folder,
+                        user: &user,
+                    },
+                    pool,
+                    blob_store,
+                    event_client,
+                )
+                .await?;
+                (
+                    destination,
+                    db::ScanStatus::Pending,
+                    Some(digest.sha256),
+                    Some(digest.size as i64),
+                    digest.page_count.map(|pages| pages as i32),
+                )
+            }
+            // The blob has already been scanned, or is waiting to be.
+            None => (
+                current_file.blob_storage_path,
+                current_file.scan_status.parse()?,
+                current_file.sha256,
+                current_file.file_size,
+                current_file.page_count,
+            ),
+        };
+
     let version_number = current_file.version_number + 1;
     let file: File = db::FileWithVersionRepo::create_version(
         db::CreateFileVersionArgs {
@@ -373,6 +934,10 @@ async fn create_file_version(
                 .unwrap_or(&current_file.file_type),
             blob_storage_path: &destination,
             version_number,
+            scan_status,
+            sha256: sha256.as_deref(),
+            file_size,
+            page_count,
         },
         pool,
     )
@@ -400,6 +965,219 @@ async fn create_file_version(
     Ok(file)
 }
 
+/// Where an upload is going to be added.
+struct UploadDestination<'a> {
+    /// The file that the upload is a new version of, if any
+    file: Option<Uuid>,
+    folder: &'a db::Folder,
+    user: &'a db::User,
+}
+
+/// Checks that the blob at the upload URL is what the user said they would upload when they
+/// asked for the URL, then copies it to the files container, checks the copy and returns its
+/// URL, hash and size. A copy that fails the checks is deleted again.
+///
+/// If the contents of the blob are not of the declared type, the upload is rejected with a
+/// `FileRejected` event.
+async fn accept_upload(
+    temporary_blob_storage_path: &str,
+    file_type: &str,
+    destination: UploadDestination<'_>,
+    pool: &PgPool,
+    blob_store: &dyn BlobStore,
+    event_client: &EventClient,
+) -> FieldResult<(String, BlobDigest)> {
+    let url = Url::parse(temporary_blob_storage_path)?;
+    let upload = db::UploadRepo::find_by_id(upload_name(&url)?, pool)
+        .await?
+        .filter(|upload| upload.created_by == destination.user.id)
+        .ok_or_else(|| anyhow::anyhow!("the upload URL was not requested by this user"))?;
+
+    if upload.file_type != file_type {
+        return Err(format!(
+            "the file type {} does not match the type {} declared for the upload",
+            file_type, upload.file_type
+        )
+        .into());
+    }
+
+    let properties = blob_store.upload_properties(&url).await?;
+    if properties.size != upload.file_size as u64 {
+        return Err(format!(
+            "the uploaded file is {} bytes, but {} bytes were declared",
+            properties.size, upload.file_size
+        )
+        .into());
+    }
+    if properties.content_type.as_deref() != Some(upload.file_type.as_str()) {
+        return Err(format!(
+            "the uploaded file has content type {}, but {} was declared",
+            properties.content_type.as_deref().unwrap_or("(none)"),
+            upload.file_type
+        )
+        .into());
+    }
+
+    // The upload URL can still be written to, so the upload could have changed since the checks
+    // above. Check the copy instead, which the user cannot change.
+    let copy = blob_store.copy_upload(&url).await?;
+    match check_copy(
+        &copy,
+        &upload,
+        &properties,
+        &destination,
+        blob_store,
+        event_client,
+    )
+    .await
+    {
+        Ok(digest) => Ok((copy, digest)),
+        Err(err) => {
+            if let Err(delete_err) = blob_store.delete_blob(&copy.parse()?).await {
+                tracing::error!("deleting the rejected copy {} failed: {}", copy, delete_err);
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Checks that the copy of an upload in the files container has the declared size and contents
+/// of the declared type, and returns its hash and size.
+///
+/// If the contents are not of the declared type, the upload is rejected with a `FileRejected`
+/// event.
+async fn check_copy(
+    copy: &str,
+    upload: &db::Upload,
+    properties: &BlobProperties,
+    destination: &UploadDestination<'_>,
+    blob_store: &dyn BlobStore,
+    event_client: &EventClient,
+) -> FieldResult<BlobDigest> {
+    let contents = blob_store.open_file(&copy.parse()?).await?;
+    let (prefix, mut contents) = crate::blob_store::peek(contents, sniff::SNIFF_LEN).await?;
+    let detected = sniff::detect(&prefix);
+    if !detected.is_compatible_with(&upload.file_type) {
+        event_client
+            .publish_events(&[Event::new(
+                upload.id.to_string(),
+                FileRejectedData {
+                    detected_type: detected.name().to_owned(),
+                    file_id: destination.file.map(|file| file.to_string()),
+                    file_name: upload.file_name.clone(),
+                    file_type: upload.file_type.clone(),
+                    folder_id: destination.folder.id.to_string(),
+                    upload_id: upload.id.to_string(),
+                    user_id: destination.user.id.to_string(),
+                    workspace_id: destination.folder.workspace.to_string(),
+                },
+            )])
+            .await?;
+
+        return Err(format!(
+            "the content of the file was detected as {}, which does not match the MIME type {}",
+            detected.name(),
+            upload.file_type
+        )
+        .into());
+    }
+
+    let digest = crate::blob_store::digest(&mut contents, &upload.file_type).await?;
+    if digest.size != properties.size {
+        return Err(anyhow::anyhow!(
+            "the copy of the upload at {} is {} bytes, but the upload was {} bytes",
+            copy,
+            digest.size,
+            properties.size
+        )
+        .into());
+    }
+
+    Ok(digest)
+}
+
+async fn restore_file_version(
+    file: ID,
+    version: ID,
+    latest_version: ID,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<File> {
+    let current_file_id = Uuid::parse_str(&file)?;
+    let current_latest_version_id = Uuid::parse_str(&latest_version)?;
+    let version_id = Uuid::parse_str(&version)?;
+
+    let current_file = db::FileWithVersionRepo::find_by_id(current_file_id, pool).await?;
+    let folder = db::FolderRepo::find_by_id(current_file.folder, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Write,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    if current_file.version != current_latest_version_id {
+        // Early check to avoid looking up the version unnecessarily. A concurrent update is still
+        // caught by `db::File::update_latest_version`.
+        return Err("specified version is not the latest version of the file".into());
+    }
+
+    let version = db::FileVersionRepo::find_by_id(version_id, pool).await?;
+    if version.file != current_file_id {
+        return Err("specified version is not a version of the file".into());
+    }
+
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+    let version_number = current_file.version_number + 1;
+    let file: File = db::FileWithVersionRepo::create_version(
+        db::CreateFileVersionArgs {
+            user_id: user.id,
+            file_id: current_file_id,
+            latest_version: current_latest_version_id,
+            folder_id: current_file.folder,
+            title: &version.file_title,
+            description: &version.file_description,
+            file_name: &version.file_name,
+            file_type: &version.file_type,
+            blob_storage_path: &version.blob_storage_path,
+            version_number,
+            scan_status: version.scan_status.parse()?,
+            sha256: version.sha256.as_deref(),
+            file_size: version.file_size,
+            page_count: version.page_count,
+        },
+        pool,
+    )
+    .await?
+    .into();
+
+    event_client
+        .publish_events(&[Event::new(
+            file.id.clone(),
+            FileUpdatedData {
+                file_id: file.id.to_string(),
+                file_description: file.description.clone(),
+                file_title: file.title.clone(),
+                file_type: file.file_type.clone(),
+                folder_id: file.folder.to_string(),
+                user_id: user.id.to_string(),
+                workspace_id: folder.workspace.to_string(),
+                version_id: file.latest_version.to_string(),
+                version_number: version_number.into(),
+                updated_at: file.modified_at,
+            },
+        )])
+        .await?;
+
+    Ok(file)
+}
+
 async fn delete_file(
     id: ID,
     pool: &PgPool,
@@ -409,9 +1187,18 @@ async fn delete_file(
     let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
         .await?
         .ok_or_else(|| anyhow::anyhow!("user not found"))?;
-    let file = db::FileWithVersionRepo::delete(Uuid::parse_str(&id)?, user.id, pool).await?;
-
+    let file = db::FileWithVersionRepo::find_by_id(Uuid::parse_str(&id)?, pool).await?;
     let folder = db::FolderRepo::find_by_id(file.folder, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Write,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    let file = db::FileWithVersionRepo::delete(file.id, user.id, pool).await?;
 
     event_client
         .publish_events(&[Event::new(
@@ -428,10 +1215,136 @@ async fn delete_file(
 
     Ok(file.into())
 }
+
+async fn restore_file(
+    id: ID,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<File> {
+    let id = Uuid::parse_str(&id)?;
+    let file = db::FileWithVersionRepo::find_deleted_by_id(id, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("file {} has not been deleted", id))?;
+    let folder = db::FolderRepo::find_by_id_if_exists(file.folder, pool)
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "cannot restore file {} because its folder {} has been deleted",
+                file.id,
+                file.folder
+            )
+            .extend_with(|_, extensions| {
+                extensions.set("problem", "The folder this file was in has been deleted.");
+                extensions.set("suggestion", "Please upload the file to another folder.");
+            })
+        })?;
+    let user =
+        require_workspace_admin(folder.workspace, "restore file", requesting_user, pool).await?;
+
+    let file = db::FileWithVersionRepo::restore(file.id, pool).await?;
+
+    event_client
+        .publish_events(&[Event::new(
+            file.id.to_string(),
+            FileRestoredData {
+                file_id: file.id.to_string(),
+                user_id: user.id.to_string(),
+                version_id: file.version.to_string(),
+                workspace_id: folder.workspace.to_string(),
+                version_number: file.version_number.into(),
+            },
+        )])
+        .await?;
+
+    Ok(file.into())
+}
+
+async fn move_files(
+    files: Vec<ID>,
+    target_folder: ID,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<Vec<File>> {
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+    // The folders and files are locked until they have been moved, so that they cannot be moved,
+    // deleted or made to require other access after they have been checked.
+    let mut tx = db::begin(pool).await?;
+    let target_folder =
+        db::FolderRepo::find_by_id_for_update(Uuid::parse_str(&target_folder)?, &mut tx).await?;
+    require_folder_access(
+        &target_folder,
+        FolderAccess::Write,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    let mut files_to_move = Vec::with_capacity(files.len());
+    for id in files {
+        let id = Uuid::parse_str(&id)?;
+        if files_to_move
+            .iter()
+            .any(|(file, _): &(db::FileWithVersion, db::Folder)| file.id == id)
+        {
+            continue;
+        }
+        let file = db::FileWithVersionRepo::find_by_id_for_update(id, &mut tx).await?;
+        let source_folder = db::FolderRepo::find_by_id_for_update(file.folder, &mut tx).await?;
+        require_folder_access(
+            &source_folder,
+            FolderAccess::Write,
+            requesting_user,
+            pool,
+            event_client,
+        )
+        .await?;
+        files_to_move.push((file, source_folder));
+    }
+
+    let ids: Vec<Uuid> = files_to_move.iter().map(|(file, _)| file.id).collect();
+    db::FileWithVersionRepo::move_to_folder(&ids, target_folder.id, &mut tx).await?;
+    tx.commit().await?;
+
+    let events: Vec<Event> = files_to_move
+        .iter()
+        .map(|(file, source_folder)| {
+            Event::new(
+                file.id.to_string(),
+                FileMovedData {
+                    file_id: file.id.to_string(),
+                    user_id: user.id.to_string(),
+                    old_folder_id: source_folder.id.to_string(),
+                    new_folder_id: target_folder.id.to_string(),
+                    old_workspace_id: source_folder.workspace.to_string(),
+                    new_workspace_id: target_folder.workspace.to_string(),
+                },
+            )
+        })
+        .collect();
+    event_client.publish_events(&events).await?;
+
+    Ok(files_to_move
+        .into_iter()
+        .map(|(file, _)| {
+            db::FileWithVersion {
+                folder: target_folder.id,
+                ..file
+            }
+            .into()
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::graphql::test_mocks::*;
+    use crate::{azure, graphql::test_mocks::*};
     use fnhs_event_models::EventData;
     use test_case::test_case;
 
@@ -505,17 +1418,18 @@ mod test {
         let pool = mock_connection_pool()?;
         let azure_config = mock_azure_config()?;
         let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let upload = mock_upload(&requesting_user, "text/plain", 42).await?;
         let (events, event_client) = mock_event_emitter();
 
         let result = create_file(
             NewFile {
                 title: "title".into(),
                 description: "description".into(),
-                folder: "d890181d-6b17-428e-896b-f76add15b54a".into(),
+                folder: folder.id.into(),
                 file_name: "file.txt".into(),
                 file_type: "text/plain".into(),
-                temporary_blob_storage_path: "http://localhost:10000/devstoreaccount1/upload/fake"
-                    .into(),
+                temporary_blob_storage_path: upload,
             },
             &pool,
             &azure_config,
@@ -524,7 +1438,10 @@ mod test {
         )
         .await;
 
-        assert_eq!(result.unwrap().title, "title");
+        let result = result.unwrap();
+        assert_eq!(result.title, "title");
+        assert_eq!(result.scan_status, ScanStatus::Pending);
+        assert_eq!(result.sha256.as_deref(), Some(FAKE_FILE_SHA256));
         assert!(events
             .try_iter()
             .any(|e| matches!(e.data, EventData::FileCreated(_))));
@@ -533,86 +1450,140 @@ mod test {
     }
 
     #[async_std::test]
-    async fn delete_file_works() -> anyhow::Result<()> {
+    async fn create_file_records_the_size_and_page_count() -> anyhow::Result<()> {
         let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
         let requesting_user = mock_unprivileged_requesting_user().await?;
-        let (events, event_client) = mock_event_emitter();
-        let id: ID = ID::from("96bb1f76-6d0e-4a14-a379-034a738715ec");
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let contents = b"%PDF-1.4\n1 0 obj << /Type /Page >> endobj\n%%EOF\n";
+        let upload =
+            mock_upload_with_contents(&requesting_user, "application/pdf", contents).await?;
+        let (_, event_client) = mock_event_emitter();
+
+        let result = create_file(
+            NewFile {
+                title: "title".into(),
+                description: "description".into(),
+                folder: folder.id.into(),
+                file_name: "file.pdf".into(),
+                file_type: "application/pdf".into(),
+                temporary_blob_storage_path: upload,
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.file_size, Some(contents.len() as i64));
+        assert_eq!(result.page_count, Some(1));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    #[ignore = "needs MinIO, run with `make test-s3`"]
+    async fn create_file_accepts_an_upload_put_to_an_s3_upload_url() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let s3_config = mock_s3_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let contents = b"%PDF-1.4\n1 0 obj << /Type /Page >> endobj\n%%EOF\n";
+        let upload =
+            mock_upload(&requesting_user, "application/pdf", contents.len() as i64).await?;
+        let upload_url = s3_config.create_upload_url(&upload_name(&Url::parse(&upload)?)?)?;
+        let (_, event_client) = mock_event_emitter();
+
+        // This is how the frontend uploads to an S3 upload URL.
+        let response = surf::put(upload_url.clone())
+            .header("content-type", "application/pdf")
+            .body(contents.to_vec())
+            .await
+            .map_err(|err| err.into_inner())?;
+        assert!(response.status().is_success());
 
-        let result = delete_file(id, &pool, &requesting_user, &event_client).await;
+        let result = create_file(
+            NewFile {
+                title: "title".into(),
+                description: "description".into(),
+                folder: folder.id.into(),
+                file_name: "file.pdf".into(),
+                file_type: "application/pdf".into(),
+                temporary_blob_storage_path: upload_url.to_string(),
+            },
+            &pool,
+            &s3_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
 
+        assert_eq!(result.file_size, Some(contents.len() as i64));
         assert_eq!(
-            result.unwrap().id,
-            ID::from("96bb1f76-6d0e-4a14-a379-034a738715ec")
+            result.sha256,
+            Some(format!(
+                "{:x}",
+                <sha2::Sha256 as sha2::Digest>::digest(contents)
+            ))
         );
-        assert!(events
-            .try_iter()
-            .any(|e| matches!(e.data, EventData::FileDeleted(_))));
 
         Ok(())
     }
 
     #[async_std::test]
-    async fn create_file_version_works() -> anyhow::Result<()> {
+    async fn create_file_fails_if_size_does_not_match_upload() -> anyhow::Result<()> {
         let pool = mock_connection_pool()?;
         let azure_config = mock_azure_config()?;
         let requesting_user = mock_unprivileged_requesting_user().await?;
-
-        let file_id = Uuid::new_v4();
-        let current_file = db::FileWithVersionRepo::find_by_id(file_id, &pool).await?;
+        let upload = mock_upload(&requesting_user, "text/plain", 43).await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
         let (events, event_client) = mock_event_emitter();
 
-        let result = create_file_version(
-            NewFileVersion {
-                file: file_id.into(),
-                latest_version: current_file.version.into(),
-                title: Some("title".into()),
-                description: None,
-                folder: Some("d890181d-6b17-428e-896b-f76add15b54a".into()),
-                file_name: Some("file.txt".into()),
-                file_type: Some("text/plain".into()),
-                temporary_blob_storage_path: Some(
-                    "http://localhost:10000/devstoreaccount1/upload/new-fake".into(),
-                ),
+        let result = create_file(
+            NewFile {
+                title: "title".into(),
+                description: "description".into(),
+                folder: folder.id.into(),
+                file_name: "file.txt".into(),
+                file_type: "text/plain".into(),
+                temporary_blob_storage_path: upload,
             },
             &pool,
             &azure_config,
             &requesting_user,
             &event_client,
         )
-        .await
-        .unwrap();
+        .await;
 
-        assert_eq!(result.title, "title");
-        assert_eq!(result.description, "fake file for tests");
-        assert_eq!(result.folder, "d890181d-6b17-428e-896b-f76add15b54a");
-        assert_eq!(result.file_name, "file.txt");
-        assert_eq!(result.file_type, "text/plain");
-        assert!(events
-            .try_iter()
-            .any(|e| matches!(e.data, EventData::FileUpdated(_))));
+        assert_eq!(
+            result.err().unwrap().message,
+            "the uploaded file is 42 bytes, but 43 bytes were declared"
+        );
+        assert_eq!(0, events.try_iter().count());
 
         Ok(())
     }
 
     #[async_std::test]
-    async fn create_file_version_fails_if_latest_version_mismatch() -> anyhow::Result<()> {
+    async fn create_file_fails_if_type_does_not_match_upload() -> anyhow::Result<()> {
         let pool = mock_connection_pool()?;
         let azure_config = mock_azure_config()?;
         let requesting_user = mock_unprivileged_requesting_user().await?;
+        let upload = mock_upload(&requesting_user, "text/csv", 42).await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
         let (events, event_client) = mock_event_emitter();
 
-        let file_id = Uuid::new_v4();
-        let result = create_file_version(
-            NewFileVersion {
-                file: file_id.into(),
-                latest_version: Uuid::new_v4().into(),
-                title: Some("title".into()),
-                description: None,
-                folder: None,
-                file_name: None,
-                file_type: None,
-                temporary_blob_storage_path: None,
+        let result = create_file(
+            NewFile {
+                title: "title".into(),
+                description: "description".into(),
+                folder: folder.id.into(),
+                file_name: "file.txt".into(),
+                file_type: "text/plain".into(),
+                temporary_blob_storage_path: upload,
             },
             &pool,
             &azure_config,
@@ -623,10 +1594,939 @@ mod test {
 
         assert_eq!(
             result.err().unwrap().message,
-            "specified version is not the latest version of the file"
+            "the file type text/plain does not match the type text/csv declared for the upload"
         );
-
         assert_eq!(0, events.try_iter().count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_file_fails_if_content_does_not_match_type() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let upload =
+            mock_upload_with_contents(&requesting_user, "application/pdf", b"MZ\x90\0\x03\0\0\0")
+                .await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = create_file(
+            NewFile {
+                title: "title".into(),
+                description: "description".into(),
+                folder: folder.id.into(),
+                file_name: "file.pdf".into(),
+                file_type: "application/pdf".into(),
+                temporary_blob_storage_path: upload,
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "the content of the file was detected as Windows executable, which does not match \
+             the MIME type application/pdf"
+        );
+        let events: Vec<_> = events.try_iter().collect();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            &events[0].data,
+            EventData::FileRejected(data) if data.detected_type == "Windows executable"
+        ));
+
+        Ok(())
+    }
+
+    /// A blob store whose uploads are overwritten with other contents just before they are
+    /// copied, like a user could do with an upload URL that has not expired yet.
+    struct OverwrittenUploads {
+        azure_config: azure::Config,
+        contents: Vec<u8>,
+        deleted: std::sync::Mutex<Vec<Url>>,
+    }
+
+    #[async_trait::async_trait]
+    impl BlobStore for OverwrittenUploads {
+        fn create_upload_url(&self, name: &Uuid) -> anyhow::Result<Url> {
+            self.azure_config.create_upload_url(name)
+        }
+
+        fn create_download_url(&self, url: &Url) -> anyhow::Result<Url> {
+            self.azure_config.create_download_url(url)
+        }
+
+        async fn upload_properties(&self, url: &Url) -> anyhow::Result<BlobProperties> {
+            self.azure_config.upload_properties(url).await
+        }
+
+        async fn open_file(&self, url: &Url) -> anyhow::Result<crate::blob_store::BlobReader> {
+            self.azure_config.open_file(url).await
+        }
+
+        async fn copy_upload(&self, url: &Url) -> anyhow::Result<String> {
+            let properties = self.azure_config.upload_properties(url).await?;
+            let content_type = properties.content_type.unwrap_or_default();
+            azure::put_fake_upload(url, &content_type, &self.contents);
+            self.azure_config.copy_upload(url).await
+        }
+
+        async fn delete_blob(&self, url: &Url) -> anyhow::Result<()> {
+            self.deleted.lock().unwrap().push(url.clone());
+            self.azure_config.delete_blob(url).await
+        }
+
+        async fn find_uploads_modified_before(
+            &self,
+            before: DateTime<Utc>,
+        ) -> anyhow::Result<Vec<Url>> {
+            self.azure_config.find_uploads_modified_before(before).await
+        }
+    }
+
+    #[async_std::test]
+    async fn create_file_fails_if_upload_is_overwritten_before_it_is_copied() -> anyhow::Result<()>
+    {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let pdf = b"%PDF-1.4\n%%EOF\n";
+        let upload = mock_upload_with_contents(&requesting_user, "application/pdf", pdf).await?;
+        let blob_store = OverwrittenUploads {
+            azure_config: mock_azure_config()?,
+            contents: b"MZ\x90\0\x03\0\0\0\0\0\0\0\0\0\0".to_vec(),
+            deleted: Default::default(),
+        };
+        assert_eq!(blob_store.contents.len(), pdf.len());
+        let (events, event_client) = mock_event_emitter();
+
+        let result = create_file(
+            NewFile {
+                title: "title".into(),
+                description: "description".into(),
+                folder: folder.id.into(),
+                file_name: "file.pdf".into(),
+                file_type: "application/pdf".into(),
+                temporary_blob_storage_path: upload.clone(),
+            },
+            &pool,
+            &blob_store,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "the content of the file was detected as Windows executable, which does not match \
+             the MIME type application/pdf"
+        );
+        assert!(events
+            .try_iter()
+            .any(|e| matches!(e.data, EventData::FileRejected(_))));
+        let copy = format!(
+            "http://localhost:10000/devstoreaccount1/files/{}",
+            upload_name(&Url::parse(&upload)?)?
+        );
+        assert_eq!(
+            *blob_store.deleted.lock().unwrap(),
+            vec![Url::parse(&copy)?]
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_file_fails_if_upload_was_requested_by_another_user() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let admin = mock_admin_requesting_user().await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let upload = mock_upload(&admin, "text/plain", 42).await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = create_file(
+            NewFile {
+                title: "title".into(),
+                description: "description".into(),
+                folder: folder.id.into(),
+                file_name: "file.txt".into(),
+                file_type: "text/plain".into(),
+                temporary_blob_storage_path: upload,
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "the upload URL was not requested by this user"
+        );
+        assert_eq!(0, events.try_iter().count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_file_fails_if_not_a_workspace_member() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = create_file(
+            NewFile {
+                title: "title".into(),
+                description: "description".into(),
+                folder: folder.id.into(),
+                file_name: "file.txt".into(),
+                file_type: "text/plain".into(),
+                temporary_blob_storage_path: "http://localhost:10000/devstoreaccount1/upload/fake"
+                    .into(),
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "user with auth_id {} does not have permission to write to folder {}",
+                requesting_user.auth_id, folder.id
+            )
+        );
+        assert_eq!(0, events.try_iter().count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn files_by_folder_works() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        mock_file(&folder, &requesting_user).await?;
+        let (_, event_client) = mock_event_emitter();
+
+        let result = files_by_folder(
+            folder.id.into(),
+            Page::new(None, Some(1))?,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_ok());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn files_by_folder_fails_if_not_a_workspace_member() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+        let (_, event_client) = mock_event_emitter();
+
+        let result = files_by_folder(
+            folder.id.into(),
+            Page::new(None, None)?,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_fails_if_not_a_workspace_member() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let admin = mock_admin_requesting_user().await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+        let file = mock_file(&folder, &admin).await?;
+        let (_, event_client) = mock_event_emitter();
+
+        let result = get_file(file.id.into(), &pool, &requesting_user, &event_client).await;
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn file_versions_are_listed_newest_first() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+        let (_, event_client) = mock_event_emitter();
+
+        create_file_version(
+            NewFileVersion {
+                file: file.id.into(),
+                latest_version: file.version.into(),
+                title: Some("title".into()),
+                description: None,
+                folder: None,
+                file_name: None,
+                file_type: None,
+                temporary_blob_storage_path: None,
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        let versions = file_versions(file.id, &pool, &requesting_user, &event_client)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            versions
+                .iter()
+                .map(|version| version.version_number)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert!(versions
+            .iter()
+            .all(|version| version.file == ID::from(file.id)));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn duplicates_lists_other_visible_files_with_the_same_hash() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let admin = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let members_only = db::FolderRepo::create(
+            "members only",
+            "",
+            "WORKSPACE_MEMBER",
+            folder.workspace,
+            None,
+            &pool,
+        )
+        .await?;
+        let other_workspace = mock_folder("PLATFORM_MEMBER").await?;
+        let (_, event_client) = mock_event_emitter();
+
+        let file = mock_file(&folder, &requesting_user).await?;
+        let duplicate = mock_file(&folder, &requesting_user).await?;
+        let hidden_duplicate = mock_file(&members_only, &requesting_user).await?;
+        let deleted_duplicate = mock_file(&folder, &requesting_user).await?;
+        db::FileWithVersionRepo::delete(deleted_duplicate.id, Uuid::new_v4(), &pool).await?;
+        mock_file(&other_workspace, &requesting_user).await?;
+        mock_file_with_hash(&folder, &requesting_user, "other contents").await?;
+
+        let result = duplicates(file.id, &pool, &requesting_user, &event_client)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.iter().map(|f| f.id.clone()).collect::<Vec<_>>(),
+            vec![ID::from(duplicate.id)]
+        );
+
+        let mut result: Vec<ID> = duplicates(file.id, &pool, &admin, &event_client)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|f| f.id)
+            .collect();
+        result.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        let mut expected = vec![ID::from(duplicate.id), ID::from(hidden_duplicate.id)];
+        expected.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        assert_eq!(result, expected);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn duplicates_fails_if_not_a_workspace_member() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+        let (_, event_client) = mock_event_emitter();
+
+        let result = duplicates(file.id, &pool, &requesting_user, &event_client).await;
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn delete_file_works() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = delete_file(file.id.into(), &pool, &requesting_user, &event_client).await;
+
+        assert_eq!(result.unwrap().id, ID::from(file.id));
+        assert!(events
+            .try_iter()
+            .any(|e| matches!(e.data, EventData::FileDeleted(_))));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn delete_file_fails_if_not_a_workspace_member() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let admin = mock_admin_requesting_user().await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let file = mock_file(&folder, &admin).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = delete_file(file.id.into(), &pool, &requesting_user, &event_client).await;
+
+        assert!(result.is_err());
+        assert!(db::FileWithVersionRepo::find_by_id(file.id, &pool)
+            .await?
+            .deleted_at
+            .is_none());
+        assert_eq!(0, events.try_iter().count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn deleted_files_works() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+        let (_, event_client) = mock_event_emitter();
+        delete_file(file.id.into(), &pool, &requesting_user, &event_client)
+            .await
+            .unwrap();
+
+        let result = deleted_files(
+            folder.workspace.into(),
+            Page::new(None, None)?,
+            &pool,
+            &requesting_user,
+        )
+        .await;
+
+        assert!(result.is_ok());
+        let deleted =
+            db::FileWithVersionRepo::count_deleted_by_workspace(folder.workspace, &pool).await?;
+        assert_eq!(deleted, 1);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn deleted_files_fails_if_not_a_workspace_admin() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+
+        let result = deleted_files(
+            folder.workspace.into(),
+            Page::new(None, None)?,
+            &pool,
+            &requesting_user,
+        )
+        .await;
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restore_file_works() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+        delete_file(file.id.into(), &pool, &requesting_user, &event_client)
+            .await
+            .unwrap();
+
+        let result = restore_file(file.id.into(), &pool, &requesting_user, &event_client).await;
+
+        assert_eq!(result.unwrap().deleted_at, None);
+        assert!(db::FileWithVersionRepo::find_deleted_by_id(file.id, &pool)
+            .await?
+            .is_none());
+        assert!(events
+            .try_iter()
+            .any(|e| matches!(e.data, EventData::FileRestored(_))));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restore_file_fails_if_not_a_workspace_admin() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+        delete_file(file.id.into(), &pool, &requesting_user, &event_client)
+            .await
+            .unwrap();
+
+        let result = restore_file(file.id.into(), &pool, &requesting_user, &event_client).await;
+
+        assert!(result.is_err());
+        assert!(!events
+            .try_iter()
+            .any(|e| matches!(e.data, EventData::FileRestored(_))));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restore_file_fails_if_folder_has_been_deleted() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+        let (_, event_client) = mock_event_emitter();
+        delete_file(file.id.into(), &pool, &requesting_user, &event_client)
+            .await
+            .unwrap();
+        let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+            .await?
+            .unwrap();
+        db::FolderRepo::delete(folder.id, user.id, &pool).await?;
+
+        let result = restore_file(file.id.into(), &pool, &requesting_user, &event_client).await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "cannot restore file {} because its folder {} has been deleted",
+                file.id, folder.id
+            )
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_file_version_works() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let new_folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let current_file = mock_file(&folder, &requesting_user).await?;
+        let upload = mock_upload(&requesting_user, "text/plain", 42).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = create_file_version(
+            NewFileVersion {
+                file: current_file.id.into(),
+                latest_version: current_file.version.into(),
+                title: Some("title".into()),
+                description: None,
+                folder: Some(new_folder.id.into()),
+                file_name: Some("file.txt".into()),
+                file_type: Some("text/plain".into()),
+                temporary_blob_storage_path: Some(upload),
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.title, "title");
+        assert_eq!(result.description, "fake file for tests");
+        assert_eq!(result.folder, ID::from(new_folder.id));
+        assert_eq!(result.file_name, "file.txt");
+        assert_eq!(result.file_type, "text/plain");
+        assert_eq!(result.scan_status, ScanStatus::Pending);
+        assert!(events
+            .try_iter()
+            .any(|e| matches!(e.data, EventData::FileUpdated(_))));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_file_version_without_upload_keeps_scan_status() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let current_file =
+            mock_scanned_file(&folder, &requesting_user, db::ScanStatus::Clean).await?;
+        let (_, event_client) = mock_event_emitter();
+
+        let result = create_file_version(
+            NewFileVersion {
+                file: current_file.id.into(),
+                latest_version: current_file.version.into(),
+                title: Some("new title".into()),
+                description: None,
+                folder: None,
+                file_name: None,
+                file_type: None,
+                temporary_blob_storage_path: None,
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.title, "new title");
+        assert_eq!(result.scan_status, ScanStatus::Clean);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_file_version_fails_if_target_folder_not_writable() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let new_folder = mock_folder("PLATFORM_MEMBER").await?;
+        let current_file = mock_file(&folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = create_file_version(
+            NewFileVersion {
+                file: current_file.id.into(),
+                latest_version: current_file.version.into(),
+                title: None,
+                description: None,
+                folder: Some(new_folder.id.into()),
+                file_name: None,
+                file_type: None,
+                temporary_blob_storage_path: None,
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(0, events.try_iter().count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_file_version_fails_if_latest_version_mismatch() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let current_file = mock_file(&folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = create_file_version(
+            NewFileVersion {
+                file: current_file.id.into(),
+                latest_version: Uuid::new_v4().into(),
+                title: Some("title".into()),
+                description: None,
+                folder: None,
+                file_name: None,
+                file_type: None,
+                temporary_blob_storage_path: None,
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "specified version is not the latest version of the file"
+        );
+
+        assert_eq!(0, events.try_iter().count());
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restore_file_version_works() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let first_version = mock_file(&folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let current_file = create_file_version(
+            NewFileVersion {
+                file: first_version.id.into(),
+                latest_version: first_version.version.into(),
+                title: Some("new title".into()),
+                description: None,
+                folder: None,
+                file_name: Some("new.txt".into()),
+                file_type: Some("text/plain".into()),
+                temporary_blob_storage_path: None,
+            },
+            &pool,
+            &azure_config,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        let result = restore_file_version(
+            first_version.id.into(),
+            first_version.version.into(),
+            current_file.latest_version,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.title, "fake file");
+        assert_eq!(result.file_name, "fake.txt");
+        assert_eq!(result.folder, ID::from(folder.id));
+        assert_eq!(
+            events
+                .try_iter()
+                .filter(|e| matches!(e.data, EventData::FileUpdated(_)))
+                .count(),
+            2
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restore_file_version_fails_if_version_belongs_to_another_file() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let current_file = mock_file(&folder, &requesting_user).await?;
+        let other_file = mock_file(&folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = restore_file_version(
+            current_file.id.into(),
+            other_file.version.into(),
+            current_file.version.into(),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "specified version is not a version of the file"
+        );
+        assert_eq!(0, events.try_iter().count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restore_file_version_fails_if_latest_version_mismatch() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+        let current_file = mock_file(&folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = restore_file_version(
+            current_file.id.into(),
+            current_file.version.into(),
+            Uuid::new_v4().into(),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            "specified version is not the latest version of the file"
+        );
+        assert_eq!(0, events.try_iter().count());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_files_works() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let source_folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let target_folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let file = mock_file(&source_folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let moved = move_files(
+            vec![file.id.into()],
+            target_folder.id.into(),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(moved[0].folder, ID::from(target_folder.id));
+        let file = db::FileWithVersionRepo::find_by_id(file.id, &pool).await?;
+        assert_eq!(file.folder, target_folder.id);
+        assert!(events.try_iter().any(|e| matches!(
+            e.data,
+            EventData::FileMoved(ref data)
+                if data.old_folder_id == source_folder.id.to_string()
+                    && data.new_workspace_id == target_folder.workspace.to_string()
+        )));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_files_leaves_earlier_versions_in_their_folder() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let source_folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let target_folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let file = mock_file(&source_folder, &requesting_user).await?;
+        let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+            .await?
+            .unwrap();
+        let latest = db::FileWithVersionRepo::create_version(
+            db::CreateFileVersionArgs {
+                user_id: user.id,
+                file_id: file.id,
+                latest_version: file.version,
+                folder_id: source_folder.id,
+                title: &file.title,
+                description: &file.description,
+                file_name: &file.file_name,
+                file_type: &file.file_type,
+                blob_storage_path: &file.blob_storage_path,
+                version_number: file.version_number + 1,
+                scan_status: db::ScanStatus::Clean,
+                sha256: file.sha256.as_deref(),
+                file_size: file.file_size,
+                page_count: file.page_count,
+            },
+            &pool,
+        )
+        .await?;
+        let (_, event_client) = mock_event_emitter();
+
+        move_files(
+            vec![file.id.into()],
+            target_folder.id.into(),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        let first = db::FileVersionRepo::find_by_id(file.version, &pool).await?;
+        assert_eq!(first.folder, source_folder.id);
+        let latest = db::FileVersionRepo::find_by_id(latest.version, &pool).await?;
+        assert_eq!(latest.folder, target_folder.id);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_files_fails_if_target_folder_not_writable() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let source_folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let target_folder = mock_folder("PLATFORM_MEMBER").await?;
+        let file = mock_file(&source_folder, &requesting_user).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = move_files(
+            vec![file.id.into()],
+            target_folder.id.into(),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_err());
+        let file = db::FileWithVersionRepo::find_by_id(file.id, &pool).await?;
+        assert_eq!(file.folder, source_folder.id);
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_files_fails_if_source_folder_not_writable() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let admin = mock_admin_requesting_user().await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let source_folder = mock_folder("PLATFORM_MEMBER").await?;
+        let target_folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let file = mock_file(&source_folder, &admin).await?;
+        let (events, event_client) = mock_event_emitter();
+
+        let result = move_files(
+            vec![file.id.into()],
+            target_folder.id.into(),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_err());
+        let file = db::FileWithVersionRepo::find_by_id(file.id, &pool).await?;
+        assert_eq!(file.folder, source_folder.id);
+        assert_eq!(events.try_iter().count(), 0);
+
         Ok(())
     }
 }
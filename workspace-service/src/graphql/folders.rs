@@ -1,17 +1,24 @@
-use super::{db, RequestingUser};
-use crate::graphql::workspaces::{requesting_user_workspace_rights, WorkspaceMembership};
-use async_graphql::{
-    Context, Enum, Error, ErrorExtensions, FieldResult, InputObject, Object, SimpleObject, ID,
+use super::{
+    authorization::{require_folder_access, FolderAccess},
+    db,
+    pagination::{Cursor, Page, PaginatedConnection},
+    RequestingUser,
 };
+use crate::graphql::workspaces::{
+    requesting_user_workspace_rights, require_workspace_admin, WorkspaceMembership,
+};
+use async_graphql::{Context, Enum, ErrorExtensions, FieldResult, InputObject, Object, ID};
+use chrono::{DateTime, Utc};
 use fnhs_event_models::{
-    Event, EventClient, EventPublisher, FolderCreatedData, FolderDeletedData, FolderUpdatedData,
+    Event, EventClient, EventPublisher, FolderCreatedData, FolderDeletedData, FolderRestoredData,
+    FolderUpdatedData,
 };
 use sqlx::PgPool;
 use std::fmt::Display;
 use std::str::FromStr;
 use uuid::Uuid;
 #[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
-enum RoleRequired {
+pub enum RoleRequired {
     PlatformMember,
     WorkspaceMember,
 }
@@ -40,19 +47,83 @@ impl FromStr for RoleRequired {
     }
 }
 
-/// A folder
-#[derive(SimpleObject)]
 pub struct Folder {
-    /// The id of the folder
     id: ID,
-    /// The title of the folder
     title: String,
-    /// The description of the folder
     description: String,
-    /// The group that can access the folder
     role_required: RoleRequired,
-    /// The workspace that this folder is in
     workspace: ID,
+    parent: Option<ID>,
+    deleted_at: Option<DateTime<Utc>>,
+}
+
+#[Object]
+/// A folder
+impl Folder {
+    /// The id of the folder
+    async fn id(&self) -> ID {
+        self.id.clone()
+    }
+    /// The title of the folder
+    async fn title(&self) -> String {
+        self.title.clone()
+    }
+    /// The description of the folder
+    async fn description(&self) -> String {
+        self.description.clone()
+    }
+    /// The group that can access the folder. A folder requires at least the role required by
+    /// the folders that it is in.
+    async fn role_required(&self) -> RoleRequired {
+        self.role_required
+    }
+    /// The workspace that this folder is in
+    async fn workspace(&self) -> ID {
+        self.workspace.clone()
+    }
+    /// The folder that this folder is in, if it is not at the top level of the workspace
+    async fn parent(&self, context: &Context<'_>) -> FieldResult<Option<Folder>> {
+        let pool = context.data()?;
+        match &self.parent {
+            Some(parent) => Ok(Some(
+                db::FolderRepo::find_by_id(Uuid::parse_str(parent)?, pool)
+                    .await?
+                    .into(),
+            )),
+            None => Ok(None),
+        }
+    }
+    /// The folders in this folder, ordered by title
+    async fn children(
+        &self,
+        context: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> FieldResult<PaginatedConnection<Folder>> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        folders_by_workspace(
+            Uuid::parse_str(&self.workspace)?,
+            Some(Uuid::parse_str(&self.id)?),
+            Page::new(after, first)?,
+            pool,
+            requesting_user,
+            event_client,
+        )
+        .await
+    }
+    /// When the folder was deleted, if it has been deleted
+    async fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at
+    }
+    /// The folders that this folder is in, starting at the top level of the workspace
+    async fn breadcrumbs(&self, context: &Context<'_>) -> FieldResult<Vec<Folder>> {
+        let pool = context.data()?;
+        let ancestors = db::FolderRepo::ancestors(Uuid::parse_str(&self.id)?, pool).await?;
+        Ok(ancestors.into_iter().map(Into::into).collect())
+    }
 }
 
 impl From<db::Folder> for Folder {
@@ -63,6 +134,8 @@ impl From<db::Folder> for Folder {
             description: d.description,
             role_required: RoleRequired::from_str(&d.role_required).unwrap(),
             workspace: d.workspace.into(),
+            parent: d.parent.map(Into::into),
+            deleted_at: d.deleted_at,
         }
     }
 }
@@ -73,6 +146,8 @@ struct NewFolder {
     description: String,
     role_required: RoleRequired,
     workspace: ID,
+    /// The folder to create this folder in. Leave out to create a top-level folder.
+    parent: Option<ID>,
 }
 
 #[derive(InputObject)]
@@ -88,28 +163,49 @@ pub struct FoldersQuery;
 
 #[Object]
 impl FoldersQuery {
-    /// Get all Folders in a workspace
+    /// Get the top-level Folders in a workspace, ordered by title
     async fn folders_by_workspace(
         &self,
         context: &Context<'_>,
         workspace: ID,
-    ) -> FieldResult<Vec<Folder>> {
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> FieldResult<PaginatedConnection<Folder>> {
         let pool = context.data()?;
-        let workspace = Uuid::parse_str(&workspace)?;
         let requesting_user = context.data()?;
-        let event_client: &EventClient = context.data()?;
-        let folders = db::FolderRepo::find_by_workspace(workspace, pool).await?;
-        let user_role =
-            requesting_user_workspace_rights(workspace, requesting_user, pool, event_client)
-                .await?;
-        Ok(folders
-            .into_iter()
-            .map(Into::into)
-            .filter(|folder: &Folder| {
-                !(folder.role_required == RoleRequired::WorkspaceMember
-                    && user_role == WorkspaceMembership::NonMember)
-            })
-            .collect())
+        let event_client = context.data()?;
+
+        folders_by_workspace(
+            Uuid::parse_str(&workspace)?,
+            None,
+            Page::new(after, first)?,
+            pool,
+            requesting_user,
+            event_client,
+        )
+        .await
+    }
+
+    /// Get the folders that have been deleted from a workspace, most recently deleted first.
+    /// Folders that were deleted with the folder they were in are not included. Only workspace
+    /// admins can see these.
+    async fn deleted_folders(
+        &self,
+        context: &Context<'_>,
+        workspace: ID,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> FieldResult<PaginatedConnection<Folder>> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+
+        deleted_folders(
+            Uuid::parse_str(&workspace)?,
+            Page::new(after, first)?,
+            pool,
+            requesting_user,
+        )
+        .await
     }
 
     /// Get folder by ID
@@ -120,21 +216,10 @@ impl FoldersQuery {
     #[graphql(entity)]
     async fn get_folder(&self, context: &Context<'_>, id: ID) -> FieldResult<Folder> {
         let pool = context.data()?;
-        let id = Uuid::parse_str(&id)?;
         let requesting_user = context.data()?;
-        let event_client: &EventClient = context.data()?;
-        let folder = db::FolderRepo::find_by_id(id, pool).await?;
-        let user_rights =
-            requesting_user_workspace_rights(folder.workspace, requesting_user, pool, event_client)
-                .await?;
-        if folder.role_required == "WORKSPACE_MEMBER"
-            && user_rights == WorkspaceMembership::NonMember
-        {
-            Err(Error::new("Insufficient permissions: access denied")
-                .extend_with(|_, e| e.set("details", "ACCESS_DENIED")))
-        } else {
-            Ok(folder.into())
-        }
+        let event_client = context.data()?;
+
+        get_folder(id, pool, requesting_user, event_client).await
     }
 }
 
@@ -144,6 +229,8 @@ pub struct FoldersMutation;
 #[Object]
 impl FoldersMutation {
     /// Create a new folder (returns the created folder)
+    ///
+    /// Creating a folder in another folder requires write access to that folder.
     async fn create_folder(
         &self,
         context: &Context<'_>,
@@ -151,13 +238,18 @@ impl FoldersMutation {
     ) -> FieldResult<Folder> {
         let pool = context.data()?;
         let workspace = Uuid::parse_str(&new_folder.workspace)?;
+        let parent = new_folder
+            .parent
+            .map(|parent| Uuid::parse_str(&parent))
+            .transpose()?;
         let event_client = context.data()?;
         let requesting_user = context.data()?;
         create_folder(
             &new_folder.title,
             &new_folder.description,
-            &new_folder.role_required.to_string(),
+            new_folder.role_required,
             workspace,
+            parent,
             pool,
             requesting_user,
             event_client,
@@ -178,29 +270,194 @@ impl FoldersMutation {
         update_folder(folder, pool, requesting_user, event_client).await
     }
 
+    /// Move a folder into another folder in its workspace, or to the top level of the workspace
+    /// if `parent` is null (returns the moved folder)
+    ///
+    /// Requires write access to the folder and to the folder it is moved into. The folder keeps
+    /// the role it required, unless it now has to require workspace membership because the folder
+    /// it was moved into does.
+    async fn move_folder(
+        &self,
+        context: &Context<'_>,
+        id: ID,
+        parent: Option<ID>,
+    ) -> FieldResult<Folder> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+        let parent = parent.map(|parent| Uuid::parse_str(&parent)).transpose()?;
+
+        move_folder(
+            Uuid::parse_str(&id)?,
+            parent,
+            pool,
+            requesting_user,
+            event_client,
+        )
+        .await
+    }
+
     /// Delete folder (returns deleted folder)
-    async fn delete_folder(&self, context: &Context<'_>, id: ID) -> FieldResult<Folder> {
+    ///
+    /// Only workspace admins can delete folders. A folder that contains files or folders can only
+    /// be deleted with `recursive: true`, which also deletes everything in it. Deleted folders can
+    /// be restored with `restoreFolder`.
+    async fn delete_folder(
+        &self,
+        context: &Context<'_>,
+        id: ID,
+        recursive: Option<bool>,
+    ) -> FieldResult<Folder> {
         let pool = context.data()?;
         let requesting_user = context.data()?;
         let event_client = context.data()?;
 
-        delete_folder(id, pool, requesting_user, event_client).await
+        delete_folder(
+            id,
+            recursive.unwrap_or(false),
+            pool,
+            requesting_user,
+            event_client,
+        )
+        .await
+    }
+
+    /// Restores a deleted folder, and everything that was deleted with it (returns the restored
+    /// folder)
+    async fn restore_folder(&self, context: &Context<'_>, id: ID) -> FieldResult<Folder> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let event_client = context.data()?;
+
+        restore_folder(id, pool, requesting_user, event_client).await
+    }
+}
+
+async fn folders_by_workspace(
+    workspace: Uuid,
+    parent: Option<Uuid>,
+    page: Page,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<PaginatedConnection<Folder>> {
+    let user_role =
+        requesting_user_workspace_rights(workspace, requesting_user, pool, event_client).await?;
+    let include_workspace_member_folders = user_role != WorkspaceMembership::NonMember;
+
+    let folders = db::FolderRepo::find_by_workspace(
+        workspace,
+        parent,
+        include_workspace_member_folders,
+        page.args(),
+        pool,
+    )
+    .await?;
+    let total_count = db::FolderRepo::count_by_workspace(
+        workspace,
+        parent,
+        include_workspace_member_folders,
+        pool,
+    )
+    .await?;
+
+    Ok(page.connection(folders, total_count, |folder| Cursor {
+        sort_key: folder.title.clone(),
+        id: folder.id,
+    }))
+}
+
+async fn get_folder(
+    id: ID,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<Folder> {
+    let folder = db::FolderRepo::find_by_id(Uuid::parse_str(&id)?, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Read,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    Ok(folder.into())
+}
+
+/// Checks that a folder in `workspace` can be put in `parent`, and returns the role that the
+/// folder requires once it inherits the role required by `parent`.
+///
+/// Pass the id of the folder if it already exists, to check that it is not being moved into
+/// itself or one of its subfolders.
+async fn inherit_from_parent(
+    folder: Option<Uuid>,
+    workspace: Uuid,
+    parent: &db::Folder,
+    role_required: RoleRequired,
+    pool: &PgPool,
+) -> FieldResult<RoleRequired> {
+    if parent.workspace != workspace {
+        return Err(
+            anyhow::anyhow!("folder {} is not in workspace {}", parent.id, workspace).into(),
+        );
+    }
+
+    if let Some(folder) = folder {
+        let ancestors = db::FolderRepo::ancestors(parent.id, pool).await?;
+        if parent.id == folder || ancestors.iter().any(|ancestor| ancestor.id == folder) {
+            return Err(anyhow::anyhow!(
+                "folder {} cannot be moved into itself or one of its subfolders",
+                folder
+            )
+            .into());
+        }
+    }
+
+    // the stored role of the parent already includes the roles of its own ancestors
+    if parent.role_required == RoleRequired::WorkspaceMember.to_string() {
+        Ok(RoleRequired::WorkspaceMember)
+    } else {
+        Ok(role_required)
     }
 }
 
 async fn create_folder(
     title: &str,
     description: &str,
-    role_required: &str,
+    role_required: RoleRequired,
     workspace: Uuid,
+    parent: Option<Uuid>,
     pool: &PgPool,
     requesting_user: &RequestingUser,
     event_client: &EventClient,
 ) -> FieldResult<Folder> {
-    let folder: Folder =
-        db::FolderRepo::create(&title, &description, &role_required, workspace, pool)
-            .await?
-            .into();
+    let role_required = match parent {
+        Some(parent) => {
+            let parent = db::FolderRepo::find_by_id(parent, pool).await?;
+            require_folder_access(
+                &parent,
+                FolderAccess::Write,
+                requesting_user,
+                pool,
+                event_client,
+            )
+            .await?;
+            inherit_from_parent(None, workspace, &parent, role_required, pool).await?
+        }
+        None => role_required,
+    };
+    let folder: Folder = db::FolderRepo::create(
+        &title,
+        &description,
+        &role_required.to_string(),
+        workspace,
+        parent,
+        pool,
+    )
+    .await?
+    .into();
 
     let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
         .await?
@@ -216,6 +473,7 @@ async fn create_folder(
                 title: folder.title.clone(),
                 description: folder.description.clone(),
                 role_required: folder.role_required.to_string(),
+                parent_folder_id: folder.parent.clone().map(Into::into),
             },
         )])
         .await?;
@@ -228,11 +486,28 @@ async fn update_folder(
     requesting_user: &RequestingUser,
     event_client: &EventClient,
 ) -> FieldResult<Folder> {
+    let id = Uuid::parse_str(&folder.id)?;
+    let existing_folder = db::FolderRepo::find_by_id(id, pool).await?;
+    let role_required = match existing_folder.parent {
+        Some(parent) => {
+            let parent = db::FolderRepo::find_by_id(parent, pool).await?;
+            inherit_from_parent(
+                None,
+                existing_folder.workspace,
+                &parent,
+                folder.role_required,
+                pool,
+            )
+            .await?
+        }
+        None => folder.role_required,
+    };
+
     let updated_folder = db::FolderRepo::update(
-        Uuid::parse_str(&folder.id)?,
+        id,
         &folder.title,
         &folder.description,
-        &folder.role_required.to_string(),
+        &role_required.to_string(),
         pool,
     )
     .await?;
@@ -251,6 +526,7 @@ async fn update_folder(
                 description: updated_folder.description.to_string(),
                 user_id: user.id.to_string(),
                 role_required: updated_folder.role_required.to_string(),
+                parent_folder_id: updated_folder.parent.map(|parent| parent.to_string()),
             },
         )])
         .await?;
@@ -258,16 +534,124 @@ async fn update_folder(
     Ok(updated_folder.into())
 }
 
-async fn delete_folder(
-    id: ID,
+async fn move_folder(
+    id: Uuid,
+    parent: Option<Uuid>,
     pool: &PgPool,
     requesting_user: &RequestingUser,
     event_client: &EventClient,
 ) -> FieldResult<Folder> {
-    let folder = db::FolderRepo::delete(Uuid::parse_str(&id)?, pool).await?;
+    let folder = db::FolderRepo::find_by_id(id, pool).await?;
+    require_folder_access(
+        &folder,
+        FolderAccess::Write,
+        requesting_user,
+        pool,
+        event_client,
+    )
+    .await?;
+
+    let role_required = RoleRequired::from_str(&folder.role_required).map_err(|_| {
+        anyhow::anyhow!(
+            "folder {} has an unknown role_required {}",
+            folder.id,
+            folder.role_required
+        )
+    })?;
+    let role_required = match parent {
+        Some(parent) => {
+            let parent = db::FolderRepo::find_by_id(parent, pool).await?;
+            require_folder_access(
+                &parent,
+                FolderAccess::Write,
+                requesting_user,
+                pool,
+                event_client,
+            )
+            .await?;
+            inherit_from_parent(Some(id), folder.workspace, &parent, role_required, pool).await?
+        }
+        None => role_required,
+    };
+
+    let moved_folder =
+        db::FolderRepo::move_to(id, parent, &role_required.to_string(), pool).await?;
+
     let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
         .await?
         .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+    event_client
+        .publish_events(&[Event::new(
+            moved_folder.id.to_string(),
+            FolderUpdatedData {
+                folder_id: moved_folder.id.to_string(),
+                workspace_id: moved_folder.workspace.to_string(),
+                title: moved_folder.title.to_string(),
+                description: moved_folder.description.to_string(),
+                user_id: user.id.to_string(),
+                role_required: moved_folder.role_required.to_string(),
+                parent_folder_id: moved_folder.parent.map(|parent| parent.to_string()),
+            },
+        )])
+        .await?;
+
+    Ok(moved_folder.into())
+}
+
+async fn deleted_folders(
+    workspace: Uuid,
+    page: Page,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+) -> FieldResult<PaginatedConnection<Folder>> {
+    require_workspace_admin(workspace, "list deleted folders", requesting_user, pool).await?;
+
+    let folders = db::FolderRepo::find_deleted_by_workspace(workspace, page.args(), pool).await?;
+    let total_count = db::FolderRepo::count_deleted_by_workspace(workspace, pool).await?;
+
+    Ok(page.connection(folders, total_count, |folder| Cursor {
+        sort_key: folder
+            .deleted_at
+            .map(|deleted_at| deleted_at.to_rfc3339())
+            .unwrap_or_default(),
+        id: folder.id,
+    }))
+}
+
+async fn delete_folder(
+    id: ID,
+    recursive: bool,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<Folder> {
+    let folder = db::FolderRepo::find_by_id(Uuid::parse_str(&id)?, pool).await?;
+    let user =
+        require_workspace_admin(folder.workspace, "delete folder", requesting_user, pool).await?;
+
+    if !recursive {
+        let files = db::FileWithVersionRepo::count_by_folder(folder.id, pool).await?;
+        let folders =
+            db::FolderRepo::count_by_workspace(folder.workspace, Some(folder.id), true, pool)
+                .await?;
+        if files > 0 || folders > 0 {
+            return Err(anyhow::anyhow!(
+                "folder {} is not empty and recursive was not specified",
+                folder.id
+            )
+            .extend_with(|_, extensions| {
+                extensions.set("problem", "This folder contains files or folders.");
+                extensions.set(
+                    "suggestion",
+                    "Please delete or move its contents first, or delete it recursively.",
+                );
+            }));
+        }
+    }
+
+    let (folder, file_count) = db::FolderRepo::delete(folder.id, user.id, pool).await?;
+
     event_client
         .publish_events(&[Event::new(
             id,
@@ -275,6 +659,52 @@ async fn delete_folder(
                 folder_id: folder.id.to_string(),
                 user_id: user.id.to_string(),
                 workspace_id: folder.workspace.to_string(),
+                file_count,
+            },
+        )])
+        .await?;
+    Ok(folder.into())
+}
+
+async fn restore_folder(
+    id: ID,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+    event_client: &EventClient,
+) -> FieldResult<Folder> {
+    let folder = db::FolderRepo::find_deleted_by_id(Uuid::parse_str(&id)?, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("folder {} has not been deleted", *id))?;
+    let user =
+        require_workspace_admin(folder.workspace, "restore folder", requesting_user, pool).await?;
+
+    if let Some(parent) = folder.parent {
+        if db::FolderRepo::find_by_id_if_exists(parent, pool)
+            .await?
+            .is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "cannot restore folder {} because its parent folder {} has been deleted",
+                folder.id,
+                parent
+            )
+            .extend_with(|_, extensions| {
+                extensions.set("problem", "The folder this folder was in has been deleted.");
+                extensions.set("suggestion", "Please restore that folder first.");
+            }));
+        }
+    }
+
+    let (folder, file_count) = db::FolderRepo::restore(folder.id, pool).await?;
+
+    event_client
+        .publish_events(&[Event::new(
+            id,
+            FolderRestoredData {
+                folder_id: folder.id.to_string(),
+                user_id: user.id.to_string(),
+                workspace_id: folder.workspace.to_string(),
+                file_count,
             },
         )])
         .await?;
@@ -291,10 +721,11 @@ mod test {
     async fn deleting_folder_emits_an_event() -> anyhow::Result<()> {
         let pool = mock_connection_pool()?;
         let (events, event_client) = mock_event_emitter();
-        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let requesting_user = mock_admin_requesting_user().await?;
 
         let folder = delete_folder(
             "d890181d-6b17-428e-896b-f76add15b54a".into(),
+            false,
             &pool,
             &requesting_user,
             &event_client,
@@ -319,8 +750,9 @@ mod test {
         let folder = create_folder(
             "title",
             "description",
-            &RoleRequired::PlatformMember.to_string(),
+            RoleRequired::PlatformMember,
             Uuid::new_v4(),
+            None,
             &pool,
             &requesting_user,
             &event_client,
@@ -362,4 +794,541 @@ mod test {
 
         Ok(())
     }
+
+    #[async_std::test]
+    async fn folder_in_workspace_member_folder_requires_workspace_member() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let parent = mock_folder("WORKSPACE_MEMBER").await?;
+
+        let folder = create_folder(
+            "title",
+            "description",
+            RoleRequired::PlatformMember,
+            parent.workspace,
+            Some(parent.id),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(folder.role_required, RoleRequired::WorkspaceMember);
+        assert_eq!(folder.parent, Some(parent.id.into()));
+        assert!(events.try_iter().any(|e| matches!(
+            e.data,
+            EventData::FolderCreated(data) if data.parent_folder_id == Some(parent.id.to_string())
+        )));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_folder_fails_if_parent_is_in_another_workspace() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let parent = mock_folder("PLATFORM_MEMBER").await?;
+
+        let result = create_folder(
+            "title",
+            "description",
+            RoleRequired::PlatformMember,
+            Uuid::new_v4(),
+            Some(parent.id),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn create_folder_fails_if_not_allowed_to_write_to_parent() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let parent = mock_folder("WORKSPACE_MEMBER").await?;
+
+        let result = create_folder(
+            "title",
+            "description",
+            RoleRequired::PlatformMember,
+            parent.workspace,
+            Some(parent.id),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "user with auth_id {} does not have permission to write to folder {}",
+                requesting_user.auth_id, parent.id
+            )
+        );
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_folder_fails_if_moved_into_its_subfolder() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let subfolder = create_folder(
+            "subfolder",
+            "",
+            RoleRequired::PlatformMember,
+            folder.workspace,
+            Some(folder.id),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        let result = move_folder(
+            folder.id,
+            Some(Uuid::parse_str(&subfolder.id)?),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "folder {} cannot be moved into itself or one of its subfolders",
+                folder.id
+            )
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_folder_into_workspace_member_folder_restricts_it() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let parent = mock_folder("WORKSPACE_MEMBER").await?;
+        let folder = db::FolderRepo::create(
+            "folder",
+            "",
+            "PLATFORM_MEMBER",
+            parent.workspace,
+            None,
+            &pool,
+        )
+        .await?;
+
+        let folder = move_folder(
+            folder.id,
+            Some(parent.id),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(folder.parent, Some(parent.id.into()));
+        assert_eq!(folder.role_required, RoleRequired::WorkspaceMember);
+        assert!(events.try_iter().any(|e| matches!(
+            e.data,
+            EventData::FolderUpdated(data) if data.parent_folder_id == Some(parent.id.to_string())
+        )));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_folder_without_parent_moves_it_to_the_top_level() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let parent = mock_folder("PLATFORM_MEMBER").await?;
+        let folder = db::FolderRepo::create(
+            "folder",
+            "",
+            "PLATFORM_MEMBER",
+            parent.workspace,
+            Some(parent.id),
+            &pool,
+        )
+        .await?;
+
+        let folder = move_folder(folder.id, None, &pool, &requesting_user, &event_client)
+            .await
+            .unwrap();
+
+        assert_eq!(folder.parent, None);
+        assert_eq!(
+            db::FolderRepo::find_by_id(Uuid::parse_str(&folder.id)?, &pool)
+                .await?
+                .parent,
+            None
+        );
+        assert!(events.try_iter().any(|e| matches!(
+            e.data,
+            EventData::FolderUpdated(data) if data.parent_folder_id.is_none()
+        )));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_folder_fails_if_not_allowed_to_write_to_it() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let parent = mock_folder("PLATFORM_MEMBER").await?;
+        let folder = db::FolderRepo::create(
+            "folder",
+            "",
+            "PLATFORM_MEMBER",
+            parent.workspace,
+            Some(parent.id),
+            &pool,
+        )
+        .await?;
+
+        let result = move_folder(folder.id, None, &pool, &requesting_user, &event_client).await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            db::FolderRepo::find_by_id(folder.id, &pool).await?.parent,
+            Some(parent.id)
+        );
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn move_folder_fails_if_not_allowed_to_write_to_parent() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let parent = mock_folder("WORKSPACE_MEMBER").await?;
+        let folder = db::FolderRepo::create(
+            "folder",
+            "",
+            "PLATFORM_MEMBER",
+            parent.workspace,
+            None,
+            &pool,
+        )
+        .await?;
+
+        let result = move_folder(
+            folder.id,
+            Some(parent.id),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(
+            db::FolderRepo::find_by_id(folder.id, &pool).await?.parent,
+            None
+        );
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restricting_folder_restricts_its_subfolders() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let subfolder = create_folder(
+            "subfolder",
+            "",
+            RoleRequired::PlatformMember,
+            folder.workspace,
+            Some(folder.id),
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        update_folder(
+            UpdateFolder {
+                id: folder.id.into(),
+                title: folder.title,
+                description: folder.description,
+                role_required: RoleRequired::WorkspaceMember,
+            },
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        let subfolder = db::FolderRepo::find_by_id(Uuid::parse_str(&subfolder.id)?, &pool).await?;
+        assert_eq!(subfolder.role_required, "WORKSPACE_MEMBER");
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn deleting_folder_with_contents_fails_unless_recursive() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+
+        let result = delete_folder(
+            folder.id.into(),
+            false,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "folder {} is not empty and recursive was not specified",
+                folder.id
+            )
+        );
+        assert!(db::FileWithVersionRepo::find_by_id(file.id, &pool)
+            .await?
+            .deleted_at
+            .is_none());
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn deleting_folder_fails_unless_workspace_admin() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+
+        let result = delete_folder(
+            folder.id.into(),
+            true,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "user with auth_id {} does not have permission to delete folder",
+                requesting_user.auth_id
+            )
+        );
+        assert!(db::FolderRepo::find_by_id_if_exists(folder.id, &pool)
+            .await?
+            .is_some());
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn deleting_folder_recursively_deletes_its_contents() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let subfolder = db::FolderRepo::create(
+            "subfolder",
+            "",
+            "PLATFORM_MEMBER",
+            folder.workspace,
+            Some(folder.id),
+            &pool,
+        )
+        .await?;
+        mock_file(&folder, &requesting_user).await?;
+        let file = mock_file(&subfolder, &requesting_user).await?;
+
+        delete_folder(
+            folder.id.into(),
+            true,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert!(db::FolderRepo::find_by_id_if_exists(subfolder.id, &pool)
+            .await?
+            .is_none());
+        assert!(db::FileWithVersionRepo::find_deleted_by_id(file.id, &pool)
+            .await?
+            .is_some());
+        assert!(events.try_iter().any(|e| matches!(
+            e.data,
+            EventData::FolderDeleted(ref data) if data.file_count == 2
+        )));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restoring_folder_restores_what_was_deleted_with_it() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+        let deleted_file = mock_file(&folder, &requesting_user).await?;
+        let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+            .await?
+            .unwrap();
+        db::FileWithVersionRepo::delete(deleted_file.id, user.id, &pool).await?;
+        delete_folder(
+            folder.id.into(),
+            true,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        let restored = restore_folder(folder.id.into(), &pool, &requesting_user, &event_client)
+            .await
+            .unwrap();
+
+        assert_eq!(restored.deleted_at, None);
+        assert!(db::FileWithVersionRepo::find_deleted_by_id(file.id, &pool)
+            .await?
+            .is_none());
+        assert!(
+            db::FileWithVersionRepo::find_deleted_by_id(deleted_file.id, &pool)
+                .await?
+                .is_some(),
+            "files deleted before the folder should stay deleted"
+        );
+        assert!(events.try_iter().any(|e| matches!(
+            e.data,
+            EventData::FolderRestored(ref data) if data.file_count == 1
+        )));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn restoring_folder_fails_if_its_parent_has_been_deleted() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        let subfolder = db::FolderRepo::create(
+            "subfolder",
+            "",
+            "PLATFORM_MEMBER",
+            folder.workspace,
+            Some(folder.id),
+            &pool,
+        )
+        .await?;
+        delete_folder(
+            subfolder.id.into(),
+            false,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+        delete_folder(
+            folder.id.into(),
+            false,
+            &pool,
+            &requesting_user,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        let result =
+            restore_folder(subfolder.id.into(), &pool, &requesting_user, &event_client).await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "cannot restore folder {} because its parent folder {} has been deleted",
+                subfolder.id, folder.id
+            )
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn get_folder_fails_if_not_allowed_to_read_it() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (_, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+
+        let result = get_folder(folder.id.into(), &pool, &requesting_user, &event_client).await;
+
+        let error = result.err().unwrap();
+        assert_eq!(
+            error.message,
+            format!(
+                "user with auth_id {} does not have permission to read folder {}",
+                requesting_user.auth_id, folder.id
+            )
+        );
+        assert!(error.extensions.is_some());
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn deleted_folders_fails_if_not_a_workspace_admin() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+
+        let result = deleted_folders(
+            folder.workspace,
+            Page::new(None, None)?,
+            &pool,
+            &requesting_user,
+        )
+        .await;
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
 }
@@ -1,22 +1,27 @@
+mod authorization;
 mod file_download_urls;
 mod file_upload_urls;
 mod files;
 mod folders;
+mod pagination;
 mod schema;
+mod search;
 #[cfg(test)]
-mod test_mocks;
+pub(crate) mod test_mocks;
 mod tracing_ext;
 mod users;
 mod validation;
 mod workspaces;
 
-use super::{azure, db};
+use super::{blob_store::BlobStore, db};
 use async_graphql::{
     http::{playground_source, GraphQLPlaygroundConfig},
     EmptySubscription, MergedObject, Schema,
 };
+use file_upload_urls::MaxUploadSize;
 use fnhs_event_models::EventClient;
 use sqlx::PgPool;
+use std::sync::Arc;
 use tide::{http::mime, Request, Response, StatusCode};
 use uuid::Uuid;
 
@@ -27,13 +32,19 @@ pub struct State {
 }
 
 impl State {
-    pub fn new(pool: PgPool, event_client: EventClient, azure_config: azure::Config) -> State {
+    pub fn new(
+        pool: PgPool,
+        event_client: EventClient,
+        blob_store: Arc<dyn BlobStore>,
+        max_upload_size: u64,
+    ) -> State {
         State {
             schema: Schema::build(Query::default(), Mutation::default(), EmptySubscription)
                 .extension(tracing_ext::Tracing)
                 .data(pool)
                 .data(event_client.clone())
-                .data(azure_config)
+                .data(blob_store)
+                .data(MaxUploadSize(max_upload_size))
                 .finish(),
             event_client,
         }
@@ -44,6 +55,7 @@ impl State {
 struct Query(
     files::FilesQuery,
     folders::FoldersQuery,
+    search::SearchQuery,
     workspaces::WorkspacesQuery,
 );
 
@@ -57,9 +69,9 @@ struct Mutation(
     users::UsersMutation,
 );
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RequestingUser {
-    auth_id: Uuid,
+    pub(crate) auth_id: Uuid,
 }
 
 pub async fn handle_healthz(req: Request<State>) -> tide::Result {
@@ -74,14 +86,11 @@ pub async fn handle_healthz(req: Request<State>) -> tide::Result {
 
 pub async fn handle_graphql(req: Request<State>) -> tide::Result {
     let schema = req.state().schema.clone();
-    let auth_id = req
-        .header("x-user-auth-id")
-        .and_then(|values| values.get(0))
-        .and_then(|value| Uuid::parse_str(value.as_str()).ok());
+    let requesting_user = req.ext::<RequestingUser>().cloned();
 
     let mut req = async_graphql_tide::receive_request(req).await?;
-    if let Some(auth_id) = auth_id {
-        req = req.data(RequestingUser { auth_id });
+    if let Some(requesting_user) = requesting_user {
+        req = req.data(requesting_user);
     }
 
     async_graphql_tide::respond(schema.execute(req).await)
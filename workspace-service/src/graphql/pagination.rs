@@ -0,0 +1,155 @@
+use super::db;
+use async_graphql::{
+    connection::{Connection, CursorType, Edge},
+    FieldResult, SimpleObject,
+};
+use uuid::Uuid;
+
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+/// Points at an item in a list ordered by a sort key and then by id. Clients treat it as an
+/// opaque string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub sort_key: String,
+    pub id: Uuid,
+}
+
+impl CursorType for Cursor {
+    type Error = anyhow::Error;
+
+    fn decode_cursor(s: &str) -> anyhow::Result<Self> {
+        let decoded = String::from_utf8(base64::decode_config(s, base64::URL_SAFE_NO_PAD)?)?;
+        let mut parts = decoded.splitn(2, ':');
+        let id = Uuid::parse_str(parts.next().unwrap_or_default())?;
+        let sort_key = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("cursor does not contain a sort key"))?
+            .to_string();
+        Ok(Self { sort_key, id })
+    }
+
+    fn encode_cursor(&self) -> String {
+        base64::encode_config(
+            format!("{}:{}", self.id, self.sort_key),
+            base64::URL_SAFE_NO_PAD,
+        )
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ConnectionFields {
+    /// The total number of items in the list
+    total_count: i64,
+}
+
+pub type PaginatedConnection<T> = Connection<Cursor, T, ConnectionFields>;
+
+/// The page of a list requested with the `first` and `after` arguments of a connection field.
+pub struct Page {
+    after: Option<Cursor>,
+    first: usize,
+}
+
+impl Page {
+    pub fn new(after: Option<String>, first: Option<i32>) -> FieldResult<Self> {
+        let first = match first {
+            Some(first) if first < 0 => return Err("first must not be negative".into()),
+            Some(first) => (first as usize).min(MAX_PAGE_SIZE),
+            None => DEFAULT_PAGE_SIZE,
+        };
+        let after = match after {
+            Some(after) => {
+                Some(Cursor::decode_cursor(&after).map_err(|_| "after is not a valid cursor")?)
+            }
+            None => None,
+        };
+
+        Ok(Self { after, first })
+    }
+
+    /// Fetches one more item than requested, to tell whether there is a next page.
+    pub fn args(&self) -> db::PageArgs<'_> {
+        db::PageArgs {
+            after_sort_key: self.after.as_ref().map(|cursor| cursor.sort_key.as_str()),
+            after_id: self.after.as_ref().map(|cursor| cursor.id),
+            limit: self.first as i64 + 1,
+        }
+    }
+
+    /// Builds the connection from the items fetched with `args()`.
+    pub fn connection<T, N>(
+        &self,
+        items: Vec<T>,
+        total_count: i64,
+        cursor: impl Fn(&T) -> Cursor,
+    ) -> PaginatedConnection<N>
+    where
+        T: Into<N>,
+        N: async_graphql::OutputValueType + Send + Sync,
+    {
+        let has_next_page = items.len() > self.first;
+        let mut connection = Connection::with_additional_fields(
+            self.after.is_some(),
+            has_next_page,
+            ConnectionFields { total_count },
+        );
+        connection.append(
+            items
+                .into_iter()
+                .take(self.first)
+                .map(|item| Edge::new(cursor(&item), item.into())),
+        );
+        connection
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = Cursor {
+            sort_key: "title: with a colon".into(),
+            id: Uuid::new_v4(),
+        };
+
+        let decoded = Cursor::decode_cursor(&cursor.encode_cursor()).unwrap();
+
+        assert_eq!(decoded, cursor);
+    }
+
+    #[test]
+    fn page_fetches_one_extra_item_after_cursor() {
+        let cursor = Cursor {
+            sort_key: "title".into(),
+            id: Uuid::new_v4(),
+        };
+
+        let page = Page::new(Some(cursor.encode_cursor()), Some(10)).unwrap();
+        let args = page.args();
+
+        assert_eq!(args.after_sort_key, Some("title"));
+        assert_eq!(args.after_id, Some(cursor.id));
+        assert_eq!(args.limit, 11);
+    }
+
+    #[test]
+    fn first_is_capped_at_max_page_size() {
+        let page = Page::new(None, Some(10_000)).unwrap();
+
+        assert_eq!(page.args().limit, MAX_PAGE_SIZE as i64 + 1);
+    }
+
+    #[test]
+    fn invalid_cursor_is_rejected() {
+        assert!(Page::new(Some("not a cursor".into()), None).is_err());
+    }
+
+    #[test]
+    fn negative_first_is_rejected() {
+        assert!(Page::new(None, Some(-1)).is_err());
+    }
+}
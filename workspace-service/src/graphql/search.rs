@@ -0,0 +1,195 @@
+use super::{db, files::File, folders::Folder, workspaces::Workspace, RequestingUser};
+use async_graphql::{Context, Enum, FieldResult, Object, SimpleObject, Union, ID};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const MAX_SEARCH_RESULTS: i64 = 50;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SearchKind {
+    /// Search workspace titles and descriptions
+    Workspace,
+    /// Search folder titles and descriptions
+    Folder,
+    /// Search file titles, descriptions and file names
+    File,
+}
+
+#[derive(Union)]
+pub enum SearchResult {
+    Workspace(Workspace),
+    Folder(Folder),
+    File(File),
+}
+
+/// A workspace, folder or file that matches a search
+#[derive(SimpleObject)]
+pub struct SearchHit {
+    /// The matching workspace, folder or file
+    item: SearchResult,
+    /// The matching text as HTML, with matched words wrapped in <mark> and </mark>. The rest of
+    /// the text is escaped.
+    snippet: String,
+    /// How well the item matches the search. Higher is better.
+    rank: f32,
+}
+
+#[derive(Default)]
+pub struct SearchQuery;
+
+#[Object]
+impl SearchQuery {
+    /// Search workspaces, folders and files, best matches first.
+    ///
+    /// Items match if they contain every word of the query, or a word with the same stem. Pass a
+    /// workspace to only search in that workspace, and kinds to only search some kinds of item.
+    async fn search(
+        &self,
+        context: &Context<'_>,
+        query: String,
+        workspace: Option<ID>,
+        kinds: Option<Vec<SearchKind>>,
+    ) -> FieldResult<Vec<SearchHit>> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+        let workspace = workspace.map(|id| Uuid::parse_str(&id)).transpose()?;
+
+        search(&query, workspace, kinds, pool, requesting_user).await
+    }
+}
+
+async fn search(
+    query: &str,
+    workspace: Option<Uuid>,
+    kinds: Option<Vec<SearchKind>>,
+    pool: &PgPool,
+    requesting_user: &RequestingUser,
+) -> FieldResult<Vec<SearchHit>> {
+    if query.trim().is_empty() {
+        return Err("query must not be empty".into());
+    }
+
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+    let includes = |kind| kinds.as_ref().map_or(true, |kinds| kinds.contains(&kind));
+    let hits = db::SearchRepo::search(
+        db::SearchArgs {
+            query,
+            workspace,
+            include_workspaces: includes(SearchKind::Workspace),
+            include_folders: includes(SearchKind::Folder),
+            include_files: includes(SearchKind::File),
+            user_id: user.id,
+            is_platform_admin: user.is_platform_admin,
+            limit: MAX_SEARCH_RESULTS,
+        },
+        pool,
+    )
+    .await?;
+
+    let mut results = Vec::with_capacity(hits.len());
+    for hit in hits {
+        let item = match hit.kind {
+            db::SearchHitKind::Workspace => {
+                SearchResult::Workspace(db::WorkspaceRepo::find_by_id(hit.id, pool).await?.into())
+            }
+            db::SearchHitKind::Folder => {
+                SearchResult::Folder(db::FolderRepo::find_by_id(hit.id, pool).await?.into())
+            }
+            db::SearchHitKind::File => SearchResult::File(
+                db::FileWithVersionRepo::find_by_id(hit.id, pool)
+                    .await?
+                    .into(),
+            ),
+        };
+        results.push(SearchHit {
+            item,
+            snippet: hit.snippet,
+            rank: hit.rank,
+        });
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graphql::test_mocks::*;
+
+    fn kinds(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter()
+            .map(|hit| match &hit.item {
+                SearchResult::Workspace(_) => "workspace",
+                SearchResult::Folder(_) => "folder",
+                SearchResult::File(_) => "file",
+            })
+            .collect()
+    }
+
+    #[async_std::test]
+    async fn search_finds_files_in_folders_the_user_can_read() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("WORKSPACE_MEMBER", &requesting_user).await?;
+        let file = mock_file(&folder, &requesting_user).await?;
+
+        let hits = search(
+            "FAKE file",
+            Some(folder.workspace),
+            Some(vec![SearchKind::File]),
+            &pool,
+            &requesting_user,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(kinds(&hits), vec!["file"]);
+        assert!(matches!(&hits[0].item, SearchResult::File(hit) if *hit.id == file.id.to_string()));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn search_hides_workspace_member_folders_from_non_members() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let admin = mock_admin_requesting_user().await?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+        mock_file(&folder, &admin).await?;
+
+        let hits = search(
+            "fake",
+            Some(folder.workspace),
+            None,
+            &pool,
+            &requesting_user,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(kinds(&hits), vec!["workspace"]);
+
+        let hits = search("fake", Some(folder.workspace), None, &pool, &admin)
+            .await
+            .unwrap();
+
+        assert_eq!(kinds(&hits), vec!["workspace", "folder", "file"]);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn search_fails_if_query_is_empty() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+
+        let result = search("  ", None, None, &pool, &requesting_user).await;
+
+        assert_eq!(result.err().unwrap().message, "query must not be empty");
+
+        Ok(())
+    }
+}
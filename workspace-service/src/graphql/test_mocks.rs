@@ -1,4 +1,5 @@
-use super::{azure, db, RequestingUser};
+use super::{db, RequestingUser};
+use crate::{azure, s3};
 use fnhs_event_models::{Event, EventClient};
 use sqlx::PgPool;
 use std::sync::{
@@ -49,6 +50,150 @@ pub async fn mock_unprivileged_requesting_user() -> anyhow::Result<RequestingUse
     })
 }
 
+/// Creates a workspace with no members, and a folder in it.
+pub async fn mock_folder(role_required: &str) -> anyhow::Result<db::Folder> {
+    let pool = mock_connection_pool()?;
+    let workspace = db::WorkspaceRepo::create("fake workspace", "", &pool).await?;
+    let folder =
+        db::FolderRepo::create("fake folder", "", role_required, workspace.id, None, &pool).await?;
+    Ok(folder)
+}
+
+/// Creates a workspace with the requesting user as a (non-admin) member, and a folder in it.
+pub async fn mock_member_folder(
+    role_required: &str,
+    requesting_user: &RequestingUser,
+) -> anyhow::Result<db::Folder> {
+    let pool = mock_connection_pool()?;
+    let folder = mock_folder(role_required).await?;
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+    db::WorkspaceRepo::change_workspace_membership(
+        folder.workspace,
+        user.id,
+        db::Role::NonAdmin,
+        &pool,
+    )
+    .await?;
+    Ok(folder)
+}
+
+/// The SHA-256 hash of the 42 byte text file that the fake Azure blob store has by default.
+pub const FAKE_FILE_SHA256: &str =
+    "c31b5250356c0d3a25dec0682e6d34e1d9fe6f0b92dcb1f9778947f7ceae0b03";
+
+/// Creates a file in the folder.
+pub async fn mock_file(
+    folder: &db::Folder,
+    requesting_user: &RequestingUser,
+) -> anyhow::Result<db::FileWithVersion> {
+    mock_file_with_hash(folder, requesting_user, FAKE_FILE_SHA256).await
+}
+
+/// Creates a file in the folder, as if its contents had the SHA-256 hash.
+pub async fn mock_file_with_hash(
+    folder: &db::Folder,
+    requesting_user: &RequestingUser,
+    sha256: &str,
+) -> anyhow::Result<db::FileWithVersion> {
+    let pool = mock_connection_pool()?;
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+    let file = db::FileWithVersionRepo::create(
+        db::CreateFileArgs {
+            user_id: user.id,
+            folder_id: folder.id,
+            title: "fake file",
+            description: "fake file for tests",
+            file_name: "fake.txt",
+            file_type: "text/plain",
+            blob_storage_path: "http://localhost:10000/devstoreaccount1/files/fake",
+            sha256,
+            file_size: 42,
+            page_count: None,
+        },
+        &pool,
+    )
+    .await?;
+    Ok(file)
+}
+
+/// Creates a file in the folder with a blob of its own, and gives it the scan status.
+pub async fn mock_scanned_file(
+    folder: &db::Folder,
+    requesting_user: &RequestingUser,
+    scan_status: db::ScanStatus,
+) -> anyhow::Result<db::FileWithVersion> {
+    let pool = mock_connection_pool()?;
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+    let blob_storage_path = format!(
+        "http://localhost:10000/devstoreaccount1/files/{}",
+        Uuid::new_v4()
+    );
+    let file = db::FileWithVersionRepo::create(
+        db::CreateFileArgs {
+            user_id: user.id,
+            folder_id: folder.id,
+            title: "fake file",
+            description: "fake file for tests",
+            file_name: "fake.txt",
+            file_type: "text/plain",
+            blob_storage_path: &blob_storage_path,
+            sha256: FAKE_FILE_SHA256,
+            file_size: 42,
+            page_count: None,
+        },
+        &pool,
+    )
+    .await?;
+    db::FileVersionRepo::update_scan_status(&blob_storage_path, scan_status, &pool).await?;
+    Ok(db::FileWithVersionRepo::find_by_id(file.id, &pool).await?)
+}
+
+/// Records an upload by the requesting user, like `fileUploadUrls` does, and returns its URL. The
+/// fake Azure blob store has a 42 byte text file at every upload URL.
+pub async fn mock_upload(
+    requesting_user: &RequestingUser,
+    file_type: &str,
+    file_size: i64,
+) -> anyhow::Result<String> {
+    let pool = mock_connection_pool()?;
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+    let upload = db::UploadRepo::create(
+        db::CreateUploadArgs {
+            id: Uuid::new_v4(),
+            file_name: "fake.txt",
+            file_type,
+            file_size,
+            created_by: user.id,
+        },
+        &pool,
+    )
+    .await?;
+    Ok(format!(
+        "http://localhost:10000/devstoreaccount1/upload/{}",
+        upload.id
+    ))
+}
+
+/// Records an upload by the requesting user, like `mock_upload`, and makes the fake Azure blob
+/// store have the contents at its URL.
+pub async fn mock_upload_with_contents(
+    requesting_user: &RequestingUser,
+    file_type: &str,
+    contents: &[u8],
+) -> anyhow::Result<String> {
+    let url = mock_upload(requesting_user, file_type, contents.len() as i64).await?;
+    azure::put_fake_upload(&Url::parse(&url)?, file_type, contents);
+    Ok(url)
+}
+
 pub fn mock_azure_config() -> anyhow::Result<azure::Config> {
     azure::Config::new(
         "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw=="
@@ -57,3 +202,15 @@ pub fn mock_azure_config() -> anyhow::Result<azure::Config> {
         Url::parse("http://localhost:10000/devstoreaccount1/files")?,
     )
 }
+
+/// The buckets of the MinIO service in docker-compose.yaml, for tests that are ignored unless
+/// they are run with `make test-s3`.
+pub fn mock_s3_config() -> anyhow::Result<s3::Config> {
+    s3::Config::new(
+        "minioadmin".into(),
+        "minioadmin".into(),
+        "us-east-1".into(),
+        Url::parse("http://localhost:9000/upload")?,
+        Url::parse("http://localhost:9000/files")?,
+    )
+}
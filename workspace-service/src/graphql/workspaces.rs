@@ -1,11 +1,18 @@
 use crate::{
     db,
     db::{Role, WorkspaceRepo},
-    graphql::{users::User, RequestingUser},
+    graphql::{
+        pagination::{Cursor, Page, PaginatedConnection},
+        users::User,
+        RequestingUser,
+    },
+};
+use async_graphql::{
+    Context, Enum, ErrorExtensions, FieldResult, InputObject, Object, SimpleObject, ID,
 };
-use async_graphql::{Context, Enum, ErrorExtensions, FieldResult, InputObject, Object, ID};
 use fnhs_event_models::{
-    Event, EventClient, EventPublisher as _, WorkspaceCreatedData, WorkspaceMembershipChangedData,
+    Event, EventClient, EventPublisher as _, WorkspaceCreatedData, WorkspaceDeletedData,
+    WorkspaceMembershipChangedData, WorkspaceUpdatedData,
 };
 use sqlx::PgPool;
 use std::convert::TryInto;
@@ -19,6 +26,29 @@ pub struct Workspace {
     members: Uuid,
 }
 
+/// How many files there are in a workspace and how much space they take up. Files uploaded
+/// before sizes were recorded count as empty.
+#[derive(SimpleObject)]
+pub struct FileTotals {
+    /// The number of files, not counting deleted files
+    file_count: i64,
+    /// The total size of the latest versions of the files, not counting deleted files, in bytes
+    total_file_size: i64,
+    /// The total size of all of the stored contents of the workspace, including old versions
+    /// and deleted files that have not been purged yet, in bytes
+    stored_size: i64,
+}
+
+impl From<db::FileTotals> for FileTotals {
+    fn from(d: db::FileTotals) -> Self {
+        Self {
+            file_count: d.file_count,
+            total_file_size: d.total_file_size,
+            stored_size: d.stored_size,
+        }
+    }
+}
+
 #[derive(Enum, Copy, Clone, Eq, PartialEq)]
 pub enum RoleFilter {
     /// Only return Admins
@@ -72,8 +102,16 @@ impl Workspace {
     async fn description(&self) -> String {
         self.description.clone()
     }
+    /// How many files there are in the workspace and how much space they take up. Only
+    /// workspace admins can see these.
+    async fn file_totals(&self, context: &Context<'_>) -> FieldResult<FileTotals> {
+        let pool = context.data()?;
+        let requesting_user = context.data()?;
+
+        file_totals(Uuid::parse_str(&self.id)?, requesting_user, pool).await
+    }
 
-    /// List of users who are members of this workspace.
+    /// List of users who are members of this workspace, ordered by name.
     ///
     /// Pass RoleFilter: Admin or NonAdmin for finer control over
     /// which members are returned.
@@ -81,16 +119,31 @@ impl Workspace {
         &self,
         context: &Context<'_>,
         filter: Option<RoleFilter>,
-    ) -> FieldResult<Vec<User>> {
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> FieldResult<PaginatedConnection<User>> {
         let pool = context.data()?;
-        let users = match filter {
-            Some(RoleFilter::Admin) => db::TeamRepo::members(self.admins, pool).await?,
-            Some(RoleFilter::NonAdmin) => {
-                db::TeamRepo::members_difference(self.members, self.admins, pool).await?
-            }
-            None => db::TeamRepo::members(self.members, pool).await?,
+        let page = Page::new(after, first)?;
+        let (users, total_count) = match filter {
+            Some(RoleFilter::Admin) => (
+                db::TeamRepo::members(self.admins, page.args(), pool).await?,
+                db::TeamRepo::count_members(self.admins, pool).await?,
+            ),
+            Some(RoleFilter::NonAdmin) => (
+                db::TeamRepo::members_difference(self.members, self.admins, page.args(), pool)
+                    .await?,
+                db::TeamRepo::count_members_difference(self.members, self.admins, pool).await?,
+            ),
+            None => (
+                db::TeamRepo::members(self.members, page.args(), pool).await?,
+                db::TeamRepo::count_members(self.members, pool).await?,
+            ),
         };
-        Ok(users.into_iter().map(Into::into).collect())
+
+        Ok(page.connection(users, total_count, |user| Cursor {
+            sort_key: user.name.clone(),
+            id: user.id,
+        }))
     }
 }
 
@@ -129,11 +182,24 @@ pub struct WorkspacesQuery;
 
 #[Object]
 impl WorkspacesQuery {
-    /// Get all Workspaces
-    async fn workspaces(&self, context: &Context<'_>) -> FieldResult<Vec<Workspace>> {
+    /// Get Workspaces, ordered by id
+    async fn workspaces(
+        &self,
+        context: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> FieldResult<PaginatedConnection<Workspace>> {
         let pool = context.data()?;
-        let workspaces = WorkspaceRepo::find_all(pool).await?;
-        Ok(workspaces.into_iter().map(Into::into).collect())
+        let page = Page::new(after, first)?;
+        let workspaces = WorkspaceRepo::find_all(page.args(), pool).await?;
+        let total_count = WorkspaceRepo::count_all(pool).await?;
+
+        Ok(
+            page.connection(workspaces, total_count, |workspace| Cursor {
+                sort_key: String::new(),
+                id: workspace.id,
+            }),
+        )
     }
 
     /// Get workspace by ID
@@ -202,26 +268,28 @@ impl WorkspacesMutation {
         id: ID,
         workspace: UpdateWorkspace,
     ) -> FieldResult<Workspace> {
-        // TODO: Add event
         let pool = context.data()?;
-        let workspace = WorkspaceRepo::update(
-            Uuid::parse_str(id.as_str())?,
+        let requesting_user = context.data::<RequestingUser>()?;
+        let event_client: &EventClient = context.data()?;
+
+        update_workspace(
+            id.try_into()?,
             &workspace.title,
             &workspace.description,
+            requesting_user,
             pool,
+            event_client,
         )
-        .await?;
-
-        Ok(workspace.into())
+        .await
     }
 
     /// Delete workspace (returns deleted workspace)
     async fn delete_workspace(&self, context: &Context<'_>, id: ID) -> FieldResult<Workspace> {
-        // TODO: Add event
         let pool = context.data()?;
-        let workspace = WorkspaceRepo::delete(Uuid::parse_str(id.as_str())?, pool).await?;
+        let requesting_user = context.data::<RequestingUser>()?;
+        let event_client: &EventClient = context.data()?;
 
-        Ok(workspace.into())
+        delete_workspace(id.try_into()?, requesting_user, pool, event_client).await
     }
 
     /// Changes workspace permissions for a user (Admin/NonAdmin/NonMember)
@@ -273,9 +341,100 @@ async fn create_workspace(
             workspace.id.clone(),
             WorkspaceCreatedData {
                 workspace_id: workspace.id.clone().into(),
-                // TODO: Fill this in when we have users in the db.
-                user_id: "".into(),
+                user_id: user.id.to_string(),
+                title: workspace.title.clone(),
+            },
+        )])
+        .await?;
+
+    Ok(workspace)
+}
+
+/// Fails unless the requesting user is an admin of the workspace or a platform admin.
+pub async fn require_workspace_admin(
+    workspace_id: Uuid,
+    action: &str,
+    requesting_user: &RequestingUser,
+    pool: &PgPool,
+) -> FieldResult<db::User> {
+    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+    if !user.is_platform_admin && !WorkspaceRepo::is_admin(workspace_id, user.id, pool).await? {
+        return Err(anyhow::anyhow!(
+            "user with auth_id {} does not have permission to {}",
+            user.auth_id,
+            action,
+        )
+        .extend_with(|_, extensions| {
+            extensions.set("problem", "You do not have permission to do this.");
+            extensions.set("suggestion", "Please contact a workspace administrator.");
+        }));
+    }
+
+    Ok(user)
+}
+
+async fn file_totals(
+    workspace_id: Uuid,
+    requesting_user: &RequestingUser,
+    pool: &PgPool,
+) -> FieldResult<FileTotals> {
+    require_workspace_admin(workspace_id, "see file totals", requesting_user, pool).await?;
+
+    let totals = db::FileWithVersionRepo::totals_by_workspace(workspace_id, pool).await?;
+
+    Ok(totals.into())
+}
+
+async fn update_workspace(
+    workspace_id: Uuid,
+    title: &str,
+    description: &str,
+    requesting_user: &RequestingUser,
+    pool: &PgPool,
+    event_client: &EventClient,
+) -> FieldResult<Workspace> {
+    let user =
+        require_workspace_admin(workspace_id, "update workspace", requesting_user, pool).await?;
+
+    let workspace: Workspace = WorkspaceRepo::update(workspace_id, title, description, pool)
+        .await?
+        .into();
+
+    event_client
+        .publish_events(&[Event::new(
+            workspace.id.clone(),
+            WorkspaceUpdatedData {
+                workspace_id: workspace.id.clone().into(),
                 title: workspace.title.clone(),
+                description: workspace.description.clone(),
+                user_id: user.id.to_string(),
+            },
+        )])
+        .await?;
+
+    Ok(workspace)
+}
+
+async fn delete_workspace(
+    workspace_id: Uuid,
+    requesting_user: &RequestingUser,
+    pool: &PgPool,
+    event_client: &EventClient,
+) -> FieldResult<Workspace> {
+    let user =
+        require_workspace_admin(workspace_id, "delete workspace", requesting_user, pool).await?;
+
+    let workspace: Workspace = WorkspaceRepo::delete(workspace_id, pool).await?.into();
+
+    event_client
+        .publish_events(&[Event::new(
+            workspace.id.clone(),
+            WorkspaceDeletedData {
+                workspace_id: workspace.id.clone().into(),
+                user_id: user.id.to_string(),
             },
         )])
         .await?;
@@ -310,20 +469,13 @@ async fn change_workspace_membership(
     pool: &PgPool,
     event_client: &EventClient,
 ) -> FieldResult<Workspace> {
-    let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, pool)
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("user not found"))?;
-
-    if !user.is_platform_admin && !WorkspaceRepo::is_admin(workspace_id, user.id, pool).await? {
-        return Err(anyhow::anyhow!(
-            "user with auth_id {} does not have permission to update workspace membership",
-            user.auth_id,
-        )
-        .extend_with(|_, extensions| {
-            extensions.set("problem", "You do not have permission to do this.");
-            extensions.set("suggestion", "Please contact a workspace administrator.");
-        }));
-    }
+    let user = require_workspace_admin(
+        workspace_id,
+        "update workspace membership",
+        requesting_user,
+        pool,
+    )
+    .await?;
 
     if !user.is_platform_admin && user.id == user_id {
         return Err(anyhow::anyhow!(
@@ -381,9 +533,10 @@ mod test {
         assert_eq!(workspace.title, "title");
         assert_eq!(workspace.description, "description");
 
-        assert!(events
-            .try_iter()
-            .any(|e| matches!(e.data, EventData::WorkspaceCreated(_))));
+        assert!(events.try_iter().any(|e| matches!(
+            e.data,
+            EventData::WorkspaceCreated(WorkspaceCreatedData { ref user_id, .. }) if !user_id.is_empty()
+        )));
 
         Ok(())
     }
@@ -409,6 +562,159 @@ mod test {
         Ok(())
     }
 
+    #[async_std::test]
+    async fn a_workspace_admin_can_update_workspace() -> anyhow::Result<()> {
+        use db::TeamRepo;
+
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+        let workspace = WorkspaceRepo::create("", "", &pool).await?;
+        TeamRepo::add_member(workspace.admins, user.id, &pool).await?;
+
+        let result = update_workspace(
+            workspace.id,
+            "title",
+            "description",
+            &requesting_user,
+            &pool,
+            &event_client,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.title, "title");
+        assert!(events
+            .try_iter()
+            .any(|e| matches!(e.data, EventData::WorkspaceUpdated(_))));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn a_workspace_member_cannot_update_workspace() -> anyhow::Result<()> {
+        use db::TeamRepo;
+
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let user = db::UserRepo::find_by_auth_id(&requesting_user.auth_id, &pool)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+
+        let workspace = WorkspaceRepo::create("", "", &pool).await?;
+        TeamRepo::add_member(workspace.members, user.id, &pool).await?;
+
+        let result = update_workspace(
+            workspace.id,
+            "title",
+            "description",
+            &requesting_user,
+            &pool,
+            &event_client,
+        )
+        .await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "user with auth_id {} does not have permission to update workspace",
+                requesting_user.auth_id
+            )
+        );
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn a_site_admin_can_delete_workspace() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_admin_requesting_user().await?;
+
+        let workspace = WorkspaceRepo::create("", "", &pool).await?;
+
+        let result = delete_workspace(workspace.id, &requesting_user, &pool, &event_client)
+            .await
+            .unwrap();
+
+        assert_eq!(result.id, ID::from(workspace.id));
+        assert!(events
+            .try_iter()
+            .any(|e| matches!(e.data, EventData::WorkspaceDeleted(_))));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn a_site_admin_can_see_file_totals() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_admin_requesting_user().await?;
+        let folder = mock_folder("PLATFORM_MEMBER").await?;
+        mock_file(&folder, &requesting_user).await?;
+        // Shares the blob of the first file.
+        mock_file(&folder, &requesting_user).await?;
+        let deleted = mock_scanned_file(&folder, &requesting_user, db::ScanStatus::Clean).await?;
+        db::FileWithVersionRepo::delete(deleted.id, Uuid::new_v4(), &pool).await?;
+
+        let result = file_totals(folder.workspace, &requesting_user, &pool)
+            .await
+            .unwrap();
+
+        assert_eq!(result.file_count, 2);
+        assert_eq!(result.total_file_size, 84);
+        assert_eq!(result.stored_size, 84);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn a_workspace_member_cannot_see_file_totals() -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+        let folder = mock_member_folder("PLATFORM_MEMBER", &requesting_user).await?;
+
+        let result = file_totals(folder.workspace, &requesting_user, &pool).await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "user with auth_id {} does not have permission to see file totals",
+                requesting_user.auth_id
+            )
+        );
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn a_user_cannot_delete_workspace_if_they_are_neither_site_nor_workspace_admin(
+    ) -> anyhow::Result<()> {
+        let pool = mock_connection_pool()?;
+        let (events, event_client) = mock_event_emitter();
+        let requesting_user = mock_unprivileged_requesting_user().await?;
+
+        let workspace = WorkspaceRepo::create("", "", &pool).await?;
+
+        let result = delete_workspace(workspace.id, &requesting_user, &pool, &event_client).await;
+
+        assert_eq!(
+            result.err().unwrap().message,
+            format!(
+                "user with auth_id {} does not have permission to delete workspace",
+                requesting_user.auth_id
+            )
+        );
+        assert_eq!(events.try_iter().count(), 0);
+
+        Ok(())
+    }
+
     #[async_std::test]
     async fn a_workspace_admin_cannot_demote_themselves_to_member() -> anyhow::Result<()> {
         use db::TeamRepo;
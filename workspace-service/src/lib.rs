@@ -1,12 +1,22 @@
+pub mod auth;
 pub mod azure;
+pub mod blob_store;
 pub mod config;
 mod db;
+pub mod events;
 mod graphql;
+mod pdf;
+pub mod purge;
+pub mod s3;
+pub mod scanner;
+mod sniff;
 
+use blob_store::BlobStore;
 use fnhs_event_models::EventClient;
 pub use graphql::generate_graphql_schema;
 use opentelemetry::api::{Extractor, TraceContextExt};
 use sqlx::PgPool;
+use std::sync::Arc;
 use tide::{Middleware, Next, Redirect, Request, Server};
 use tracing::info_span;
 use tracing_futures::Instrument;
@@ -62,20 +72,27 @@ impl<State: Clone + Send + Sync + 'static> Middleware<State> for TracingMiddlewa
 pub async fn create_app(
     connection_pool: PgPool,
     event_client: EventClient,
-    azure_config: azure::Config,
+    blob_store: Arc<dyn BlobStore>,
+    max_upload_size: u64,
+    authentication: auth::Authentication,
 ) -> anyhow::Result<Server<graphql::State>> {
     let mut app = tide::with_state(graphql::State::new(
         connection_pool,
         event_client,
-        azure_config,
+        blob_store.clone(),
+        max_upload_size,
     ));
 
     app.with(TracingMiddleware);
+    app.with(auth::AuthMiddleware::new(authentication));
 
     app.at("/").get(Redirect::permanent("/graphiql"));
     app.at("/healthz").get(graphql::handle_healthz);
     app.at("/graphql").post(graphql::handle_graphql);
     app.at("/graphiql").get(graphql::handle_graphiql);
+    if let Some(endpoint) = blob_store.endpoint() {
+        app.at("/blobs").nest(endpoint);
+    }
 
     Ok(app)
 }
@@ -1,14 +1,19 @@
 use anyhow::{anyhow, Result};
-use fnhs_event_models::EventClient;
 use opentelemetry::{
     api::{trace::b3_propagator::B3Encoding, B3Propagator, Provider},
     global, sdk,
     sdk::BatchSpanProcessor,
 };
 use sqlx::PgPool;
+use std::time::Duration;
 use structopt::StructOpt;
 use tracing_subscriber::{layer::SubscriberExt, Registry};
-use workspace_service::{azure, config::Config};
+use workspace_service::{
+    auth::{Authentication, Jwks, TokenValidator},
+    blob_store,
+    config::{Command, Config, ScannerKind},
+    events, scanner,
+};
 
 #[async_std::main]
 async fn main() -> Result<()> {
@@ -24,6 +29,25 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(Command::Purge(purge_config)) = &config.command {
+        let connection_pool =
+            PgPool::connect(config.database_url.as_ref().expect("required").as_str()).await?;
+        sqlx::migrate!("./migrations").run(&connection_pool).await?;
+        let blob_store = blob_store::from_config(&config)?;
+
+        let report =
+            workspace_service::purge::purge(purge_config, &connection_pool, blob_store.as_ref())
+                .await?;
+        print!("{}", report);
+        if !report.failures.is_empty() {
+            return Err(anyhow!("failed to remove {} blobs", report.failures.len()));
+        }
+        return Ok(());
+    }
+
+    let blob_store = blob_store::from_config(&config)?;
+    let file_scanner = scanner::from_config(&config);
+
     let provider = if let Some(instrumentation_key) = config.instrumentation_key {
         let exporter = opentelemetry_application_insights::Exporter::new(instrumentation_key);
         let batch_exporter = BatchSpanProcessor::builder(
@@ -53,28 +77,45 @@ async fn main() -> Result<()> {
     let connection_pool = PgPool::connect(config.database_url.expect("required").as_str()).await?;
     sqlx::migrate!("./migrations").run(&connection_pool).await?;
 
-    let event_client = if let (Some(topic_endpoint), Some(topic_key)) =
-        (config.eventgrid_topic_endpoint, config.eventgrid_topic_key)
-    {
-        EventClient::new(
-            topic_endpoint
-                .host_str()
-                .ok_or_else(|| anyhow!("EVENTGRID_TOPIC_ENDPOINT does not contain host name"))?
-                .to_owned(),
-            topic_key,
-        )
+    let event_client = events::from_config(&config)?;
+
+    let authentication = if config.trust_auth_id_header {
+        println!("--trust-auth-id-header is set. Do not use this in production!");
+        Authentication::TrustedHeader
     } else {
-        EventClient::default()
+        Authentication::Token(TokenValidator::new(
+            config
+                .auth_issuer
+                .ok_or_else(|| anyhow!("AUTH_ISSUER is required"))?,
+            config
+                .auth_audience
+                .ok_or_else(|| anyhow!("AUTH_AUDIENCE is required"))?,
+            Jwks::load(
+                &config
+                    .auth_jwks
+                    .ok_or_else(|| anyhow!("AUTH_JWKS is required"))?,
+            )
+            .await?,
+        ))
     };
 
+    if config.scanner == ScannerKind::None {
+        println!("--scanner none is set, so files are not scanned. Do not use this in production!");
+    }
+    async_std::task::spawn(scanner::run(
+        connection_pool.clone(),
+        blob_store.clone(),
+        file_scanner,
+        event_client.clone(),
+        Duration::from_secs(config.scan_interval_seconds),
+    ));
+
     let app = workspace_service::create_app(
         connection_pool,
         event_client,
-        azure::Config::new(
-            config.file_storage_access_key.expect("required"),
-            config.upload_container_url.expect("required"),
-            config.files_container_url.expect("required"),
-        )?,
+        blob_store,
+        config.max_upload_size,
+        authentication,
     )
     .await?;
     app.listen("0.0.0.0:3030").await?;
@@ -0,0 +1,141 @@
+//! Counts the pages of a PDF as it is read, without parsing it.
+
+/// Counts the page objects (`/Type /Page` dictionaries) in a PDF that is fed to it in chunks.
+///
+/// This is an estimate: page objects in compressed object streams cannot be seen, and a PDF
+/// that has been edited incrementally can contain old copies of the same page. It is good enough
+/// to show users, but not to rely on.
+#[derive(Debug, Default)]
+pub struct PageCounter {
+    state: State,
+    pages: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// Looking for `/Type`, having matched this many bytes of it
+    Type(usize),
+    /// Skipping whitespace after `/Type`
+    AfterType,
+    /// Matching `/Page`, having matched this many bytes of it
+    Page(usize),
+    /// Checking that `/Page` is not the start of a longer name, such as `/Pages`
+    AfterPage,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State::Type(0)
+    }
+}
+
+const TYPE: &[u8] = b"/Type";
+const PAGE: &[u8] = b"/Page";
+
+impl PageCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        for &byte in chunk {
+            self.next(byte);
+        }
+    }
+
+    /// The number of pages found, or `None` if there were none, which is never true of a valid
+    /// PDF.
+    pub fn finish(mut self) -> Option<u32> {
+        // The file may end straight after the last `/Page`.
+        if self.state == State::AfterPage {
+            self.pages += 1;
+        }
+        match self.pages {
+            0 => None,
+            pages => Some(pages),
+        }
+    }
+
+    fn next(&mut self, byte: u8) {
+        self.state = match self.state {
+            State::Type(matched) if byte == TYPE[matched] => {
+                if matched + 1 == TYPE.len() {
+                    State::AfterType
+                } else {
+                    State::Type(matched + 1)
+                }
+            }
+            State::AfterType if is_whitespace(byte) => State::AfterType,
+            State::AfterType | State::Page(_) if byte == PAGE[self.page_matched()] => {
+                if self.page_matched() + 1 == PAGE.len() {
+                    State::AfterPage
+                } else {
+                    State::Page(self.page_matched() + 1)
+                }
+            }
+            State::AfterPage => {
+                if is_whitespace(byte) || is_delimiter(byte) {
+                    self.pages += 1;
+                }
+                Self::start(byte)
+            }
+            _ => Self::start(byte),
+        };
+    }
+
+    fn page_matched(&self) -> usize {
+        match self.state {
+            State::Page(matched) => matched,
+            _ => 0,
+        }
+    }
+
+    /// The state after a byte that does not continue the current match, which may start a new
+    /// one.
+    fn start(byte: u8) -> State {
+        if byte == TYPE[0] {
+            State::Type(1)
+        } else {
+            State::Type(0)
+        }
+    }
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\0' | b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(b"<< /Type /Page /Parent 2 0 R >>" => Some(1) ; "one page")]
+    #[test_case(b"<</Type/Pages/Count 2>> <</Type/Page>> <</Type/Page>>" => Some(2) ; "pages node is not a page")]
+    #[test_case(b"<< /Type\r\n/Page\n>>" => Some(1) ; "whitespace between names")]
+    #[test_case(b"<< /Type /Page" => Some(1) ; "page at the end")]
+    #[test_case(b"<< /Type /PageLabel >>" => None ; "longer name")]
+    #[test_case(b"%PDF-1.5 compressed object streams only" => None ; "no page objects")]
+    #[test_case(b"/T/Type /Page " => Some(1) ; "restarts on slash")]
+    fn counts_page_objects(pdf: &[u8]) -> Option<u32> {
+        let mut counter = PageCounter::new();
+        counter.update(pdf);
+        counter.finish()
+    }
+
+    #[test]
+    fn counts_page_objects_split_across_chunks() {
+        let mut counter = PageCounter::new();
+        for chunk in b"<< /Type /Page >> << /Type /Page >>".chunks(3) {
+            counter.update(chunk);
+        }
+        assert_eq!(counter.finish(), Some(2));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    blob_store::{BlobStore, UPLOAD_URL_VALID_MINUTES},
+    config::PurgeConfig,
+    db,
+};
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::fmt;
+use url::Url;
+
+/// What was permanently removed by `purge`, or would have been in a dry run.
+pub struct PurgeReport {
+    pub dry_run: bool,
+    pub files: usize,
+    pub folders: usize,
+    pub blobs: Vec<Url>,
+    pub uploads: Vec<Url>,
+    /// Blobs that could not be deleted, with the reason
+    pub failures: Vec<(Url, String)>,
+}
+
+/// Permanently deletes the files and folders that were deleted longer ago than the retention
+/// period, the blobs that only they used, and the uploads whose upload URL has expired.
+///
+/// The database is changed first, so a blob that cannot be deleted is left behind rather than a
+/// file version that points at a missing blob.
+pub async fn purge(
+    config: &PurgeConfig,
+    pool: &PgPool,
+    blob_store: &dyn BlobStore,
+) -> Result<PurgeReport> {
+    let now = Utc::now();
+    let deleted_before = now - Duration::days(config.retention_days.into());
+    let purged = db::PurgeRepo::purge_deleted(deleted_before, config.dry_run, pool).await?;
+    let blobs = purged
+        .blob_storage_paths
+        .iter()
+        .map(|path| Url::parse(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let uploads = blob_store
+        .find_uploads_modified_before(now - Duration::minutes(UPLOAD_URL_VALID_MINUTES))
+        .await?;
+
+    let mut failures = vec![];
+    if !config.dry_run {
+        db::UploadRepo::delete_created_before(
+            now - Duration::minutes(UPLOAD_URL_VALID_MINUTES),
+            pool,
+        )
+        .await?;
+        for blob in blobs.iter().chain(&uploads) {
+            if let Err(err) = blob_store.delete_blob(blob).await {
+                failures.push((blob.clone(), err.to_string()));
+            }
+        }
+    }
+
+    Ok(PurgeReport {
+        dry_run: config.dry_run,
+        files: purged.files.len(),
+        folders: purged.folders.len(),
+        blobs,
+        uploads,
+        failures,
+    })
+}
+
+impl fmt::Display for PurgeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = if self.dry_run {
+            "Would remove"
+        } else {
+            "Removed"
+        };
+        writeln!(
+            f,
+            "{} {} files, {} folders, {} file blobs and {} uploads",
+            verb,
+            self.files,
+            self.folders,
+            self.blobs.len(),
+            self.uploads.len()
+        )?;
+        for blob in self.blobs.iter().chain(&self.uploads) {
+            writeln!(f, "  {}", blob)?;
+        }
+        for (blob, err) in &self.failures {
+            writeln!(f, "Failed to remove {}: {}", blob, err)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn dry_run_report_says_what_would_be_removed() {
+        let report = PurgeReport {
+            dry_run: true,
+            files: 2,
+            folders: 1,
+            blobs: vec![Url::parse("http://127.0.0.1:10000/devstoreaccount1/files/a").unwrap()],
+            uploads: vec![],
+            failures: vec![],
+        };
+
+        assert_eq!(
+            report.to_string(),
+            "Would remove 2 files, 1 folders, 1 file blobs and 0 uploads\n  \
+             http://127.0.0.1:10000/devstoreaccount1/files/a\n"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+mod signature;
+
+use crate::blob_store::{
+    BlobProperties, BlobReader, BlobStore, DOWNLOAD_URL_VALID_MINUTES, UPLOAD_URL_VALID_MINUTES,
+};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use signature::{encode_query, Credentials, EMPTY_PAYLOAD_HASH};
+use surf::http::Method;
+use url::Url;
+use uuid::Uuid;
+
+lazy_static! {
+    static ref CONTENTS: Regex = Regex::new(r"(?s)<Contents>(.*?)</Contents>").unwrap();
+    static ref KEY: Regex = Regex::new(r"<Key>([^<]*)</Key>").unwrap();
+    static ref LAST_MODIFIED: Regex = Regex::new(r"<LastModified>([^<]*)</LastModified>").unwrap();
+    static ref NEXT_CONTINUATION_TOKEN: Regex =
+        Regex::new(r"<NextContinuationToken>([^<]*)</NextContinuationToken>").unwrap();
+}
+
+/// An S3-compatible object store, e.g. Amazon S3 or MinIO. Buckets are given by URL, either
+/// path-style (`http://localhost:9000/upload`) or virtual-hosted-style
+/// (`https://upload.s3.eu-west-2.amazonaws.com`).
+#[derive(Debug, Clone)]
+pub struct Config {
+    credentials: Credentials,
+    pub upload_bucket: String,
+    pub upload_bucket_url: Url,
+    pub files_bucket: String,
+    pub files_bucket_url: Url,
+}
+
+impl Config {
+    pub fn new(
+        access_key_id: String,
+        secret_access_key: String,
+        region: String,
+        upload_bucket_url: Url,
+        files_bucket_url: Url,
+    ) -> Result<Self> {
+        Ok(Self {
+            credentials: Credentials {
+                access_key_id,
+                secret_access_key,
+                region,
+            },
+            upload_bucket: bucket_name(&upload_bucket_url)?,
+            upload_bucket_url,
+            files_bucket: bucket_name(&files_bucket_url)?,
+            files_bucket_url,
+        })
+    }
+
+    /// Returns the key of the object at the URL if it is in the bucket, ignoring any query.
+    fn key(bucket_url: &Url, url: &Url) -> Option<String> {
+        if url.origin() != bucket_url.origin() {
+            return None;
+        }
+        url.path()
+            .strip_prefix(bucket_url.path().trim_end_matches('/'))
+            .and_then(|path| path.strip_prefix('/'))
+            .filter(|key| !key.is_empty())
+            .map(str::to_owned)
+    }
+
+    /// Sends a request with an empty body, signed with the credentials, and returns the
+    /// response and its body.
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        extra_headers: Vec<(String, String)>,
+    ) -> Result<(surf::Response, Vec<u8>)> {
+        let request = format!("{} {}", method, url);
+        let headers = self.credentials.sign_request(
+            method.as_ref(),
+            &url,
+            extra_headers,
+            EMPTY_PAYLOAD_HASH,
+            Utc::now(),
+        );
+
+        let mut builder = surf::Request::builder(method, url);
+        // The HTTP client sets the host header itself.
+        for (name, value) in headers.into_iter().filter(|(name, _)| name != "host") {
+            builder = builder.header(name.as_str(), value);
+        }
+        let mut response = surf::client()
+            .send(builder.build())
+            .await
+            .map_err(|err| err.into_inner())?;
+        let body = response
+            .body_bytes()
+            .await
+            .map_err(|err| err.into_inner())?;
+
+        if !response.status().is_success() {
+            bail!(
+                "{} failed with {}: {}",
+                request,
+                response.status(),
+                String::from_utf8_lossy(&body)
+            );
+        }
+        Ok((response, body))
+    }
+}
+
+/// The bucket is the first segment of the path of a path-style URL, or the first label of the
+/// host name of a virtual-hosted-style URL.
+fn bucket_name(bucket_url: &Url) -> Result<String> {
+    let first_segment = bucket_url
+        .path_segments()
+        .and_then(|mut segments| segments.next())
+        .filter(|segment| !segment.is_empty());
+    let bucket = match first_segment {
+        Some(segment) => segment,
+        None => bucket_url
+            .host_str()
+            .and_then(|host| host.split('.').next())
+            .ok_or_else(|| anyhow!("cannot get bucket name from url"))?,
+    };
+    Ok(bucket.to_owned())
+}
+
+fn object_url(bucket_url: &Url, key: &str) -> Url {
+    let mut url = bucket_url.clone();
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.pop_if_empty().push(key);
+    }
+    url
+}
+
+#[async_trait::async_trait]
+impl BlobStore for Config {
+    fn create_upload_url(&self, name: &Uuid) -> Result<Url> {
+        Ok(self.credentials.presign(
+            "PUT",
+            &object_url(&self.upload_bucket_url, &name.to_string()),
+            Duration::minutes(UPLOAD_URL_VALID_MINUTES),
+            Utc::now(),
+        ))
+    }
+
+    fn create_download_url(&self, url: &Url) -> Result<Url> {
+        let key = Self::key(&self.files_bucket_url, url)
+            .ok_or_else(|| anyhow!("file is from an unsupported bucket"))?;
+        Ok(self.credentials.presign(
+            "GET",
+            &object_url(&self.files_bucket_url, &key),
+            Duration::minutes(DOWNLOAD_URL_VALID_MINUTES),
+            Utc::now(),
+        ))
+    }
+
+    async fn upload_properties(&self, url: &Url) -> Result<BlobProperties> {
+        let key = Self::key(&self.upload_bucket_url, url)
+            .ok_or_else(|| anyhow!("upload is in an unsupported bucket"))?;
+
+        let (response, _) = self
+            .send(
+                Method::Head,
+                object_url(&self.upload_bucket_url, &key),
+                vec![],
+            )
+            .await?;
+
+        Ok(BlobProperties {
+            size: response
+                .header("content-length")
+                .ok_or_else(|| anyhow!("upload has no content length"))?
+                .as_str()
+                .parse()?,
+            content_type: response
+                .header("content-type")
+                .map(|content_type| content_type.as_str().to_owned()),
+        })
+    }
+
+    async fn open_file(&self, url: &Url) -> Result<BlobReader> {
+        let key = Self::key(&self.files_bucket_url, url)
+            .ok_or_else(|| anyhow!("file is in an unsupported bucket"))?;
+
+        // `send` reads the whole body, so stream the object from a presigned URL instead.
+        let response = surf::get(self.credentials.presign(
+            "GET",
+            &object_url(&self.files_bucket_url, &key),
+            Duration::minutes(DOWNLOAD_URL_VALID_MINUTES),
+            Utc::now(),
+        ))
+        .await
+        .map_err(|err| err.into_inner())?;
+        if !response.status().is_success() {
+            bail!("GET {} failed with {}", key, response.status());
+        }
+
+        Ok(Box::new(response))
+    }
+
+    async fn copy_upload(&self, url: &Url) -> Result<String> {
+        let key = Self::key(&self.upload_bucket_url, url)
+            .ok_or_else(|| anyhow!("source file is from an unsupported bucket"))?;
+        let destination = object_url(&self.files_bucket_url, &key);
+
+        let (_, body) = self
+            .send(
+                Method::Put,
+                destination.clone(),
+                vec![(
+                    "x-amz-copy-source".into(),
+                    format!("/{}/{}", self.upload_bucket, key),
+                )],
+            )
+            .await?;
+        // Copy Object can fail after it responded with 200 OK, in which case the error is in the
+        // body.
+        let body = String::from_utf8(body)?;
+        if body.contains("<Error>") {
+            bail!("copying {} failed: {}", key, body);
+        }
+
+        Ok(destination.to_string())
+    }
+
+    async fn delete_blob(&self, url: &Url) -> Result<()> {
+        if Self::key(&self.upload_bucket_url, url).is_none()
+            && Self::key(&self.files_bucket_url, url).is_none()
+        {
+            bail!("blob is in an unsupported bucket");
+        }
+        let mut url = url.clone();
+        url.set_query(None);
+
+        self.send(Method::Delete, url, vec![]).await?;
+        Ok(())
+    }
+
+    async fn find_uploads_modified_before(&self, before: DateTime<Utc>) -> Result<Vec<Url>> {
+        let mut uploads = vec![];
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![("list-type", "2".to_owned())];
+            if let Some(continuation_token) = continuation_token {
+                query.push(("continuation-token", continuation_token));
+            }
+            let mut url = self.upload_bucket_url.clone();
+            url.set_query(Some(&encode_query(&query)));
+            let (_, body) = self.send(Method::Get, url, vec![]).await?;
+            let body = String::from_utf8(body)?;
+
+            for object in CONTENTS.captures_iter(&body) {
+                let key = KEY
+                    .captures(&object[1])
+                    .ok_or_else(|| anyhow!("object has no key"))?;
+                let last_modified: DateTime<Utc> = LAST_MODIFIED
+                    .captures(&object[1])
+                    .ok_or_else(|| anyhow!("object has no last modified date"))?[1]
+                    .parse()?;
+                if last_modified < before {
+                    uploads.push(object_url(&self.upload_bucket_url, &key[1]));
+                }
+            }
+
+            continuation_token = NEXT_CONTINUATION_TOKEN
+                .captures(&body)
+                .map(|captures| captures[1].to_owned());
+            if continuation_token.is_none() {
+                return Ok(uploads);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn config() -> Config {
+        Config::new(
+            "minioadmin".into(),
+            "minioadmin".into(),
+            "us-east-1".into(),
+            Url::parse("http://localhost:9000/upload").unwrap(),
+            Url::parse("http://localhost:9000/files").unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn bucket_name_from_path_or_host() {
+        assert_eq!(
+            bucket_name(&Url::parse("http://localhost:9000/upload").unwrap()).unwrap(),
+            "upload"
+        );
+        assert_eq!(
+            bucket_name(&Url::parse("https://files.s3.eu-west-2.amazonaws.com").unwrap()).unwrap(),
+            "files"
+        );
+    }
+
+    #[test]
+    fn key_is_only_returned_for_objects_in_the_bucket() {
+        let config = config();
+        let url = Url::parse("http://localhost:9000/upload/my-object?X-Amz-Signature=abc").unwrap();
+
+        assert_eq!(
+            Config::key(&config.upload_bucket_url, &url).as_deref(),
+            Some("my-object")
+        );
+        assert_eq!(Config::key(&config.files_bucket_url, &url), None);
+        assert_eq!(
+            Config::key(
+                &config.upload_bucket_url,
+                &Url::parse("http://localhost:9000/uploads/my-object").unwrap()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn upload_url_is_presigned_for_put() {
+        let name = Uuid::new_v4();
+
+        let url = config().create_upload_url(&name).unwrap();
+
+        assert_eq!(url.path(), format!("/upload/{}", name));
+        assert!(url
+            .query_pairs()
+            .any(|(key, value)| key == "X-Amz-Expires" && value == "900"));
+        assert!(url.query_pairs().any(|(key, _)| key == "X-Amz-Signature"));
+    }
+
+    #[test]
+    fn download_url_is_only_created_for_files() {
+        let config = config();
+
+        assert!(config
+            .create_download_url(&Url::parse("http://localhost:9000/files/a").unwrap())
+            .is_ok());
+        assert!(config
+            .create_download_url(&Url::parse("http://localhost:9000/upload/a").unwrap())
+            .is_err());
+    }
+}
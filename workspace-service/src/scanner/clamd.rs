@@ -0,0 +1,178 @@
+use super::{ScanResult, Scanner};
+use crate::blob_store::BlobReader;
+use anyhow::{anyhow, bail, Result};
+use async_std::{
+    io::{Read, Write},
+    net::TcpStream,
+    os::unix::net::UnixStream,
+    prelude::*,
+};
+
+/// How much of a file is sent to clamd in each chunk of an `INSTREAM` command.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Scans files with a ClamAV daemon, using the [`INSTREAM` command] so that it does not need
+/// access to the files itself.
+///
+/// clamd rejects files larger than its `StreamMaxLength`, which should be at least the maximum
+/// upload size.
+///
+/// [`INSTREAM` command]: https://linux.die.net/man/8/clamd
+#[derive(Debug, Clone)]
+pub struct ClamdScanner {
+    /// `host:port` for a TCP socket, or the path of a unix socket
+    address: String,
+}
+
+impl ClamdScanner {
+    pub fn new(address: String) -> Self {
+        Self { address }
+    }
+}
+
+#[async_trait::async_trait]
+impl Scanner for ClamdScanner {
+    async fn scan(&self, contents: &mut BlobReader) -> Result<ScanResult> {
+        let response = if self.address.starts_with('/') {
+            instream(UnixStream::connect(&self.address).await?, contents).await?
+        } else {
+            instream(TcpStream::connect(&self.address).await?, contents).await?
+        };
+        parse_response(&response)
+    }
+}
+
+/// Sends the contents to clamd in length-prefixed chunks, ending with an empty chunk, and
+/// returns the response.
+async fn instream<S>(mut stream: S, contents: &mut BlobReader) -> Result<String>
+where
+    S: Read + Write + Unpin,
+{
+    // The `z` prefix means that the command and the response are terminated by a null byte.
+    stream.write_all(b"zINSTREAM\0").await?;
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let len = contents.read(&mut chunk).await?;
+        stream.write_all(&(len as u32).to_be_bytes()).await?;
+        if len == 0 {
+            break;
+        }
+        // clamd responds and closes the connection as soon as the file is too large, so a
+        // failed write may still have a response to read.
+        if let Err(err) = stream.write_all(&chunk[..len]).await {
+            return match read_response(&mut stream).await {
+                Ok(response) if !response.is_empty() => Ok(response),
+                _ => Err(err.into()),
+            };
+        }
+    }
+    stream.flush().await?;
+
+    read_response(&mut stream).await
+}
+
+async fn read_response<S>(stream: &mut S) -> Result<String>
+where
+    S: Read + Unpin,
+{
+    let mut response = vec![];
+    stream.read_to_end(&mut response).await?;
+    Ok(String::from_utf8(response)?
+        .trim_end_matches(&['\0', '\n'][..])
+        .to_owned())
+}
+
+/// Parses `stream: OK`, `stream: <signature> FOUND` or `<message> ERROR`.
+fn parse_response(response: &str) -> Result<ScanResult> {
+    if response.ends_with(" ERROR") {
+        bail!("clamd failed to scan the file: {}", response);
+    }
+    let result = response
+        .strip_prefix("stream: ")
+        .ok_or_else(|| anyhow!("unexpected response from clamd: {}", response))?;
+    if result == "OK" {
+        return Ok(ScanResult::Clean);
+    }
+    match result.strip_suffix(" FOUND") {
+        Some(signature) => Ok(ScanResult::Infected(signature.to_owned())),
+        None => bail!("unexpected response from clamd: {}", response),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use async_std::net::TcpListener;
+
+    /// Accepts a single `INSTREAM` command, and responds with `response` if it received
+    /// `expected`.
+    async fn fake_clamd(expected: &'static [u8], response: &'static str) -> Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?.to_string();
+        async_std::task::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut command = [0; 10];
+            stream.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+            let mut received = vec![];
+            loop {
+                let mut len = [0; 4];
+                stream.read_exact(&mut len).await.unwrap();
+                let len = u32::from_be_bytes(len) as usize;
+                if len == 0 {
+                    break;
+                }
+                let mut chunk = vec![0; len];
+                stream.read_exact(&mut chunk).await.unwrap();
+                received.extend(chunk);
+            }
+            let response = if received == expected {
+                response
+            } else {
+                "unexpected contents ERROR"
+            };
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.write_all(b"\0").await.unwrap();
+        });
+        Ok(address)
+    }
+
+    fn reader(contents: &'static [u8]) -> BlobReader {
+        Box::new(async_std::io::Cursor::new(contents))
+    }
+
+    #[async_std::test]
+    async fn scan_sends_the_contents_and_parses_a_clean_result() -> Result<()> {
+        let address = fake_clamd(b"hello world", "stream: OK").await?;
+
+        let result = ClamdScanner::new(address)
+            .scan(&mut reader(b"hello world"))
+            .await?;
+
+        assert_eq!(result, ScanResult::Clean);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn scan_returns_the_signature_of_malware() -> Result<()> {
+        let address = fake_clamd(b"X5O!P%@AP", "stream: Eicar-Signature FOUND").await?;
+
+        let result = ClamdScanner::new(address)
+            .scan(&mut reader(b"X5O!P%@AP"))
+            .await?;
+
+        assert_eq!(result, ScanResult::Infected("Eicar-Signature".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn errors_are_not_scan_results() {
+        assert_eq!(
+            parse_response("INSTREAM size limit exceeded. ERROR")
+                .unwrap_err()
+                .to_string(),
+            "clamd failed to scan the file: INSTREAM size limit exceeded. ERROR"
+        );
+        assert!(parse_response("PONG").is_err());
+    }
+}
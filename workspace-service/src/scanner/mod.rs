@@ -0,0 +1,280 @@
+mod clamd;
+
+use crate::{
+    blob_store::{BlobReader, BlobStore},
+    config::{Config, ScannerKind},
+    db,
+};
+use anyhow::Result;
+pub use clamd::ClamdScanner;
+use fnhs_event_models::{
+    Event, EventClient, EventPublisher as _, FileScanFailedData, FileScanPassedData,
+};
+use sqlx::PgPool;
+use std::{sync::Arc, time::Duration};
+
+/// How many blobs `scan_pending` scans at a time.
+const SCAN_BATCH_SIZE: i64 = 10;
+
+/// How many times `scan_pending` tries to scan a blob before it gives up and marks its versions
+/// as failed.
+const MAX_SCAN_ATTEMPTS: i32 = 5;
+
+/// What a scanner found in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScanResult {
+    Clean,
+    /// The file contains malware, with the name of its signature
+    Infected(String),
+}
+
+/// Scans files for malware. Every new file version is quarantined until it has been scanned,
+/// and it can only be downloaded if it is clean.
+#[async_trait::async_trait]
+pub trait Scanner: Send + Sync {
+    async fn scan(&self, contents: &mut BlobReader) -> Result<ScanResult>;
+}
+
+/// Treats every file as clean without scanning it, for local development without a ClamAV
+/// daemon.
+pub struct NoScanner;
+
+#[async_trait::async_trait]
+impl Scanner for NoScanner {
+    async fn scan(&self, _contents: &mut BlobReader) -> Result<ScanResult> {
+        Ok(ScanResult::Clean)
+    }
+}
+
+/// Creates the scanner selected with `--scanner`.
+pub fn from_config(config: &Config) -> Arc<dyn Scanner> {
+    match config.scanner {
+        ScannerKind::Clamd => Arc::new(ClamdScanner::new(config.clamd_address.clone())),
+        ScannerKind::None => Arc::new(NoScanner),
+    }
+}
+
+/// Scans the blobs of the file versions that are waiting to be scanned, sets the scan status of
+/// those versions, and publishes a `FileScanPassed` or `FileScanFailed` event for each of them.
+///
+/// A blob that cannot be scanned stays pending, and is tried again after a delay that doubles
+/// every time, so that it does not hold up newer blobs. After `MAX_SCAN_ATTEMPTS` attempts its
+/// versions are marked as failed, and they can never be downloaded. Returns how many blobs were
+/// scanned.
+pub async fn scan_pending(
+    pool: &PgPool,
+    blob_store: &dyn BlobStore,
+    scanner: &dyn Scanner,
+    event_client: &EventClient,
+) -> Result<usize> {
+    let blobs = db::FileVersionRepo::find_blobs_pending_scan(SCAN_BATCH_SIZE, pool).await?;
+    let mut scanned = 0;
+    for blob in blobs {
+        match scan_blob(&blob, pool, blob_store, scanner, event_client).await {
+            Ok(()) => scanned += 1,
+            Err(err) => {
+                tracing::error!("scanning {} failed: {:?}", blob, err);
+                match db::FileVersionRepo::record_scan_failure(&blob, MAX_SCAN_ATTEMPTS, pool).await
+                {
+                    Ok(true) => tracing::error!(
+                        "gave up scanning {} after {} attempts",
+                        blob,
+                        MAX_SCAN_ATTEMPTS
+                    ),
+                    Ok(false) => {}
+                    Err(err) => {
+                        tracing::error!("recording the failed scan of {} failed: {:?}", blob, err)
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(scanned)
+}
+
+async fn scan_blob(
+    blob_storage_path: &str,
+    pool: &PgPool,
+    blob_store: &dyn BlobStore,
+    scanner: &dyn Scanner,
+    event_client: &EventClient,
+) -> Result<()> {
+    let mut contents = blob_store.open_file(&blob_storage_path.parse()?).await?;
+    let result = scanner.scan(&mut contents).await?;
+    let scan_status = match result {
+        ScanResult::Clean => db::ScanStatus::Clean,
+        ScanResult::Infected(_) => db::ScanStatus::Infected,
+    };
+
+    let versions =
+        db::FileVersionRepo::update_scan_status(blob_storage_path, scan_status, pool).await?;
+    let events: Vec<Event> = versions
+        .into_iter()
+        .map(|version| match &result {
+            ScanResult::Clean => Event::new(
+                version.file.to_string(),
+                FileScanPassedData {
+                    file_id: version.file.to_string(),
+                    version_id: version.id.to_string(),
+                    version_number: version.version_number.into(),
+                    workspace_id: version.workspace.to_string(),
+                },
+            ),
+            ScanResult::Infected(signature) => Event::new(
+                version.file.to_string(),
+                FileScanFailedData {
+                    file_id: version.file.to_string(),
+                    signature: signature.clone(),
+                    version_id: version.id.to_string(),
+                    version_number: version.version_number.into(),
+                    workspace_id: version.workspace.to_string(),
+                },
+            ),
+        })
+        .collect();
+    if !events.is_empty() {
+        event_client.publish_events(&events).await?;
+    }
+
+    Ok(())
+}
+
+/// Scans new file versions until the service stops, looking for them every `interval`.
+pub async fn run(
+    pool: PgPool,
+    blob_store: Arc<dyn BlobStore>,
+    scanner: Arc<dyn Scanner>,
+    event_client: EventClient,
+    interval: Duration,
+) {
+    loop {
+        match scan_pending(&pool, blob_store.as_ref(), scanner.as_ref(), &event_client).await {
+            // There may be more waiting, so carry on without waiting.
+            Ok(scanned) if scanned as i64 == SCAN_BATCH_SIZE => continue,
+            Ok(_) => {}
+            Err(err) => tracing::error!("finding files to scan failed: {:?}", err),
+        }
+        async_std::task::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{azure, graphql::test_mocks::*};
+    use async_std::prelude::*;
+    use fnhs_event_models::EventData;
+    use url::Url;
+    use uuid::Uuid;
+
+    /// Finds malware in files that contain the word "virus", and fails to scan files that
+    /// contain the word "unscannable".
+    struct FakeScanner;
+
+    #[async_trait::async_trait]
+    impl Scanner for FakeScanner {
+        async fn scan(&self, contents: &mut BlobReader) -> Result<ScanResult> {
+            let mut text = String::new();
+            contents.read_to_string(&mut text).await?;
+            if text.contains("unscannable") {
+                anyhow::bail!("fake scan failure")
+            } else if text.contains("virus") {
+                Ok(ScanResult::Infected("Fake-Virus".into()))
+            } else {
+                Ok(ScanResult::Clean)
+            }
+        }
+    }
+
+    async fn mock_pending_version(contents: &[u8]) -> Result<db::FileVersion> {
+        let pool = mock_connection_pool()?;
+        let folder = mock_folder("WORKSPACE_MEMBER").await?;
+        let blob_storage_path = format!(
+            "http://localhost:10000/devstoreaccount1/files/{}",
+            Uuid::new_v4()
+        );
+        azure::put_fake_file(&Url::parse(&blob_storage_path)?, contents);
+        db::FileVersionRepo::create(
+            Uuid::new_v4(),
+            folder.id,
+            Uuid::new_v4(),
+            "fake file",
+            "",
+            "fake.txt",
+            "text/plain",
+            &blob_storage_path,
+            Uuid::new_v4(),
+            1,
+            "",
+            db::ScanStatus::Pending,
+            None,
+            None,
+            None,
+            &pool,
+        )
+        .await
+    }
+
+    #[async_std::test]
+    async fn scan_pending_marks_versions_clean_or_infected() -> Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let (events, event_client) = mock_event_emitter();
+        let clean = mock_pending_version(b"a letter to a patient").await?;
+        let infected = mock_pending_version(b"a virus").await?;
+
+        scan_pending(&pool, &azure_config, &FakeScanner, &event_client).await?;
+
+        let clean = db::FileVersionRepo::find_by_id(clean.id, &pool).await?;
+        let infected = db::FileVersionRepo::find_by_id(infected.id, &pool).await?;
+        assert_eq!(clean.scan_status, "CLEAN");
+        assert_eq!(infected.scan_status, "INFECTED");
+        let events: Vec<_> = events.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            &e.data,
+            EventData::FileScanPassed(data) if data.version_id == clean.id.to_string()
+        )));
+        assert!(events.iter().any(|e| matches!(
+            &e.data,
+            EventData::FileScanFailed(data)
+                if data.version_id == infected.id.to_string() && data.signature == "Fake-Virus"
+        )));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn scan_pending_gets_past_blobs_that_cannot_be_scanned() -> Result<()> {
+        let pool = mock_connection_pool()?;
+        let azure_config = mock_azure_config()?;
+        let (_, event_client) = mock_event_emitter();
+        let mut unscannable = vec![];
+        for _ in 0..SCAN_BATCH_SIZE {
+            unscannable.push(mock_pending_version(b"an unscannable file").await?);
+        }
+        let newer = mock_pending_version(b"a letter to a patient").await?;
+
+        // Other tests add pending versions too, but each call either scans a blob or puts it
+        // back until later, so the newer one is reached after a few calls.
+        for _ in 0..10 {
+            scan_pending(&pool, &azure_config, &FakeScanner, &event_client).await?;
+            if db::FileVersionRepo::find_by_id(newer.id, &pool)
+                .await?
+                .scan_status
+                != "PENDING"
+            {
+                break;
+            }
+        }
+
+        let newer = db::FileVersionRepo::find_by_id(newer.id, &pool).await?;
+        assert_eq!(newer.scan_status, "CLEAN");
+        for version in unscannable {
+            let version = db::FileVersionRepo::find_by_id(version.id, &pool).await?;
+            assert_eq!(version.scan_status, "PENDING");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+//! Detects what a file is from its first bytes (its "magic number"), so that a file cannot be
+//! accepted as a type that its contents are not, e.g. an executable renamed to `.pdf`.
+
+/// How many bytes from the start of a file `detect` looks at.
+pub const SNIFF_LEN: usize = 512;
+
+/// A binary format that can be recognised by its first bytes.
+#[derive(Debug, PartialEq)]
+pub struct Format {
+    /// A description of the format for error messages and events
+    pub name: &'static str,
+    /// The MIME types that files of this format can be declared as
+    mime_types: &'static [&'static str],
+    /// A file is of this format if it has all the bytes of any one of the signatures at their
+    /// offsets
+    signatures: &'static [&'static [(usize, &'static [u8])]],
+}
+
+impl Format {
+    fn matches(&self, prefix: &[u8]) -> bool {
+        self.signatures.iter().any(|signature| {
+            signature
+                .iter()
+                .all(|(offset, bytes)| prefix.get(*offset..*offset + bytes.len()) == Some(*bytes))
+        })
+    }
+}
+
+/// The formats we know. The ones without MIME types can never be uploaded, but are recognised
+/// so that we can say what was uploaded instead. As that only matters if the file is not text,
+/// their signatures can be short.
+const FORMATS: &[Format] = &[
+    Format {
+        name: "BMP image",
+        mime_types: &["image/bmp", "image/x-ms-bmp"],
+        signatures: &[&[(0, b"BM"), (6, b"\0\0\0\0")]],
+    },
+    Format {
+        name: "GIF image",
+        mime_types: &["image/gif"],
+        signatures: &[&[(0, b"GIF87a")], &[(0, b"GIF89a")]],
+    },
+    Format {
+        name: "JPEG image",
+        mime_types: &["image/jpeg"],
+        signatures: &[&[(0, b"\xFF\xD8\xFF")]],
+    },
+    Format {
+        name: "PNG image",
+        mime_types: &["image/png"],
+        signatures: &[&[(0, b"\x89PNG\r\n\x1A\n")]],
+    },
+    Format {
+        name: "WebP image",
+        mime_types: &["image/webp"],
+        signatures: &[&[(0, b"RIFF"), (8, b"WEBP")]],
+    },
+    Format {
+        name: "PDF document",
+        mime_types: &["application/pdf"],
+        signatures: &[&[(0, b"%PDF-")]],
+    },
+    Format {
+        name: "PostScript document",
+        mime_types: &["application/postscript"],
+        signatures: &[&[(0, b"%!PS")], &[(0, b"\xC5\xD0\xD3\xC6")]],
+    },
+    Format {
+        name: "QuickTime video",
+        mime_types: &["video/quicktime"],
+        signatures: &[
+            &[(4, b"ftyp")],
+            &[(4, b"moov")],
+            &[(4, b"mdat")],
+            &[(4, b"wide")],
+            &[(4, b"free")],
+        ],
+    },
+    Format {
+        name: "Microsoft Office 97-2003 document",
+        mime_types: &[
+            "application/msword",
+            "application/vnd.ms-excel",
+            "application/vnd.ms-powerpoint",
+        ],
+        signatures: &[&[(0, b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1")]],
+    },
+    // Office Open XML and OpenDocument files are ZIP archives. We cannot tell them apart from
+    // the first bytes.
+    Format {
+        name: "ZIP archive",
+        mime_types: &[
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+            "application/vnd.oasis.opendocument.text",
+            "application/vnd.oasis.opendocument.spreadsheet",
+            "application/vnd.oasis.opendocument.presentation",
+        ],
+        signatures: &[&[(0, b"PK\x03\x04")]],
+    },
+    Format {
+        name: "Windows executable",
+        mime_types: &[],
+        signatures: &[&[(0, b"MZ")]],
+    },
+    Format {
+        name: "ELF executable",
+        mime_types: &[],
+        signatures: &[&[(0, b"\x7FELF")]],
+    },
+    Format {
+        name: "Mach-O executable",
+        mime_types: &[],
+        signatures: &[
+            &[(0, b"\xFE\xED\xFA\xCE")],
+            &[(0, b"\xFE\xED\xFA\xCF")],
+            &[(0, b"\xCE\xFA\xED\xFE")],
+            &[(0, b"\xCF\xFA\xED\xFE")],
+        ],
+    },
+];
+
+/// The text types we accept. Text has no magic number, so anything that looks like text is
+/// accepted as any of them.
+const TEXT_MIME_TYPES: &[&str] = &["text/csv", "text/plain", "image/svg+xml"];
+
+/// What the first bytes of a file look like.
+#[derive(Debug, PartialEq)]
+pub enum Detected {
+    Format(&'static Format),
+    Text,
+    Unknown,
+}
+
+impl Detected {
+    /// A description of what was detected for error messages and events.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Detected::Format(format) => format.name,
+            Detected::Text => "text",
+            Detected::Unknown => "unknown binary data",
+        }
+    }
+
+    /// Whether a file that looks like this can be declared as the MIME type.
+    pub fn is_compatible_with(&self, mime_type: &str) -> bool {
+        match self {
+            Detected::Format(format) => format.mime_types.contains(&mime_type),
+            Detected::Text => TEXT_MIME_TYPES.contains(&mime_type),
+            Detected::Unknown => false,
+        }
+    }
+}
+
+/// Detects what a file is from up to `SNIFF_LEN` bytes from its start.
+pub fn detect(prefix: &[u8]) -> Detected {
+    match FORMATS.iter().find(|format| format.matches(prefix)) {
+        Some(format) if !format.mime_types.is_empty() => Detected::Format(format),
+        _ if is_text(prefix) => Detected::Text,
+        Some(format) => Detected::Format(format),
+        None => Detected::Unknown,
+    }
+}
+
+/// Text is valid UTF-8 without control characters other than whitespace. The prefix may end in
+/// the middle of a character.
+fn is_text(prefix: &[u8]) -> bool {
+    let text = match std::str::from_utf8(prefix) {
+        Ok(text) => text,
+        Err(err) if err.error_len().is_none() => {
+            std::str::from_utf8(&prefix[..err.valid_up_to()]).expect("valid up to here")
+        }
+        Err(_) => return false,
+    };
+    !text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0C'))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_case::test_case;
+
+    #[test_case(b"%PDF-1.7\n%\xE2\xE3\xCF\xD3", "application/pdf" ; "pdf")]
+    #[test_case(b"\x89PNG\r\n\x1A\n\0\0\0\rIHDR", "image/png" ; "png")]
+    #[test_case(b"RIFF\x24\0\0\0WEBPVP8 ", "image/webp" ; "webp")]
+    #[test_case(b"\0\0\0\x14ftypqt  ", "video/quicktime" ; "mov")]
+    #[test_case(b"PK\x03\x04\x14\0\x06\0", "application/vnd.openxmlformats-officedocument.wordprocessingml.document" ; "docx")]
+    #[test_case(b"PK\x03\x04\x14\0\0\x08", "application/vnd.oasis.opendocument.spreadsheet" ; "ods")]
+    #[test_case(b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1\0\0", "application/vnd.ms-excel" ; "xls")]
+    #[test_case(b"name,email\r\nAlice,alice@example.com\r\n", "text/csv" ; "csv")]
+    #[test_case("Caf\u{e9} \u{2615}".as_bytes(), "text/plain" ; "unicode text")]
+    #[test_case(&"\u{2615}".as_bytes()[..2], "text/plain" ; "text cut off in a character")]
+    #[test_case(b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>", "image/svg+xml" ; "svg")]
+    #[test_case(b"", "text/plain" ; "empty file")]
+    #[test_case(b"MZ is not always an executable", "text/plain" ; "text like executable")]
+    #[test_case(b"BM\x36\x10\x0E\0\0\0\0\0\x36\0", "image/bmp" ; "bmp")]
+    fn accepts_matching_content(prefix: &[u8], mime_type: &str) {
+        assert!(detect(prefix).is_compatible_with(mime_type));
+    }
+
+    #[test_case(b"MZ\x90\0\x03\0\0\0", "application/pdf", "Windows executable" ; "exe as pdf")]
+    #[test_case(b"\x7FELF\x02\x01\x01\0", "text/plain", "ELF executable" ; "elf as text")]
+    #[test_case(b"GIF89a\x01\0\x01\0", "image/png", "GIF image" ; "gif as png")]
+    #[test_case(b"PK\x03\x04\x14\0\0\0", "application/msword", "ZIP archive" ; "zip as doc")]
+    #[test_case(b"%PDF-1.4\n", "text/plain", "PDF document" ; "pdf as text")]
+    #[test_case(b"hello world", "application/pdf", "text" ; "text as pdf")]
+    #[test_case(b"\0\x01\x02\x03garbage", "text/plain", "unknown binary data" ; "binary as text")]
+    fn rejects_mismatched_content(prefix: &[u8], mime_type: &str, detected: &str) {
+        let actual = detect(prefix);
+
+        assert!(!actual.is_compatible_with(mime_type));
+        assert_eq!(actual.name(), detected);
+    }
+}